@@ -1,17 +1,32 @@
 use ecolor::Color32;
 use egui::{Button, Checkbox};
-use egui_plot::{Legend, MarkerShape, Plot, PlotBounds, PlotResponse, Points};
+use egui_plot::{
+    Legend, Line, MarkerShape, Plot, PlotBounds, PlotPoint, PlotResponse, Points, Polygon,
+};
 
 use self::{
-    data_definition::{Data, DataLabel},
+    plot_export::{ImageSettings, MarkerSeries},
     plot_zoom_reset::StatePlotResetZoom,
-    status_msg::StatusMsg,
+    script_console::ScriptSettings,
+};
+use crate::dataset::{
+    format_timestamp, AspectMode, Augmentation, AxesSettings, AxisSettings, Data, DataLabel,
+    DataPoint, GridSnap, MinMaxPair, Mirror, PointMetadata, Projection, SnapMode, SnapSettings,
+    Split, SplitSettings, StatusMsg, TraceColumns, Transform, ZoomTarget,
 };
 
 mod data_conversion;
-mod data_definition;
+mod diff_view;
+mod eraser;
+#[cfg(not(target_arch = "wasm32"))]
+mod file_watch;
+mod files;
+mod marker_style;
+mod plot_export;
 mod plot_zoom_reset;
-mod status_msg;
+#[cfg(not(target_arch = "wasm32"))]
+mod remote_control;
+mod script_console;
 
 // TODO: Add option to show data as table
 // TODO: Support saving multiple version with just a single click, each just having a number appended to the name
@@ -25,10 +40,31 @@ pub struct ManualDataCreatorApp {
     marker_radius: f32,
     normal_color: Color32,
     anom_color: Color32,
+    /// Shape, size and look of the markers of each label
+    label_styles: marker_style::LabelStyles,
+    style_presets: Vec<marker_style::StylePreset>,
     data: Data,
     click_mode: ClickMode,
     primary_click_label: DataLabel,
     allow_boxed_zoom: bool,
+    /// Move smoothly to the new view when zooming to a target
+    is_zoom_animated: bool,
+    /// Settings last used in the augment window
+    augmentation: Augmentation,
+    /// Show the result of the augmentation on the plot while the augment window is open
+    should_preview_augmentation: bool,
+    /// Settings last used to randomly assign splits
+    split_settings: SplitSettings,
+    /// Draw an outline around points showing which split they are in
+    should_show_splits: bool,
+    /// Options used when exporting the plot as an image
+    image_settings: ImageSettings,
+    /// Last script written in the script console
+    script: ScriptSettings,
+    #[cfg(not(target_arch = "wasm32"))]
+    remote_control: remote_control::RemoteControlSettings,
+    #[cfg(not(target_arch = "wasm32"))]
+    watch_settings: file_watch::WatchSettings,
     #[serde(skip)]
     should_show_reset_all_button: bool,
     #[serde(skip)]
@@ -40,24 +76,94 @@ pub struct ManualDataCreatorApp {
     #[serde(skip)]
     plot_bounds: Option<PlotBounds>,
     #[serde(skip)]
+    /// Set on the plot in the next frame, used to keep the zoom when the points are replaced
+    restore_plot_bounds: Option<PlotBounds>,
+    #[serde(skip)]
     state_reset_plot_zoom: StatePlotResetZoom,
     #[serde(skip)]
     status_msg: StatusMsg,
+    #[serde(skip)]
+    /// Start and current position (plot space) of the area being dragged to select or label points
+    selection_drag: Option<(PlotPoint, PlotPoint)>,
+    #[serde(skip)]
+    /// Label to apply to the range or region being dragged in [`ClickMode::LabelRange`] or [`ClickMode::Relabel`]
+    range_drag_label: DataLabel,
+    #[serde(skip)]
+    /// Name to use for the next bookmark saved
+    bookmark_name: String,
+    #[serde(skip)]
+    /// Name to use for the next style preset saved
+    style_preset_name: String,
+    #[serde(skip)]
+    /// Metadata being edited (by point index) while the point details window is open
+    point_details_edits: Option<Vec<(usize, PointMetadata)>>,
+    #[serde(skip)]
+    is_augment_window_open: bool,
+    #[serde(skip)]
+    is_script_window_open: bool,
+    #[serde(skip)]
+    /// Printed by the last script run (or its error)
+    script_output: Vec<String>,
+    #[serde(skip)]
+    dropped_data: Option<files::DroppedData>,
+    #[serde(skip)]
+    /// Another version of the points being compared with the current points
+    diff_view: Option<diff_view::DiffView>,
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    pending_upload: files::PendingUpload,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    remote_server: Option<remote_control::RemoteServer>,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    watched_files: Option<file_watch::WatchedFiles>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, PartialEq)]
 enum ClickMode {
     AddPoints,
     DeletePoints,
+    /// Drag to delete every point within a radius of the pointer
+    Erase,
+    /// Click to change the label of the nearest point or drag to label every point in a region
+    Relabel,
+    Select,
+    /// Drag across the time axis to label a range of samples (time series mode only)
+    LabelRange,
 }
 
 impl ClickMode {
-    /// Returns `true` if the click mode is [`DeletePoints`].
+    /// Returns `true` if the click mode is [`Relabel`].
     ///
-    /// [`DeletePoints`]: ClickMode::DeletePoints
+    /// [`Relabel`]: ClickMode::Relabel
     #[must_use]
-    fn is_delete_points(&self) -> bool {
-        matches!(self, Self::DeletePoints)
+    fn is_relabel(&self) -> bool {
+        matches!(self, Self::Relabel)
+    }
+
+    /// Returns `true` if the click mode is [`Erase`].
+    ///
+    /// [`Erase`]: ClickMode::Erase
+    #[must_use]
+    fn is_erase(&self) -> bool {
+        matches!(self, Self::Erase)
+    }
+
+    /// Returns `true` if the click mode is [`Select`].
+    ///
+    /// [`Select`]: ClickMode::Select
+    #[must_use]
+    fn is_select(&self) -> bool {
+        matches!(self, Self::Select)
+    }
+
+    /// Returns `true` if the click mode is [`LabelRange`].
+    ///
+    /// [`LabelRange`]: ClickMode::LabelRange
+    #[must_use]
+    fn is_label_range(&self) -> bool {
+        matches!(self, Self::LabelRange)
     }
 }
 
@@ -67,22 +173,55 @@ impl Default for ManualDataCreatorApp {
             marker_radius: 8.0,
             normal_color: Color32::from_rgb(100, 150, 230),
             anom_color: Color32::from_rgb(200, 150, 70),
+            label_styles: Default::default(),
+            style_presets: vec![],
             data: Default::default(),
             click_mode: ClickMode::AddPoints,
             primary_click_label: DataLabel::Normal,
             allow_boxed_zoom: false,
+            is_zoom_animated: true,
+            augmentation: Default::default(),
+            should_preview_augmentation: true,
+            split_settings: Default::default(),
+            should_show_splits: true,
+            image_settings: Default::default(),
+            script: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            remote_control: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            watch_settings: Default::default(),
             should_show_reset_all_button: false,
             should_show_clear_history: false,
             is_changing_max_history_size: false,
             during_edit_max_history_size: None,
             plot_bounds: Default::default(),
+            restore_plot_bounds: None,
             state_reset_plot_zoom: Default::default(),
             status_msg: Default::default(),
+            selection_drag: None,
+            range_drag_label: DataLabel::Anomaly,
+            bookmark_name: Default::default(),
+            style_preset_name: Default::default(),
+            point_details_edits: None,
+            is_augment_window_open: false,
+            is_script_window_open: false,
+            script_output: vec![],
+            dropped_data: None,
+            diff_view: None,
+            #[cfg(target_arch = "wasm32")]
+            pending_upload: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            remote_server: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            watched_files: None,
         }
     }
 }
 
 impl ManualDataCreatorApp {
+    /// Distance in screen points that the pointer needs to be within to show the details of a point
+    const MIN_HOVER_DISTANCE: f32 = 5.0;
+
     /// Called once before the first frame.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // This is also where you can customize the look and feel of egui using
@@ -90,16 +229,23 @@ impl ManualDataCreatorApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        }
+        let mut result: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        result.data.migrate_rounding_to_snapping();
+
+        // A dataset in the URL replaces the saved points (undoably)
+        #[cfg(target_arch = "wasm32")]
+        result.load_share_link();
 
-        Default::default()
+        result
     }
 
     fn panel_top(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
         egui::menu::bar(ui, |ui| {
             ui.menu_button("File", |ui| {
+                self.file_menu(ui);
                 #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
                 if ui.button("Quit").clicked() {
                     _frame.close();
@@ -113,7 +259,14 @@ impl ManualDataCreatorApp {
             ui.collapsing("Instructions", |ui| {
                 ui.label("Primary click to add normal point (Usually left click)");
                 ui.label("Secondary click to add anomaly point (Usually right click)");
-                ui.label("Middle click to switch between adding and removing points");
+                ui.label("Middle click to switch between adding, removing, erasing, relabelling and selecting points");
+                ui.label("When erasing, drag to remove every point within the eraser radius (each drag is undone at once)");
+                ui.label("When relabelling, click to switch the label of the nearest point or drag to label every point in a region (each button uses the label it adds)");
+                ui.label("When selecting, drag to select points (+ shift = add to selection) and click to clear the selection");
+                if self.data.is_time_series() {
+                    ui.label("When labelling a range, primary drag across time to label anomalies and secondary drag to label normal");
+                }
+                ui.label("Drop a data file onto the window to load its points");
                 ui.label("Pan by dragging, or scroll (+ shift = horizontal).");
                 if self.allow_boxed_zoom {
                     ui.label("Box zooming: Right click to zoom in and zoom out using a selection.");
@@ -139,24 +292,44 @@ impl ManualDataCreatorApp {
                             .clamp_range(0.0..=f64::INFINITY)
                             .prefix("Point Display Radius: "),
                     );
-
-                    ui.separator();
-                    ui.label("Normal Points Color: ");
-                    ui.color_edit_button_srgba(&mut self.normal_color);
-
-                    ui.separator();
-                    ui.label("Anomaly Points Color: ");
-                    ui.color_edit_button_srgba(&mut self.anom_color);
                 });
+                ui.collapsing("Marker Style", |ui| self.marker_style_options(ui));
 
                 ui.separator();
-                let mut should_remove_on_click: bool = self.click_mode.is_delete_points();
-                ui.checkbox(&mut should_remove_on_click, "Should remove point on click");
-                self.click_mode = if should_remove_on_click {
-                    ClickMode::DeletePoints
-                } else {
-                    ClickMode::AddPoints
-                };
+                ui.horizontal(|ui| {
+                    ui.label("Click Mode: ");
+                    ui.radio_value(&mut self.click_mode, ClickMode::AddPoints, "Add");
+                    ui.radio_value(&mut self.click_mode, ClickMode::DeletePoints, "Delete");
+                    ui.radio_value(&mut self.click_mode, ClickMode::Erase, "Erase");
+                    ui.radio_value(&mut self.click_mode, ClickMode::Relabel, "Relabel");
+                    ui.radio_value(&mut self.click_mode, ClickMode::Select, "Select");
+                    if self.data.is_time_series() {
+                        ui.radio_value(&mut self.click_mode, ClickMode::LabelRange, "Label Range");
+                    }
+                });
+                let mut is_time_series = self.data.is_time_series();
+                if ui
+                    .checkbox(&mut is_time_series, "Time series mode")
+                    .on_hover_text("First dimension is time, points are kept sorted by time and joined by a line")
+                    .changed()
+                {
+                    self.data.set_time_series(is_time_series, &mut self.status_msg);
+                }
+                if !self.data.is_time_series() && self.click_mode.is_label_range() {
+                    self.click_mode = ClickMode::AddPoints;
+                }
+
+                ui.horizontal(|ui| {
+                    for label in [DataLabel::Normal, DataLabel::Anomaly] {
+                        let state = self.data.label_state_mut(label);
+                        ui.label(format!("{}: ", label.as_str()));
+                        ui.checkbox(&mut state.is_hidden, "Hide")
+                            .on_hover_text("Leaves the points off the plot and out of clicks and selections");
+                        ui.checkbox(&mut state.is_locked, "Lock")
+                            .on_hover_text("Prevents clicks from adding, deleting or relabelling the points");
+                        ui.separator();
+                    }
+                });
 
                 let mut should_swap_normal_on_click = self.primary_click_label.is_anomaly();
                 ui.checkbox(
@@ -169,27 +342,21 @@ impl ManualDataCreatorApp {
                     DataLabel::Normal
                 };
 
-                // Handle setting rounding of new points
-                ui.horizontal(|ui| {
-                    let mut is_rounding_new_points_enabled = self.data.is_rounding_enabled();
-                    ui.checkbox(
-                        &mut is_rounding_new_points_enabled,
-                        "Should round new points",
-                    );
-                    self.data
-                        .set_rounding_enabled(is_rounding_new_points_enabled);
-                    if is_rounding_new_points_enabled {
-                        ui.add(
-                            egui::DragValue::new(self.data.rounding_decimal_places_mut())
-                                .speed(1)
-                                .clamp_range(0..=Data::MAX_DECIMAL_PLACES)
-                                .prefix("Number of Decimal places: "),
-                        );
-                    }
-                });
+                self.snapping_options(ui);
+                ui.collapsing("Deleting and Erasing", |ui| self.erase_options(ui));
+                ui.collapsing("Axes", |ui| self.axes_options(ui));
+                ui.collapsing("Dimensions", |ui| self.dimensions_options(ui));
+                ui.collapsing("Reference Trace", |ui| self.reference_trace_options(ui));
+                ui.collapsing("Splits", |ui| self.split_options(ui));
+                ui.collapsing("Image Export", |ui| self.image_export_options(ui));
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.collapsing("File Watching", |ui| self.file_watch_options(ui));
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.collapsing("Remote Control", |ui| self.remote_control_options(ui));
 
                 ui.checkbox(&mut self.allow_boxed_zoom, "Allow boxed zoom")
                     .on_hover_text("When enabled, instructions include an explanation");
+                ui.checkbox(&mut self.is_zoom_animated, "Animate zooming to fit");
 
                 ui.horizontal(|ui| {
                     ui.checkbox(
@@ -210,6 +377,10 @@ impl ManualDataCreatorApp {
             match self.click_mode {
                 ClickMode::AddPoints => "ADD",
                 ClickMode::DeletePoints => "DELETE",
+                ClickMode::Erase => "ERASE",
+                ClickMode::Relabel => "RELABEL",
+                ClickMode::Select => "SELECT",
+                ClickMode::LabelRange => "LABEL RANGE",
             },
             if self.primary_click_label.is_normal() {
                 ""
@@ -220,6 +391,390 @@ impl ManualDataCreatorApp {
         self.undo_redo_controls(ui);
     }
 
+    fn snapping_options(&mut self, ui: &mut egui::Ui) {
+        let is_reference_trace_loaded = self.data.reference_trace().is_some();
+        let settings = self.data.snap_settings_mut();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.is_enabled, "Should snap new points");
+            if !settings.is_enabled {
+                return;
+            }
+            egui::ComboBox::from_id_source("snap_mode")
+                .selected_text(settings.mode.as_str())
+                .show_ui(ui, |ui| {
+                    for mode in SnapMode::ALL {
+                        ui.selectable_value(&mut settings.mode, mode, mode.as_str());
+                    }
+                });
+            if matches!(settings.mode, SnapMode::Grid | SnapMode::Polar) {
+                ui.checkbox(&mut settings.should_show_grid, "Show grid");
+            }
+        });
+        if !settings.is_enabled {
+            return;
+        }
+        ui.horizontal(|ui| match settings.mode {
+            SnapMode::Grid => {
+                let grid = &mut settings.grid;
+                ui.add(
+                    egui::DragValue::new(&mut grid.step[0])
+                        .speed(0.01)
+                        .clamp_range(0.0..=f64::INFINITY)
+                        .prefix("Step X: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut grid.step[1])
+                        .speed(0.01)
+                        .clamp_range(0.0..=f64::INFINITY)
+                        .prefix("Step Y: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut grid.origin[0])
+                        .speed(0.01)
+                        .prefix("Origin X: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut grid.origin[1])
+                        .speed(0.01)
+                        .prefix("Origin Y: "),
+                );
+                ui.separator();
+                // Shortcut to get the same behaviour as rounding to a number of decimal places
+                let mut decimal_places = grid.decimal_places().unwrap_or_default();
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut decimal_places)
+                            .speed(1)
+                            .clamp_range(0..=SnapSettings::MAX_DECIMAL_PLACES)
+                            .prefix("Steps from Decimal places: "),
+                    )
+                    .changed()
+                {
+                    grid.step = GridSnap::from_decimal_places(decimal_places).step;
+                }
+            }
+            SnapMode::Polar => {
+                let polar = &mut settings.polar;
+                ui.add(
+                    egui::DragValue::new(&mut polar.radial_step)
+                        .speed(0.01)
+                        .clamp_range(0.0..=f64::INFINITY)
+                        .prefix("Radial Step: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut polar.angle_divisions)
+                        .speed(1)
+                        .prefix("Angle Divisions: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut polar.origin[0])
+                        .speed(0.01)
+                        .prefix("Origin X: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut polar.origin[1])
+                        .speed(0.01)
+                        .prefix("Origin Y: "),
+                );
+            }
+            SnapMode::ExistingPoints => {
+                let existing = &mut settings.existing_points;
+                ui.checkbox(&mut existing.should_snap_x, "Snap X");
+                ui.checkbox(&mut existing.should_snap_y, "Snap Y");
+                ui.add(
+                    egui::DragValue::new(&mut existing.max_distance)
+                        .speed(0.01)
+                        .clamp_range(0.0..=f64::INFINITY)
+                        .prefix("Max Distance: "),
+                );
+            }
+            SnapMode::ReferenceTrace => {
+                if is_reference_trace_loaded {
+                    ui.label("New points are moved onto the nearest sample of the reference trace");
+                } else {
+                    ui.label("No reference trace loaded, points are not moved");
+                }
+            }
+        });
+    }
+
+    fn reference_trace_options(&mut self, ui: &mut egui::Ui) {
+        let Some(trace) = self.data.reference_trace_mut() else {
+            ui.label("No reference trace loaded");
+            ui.label("Load one from the File menu");
+            return;
+        };
+        ui.label(format!(
+            "{} ({} samples)",
+            trace.name,
+            trace.samples().len()
+        ));
+        let mut columns = trace.columns();
+        ui.horizontal(|ui| {
+            let mut is_against_time = matches!(columns, TraceColumns::AgainstTime { .. });
+            ui.radio_value(&mut is_against_time, true, "Column against time");
+            ui.radio_value(&mut is_against_time, false, "Two columns as X and Y");
+            columns = match (is_against_time, columns) {
+                (true, TraceColumns::XY { x, y }) => TraceColumns::AgainstTime {
+                    time: Some(x),
+                    value: y,
+                },
+                (false, TraceColumns::AgainstTime { time, value }) => TraceColumns::XY {
+                    x: time.unwrap_or_default(),
+                    y: value,
+                },
+                (_, columns) => columns, // Already in the correct mode
+            };
+        });
+        let headers = trace.headers();
+        let column_combo = |ui: &mut egui::Ui, label: &str, selected: &mut usize| {
+            egui::ComboBox::from_label(label)
+                .selected_text(&headers[*selected])
+                .show_ui(ui, |ui| {
+                    for (i, header) in headers.iter().enumerate() {
+                        ui.selectable_value(selected, i, header);
+                    }
+                });
+        };
+        ui.horizontal(|ui| match &mut columns {
+            TraceColumns::AgainstTime { time, value } => {
+                egui::ComboBox::from_label("Time")
+                    .selected_text(time.map_or("Row Number", |t| &headers[t]))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(time, None, "Row Number");
+                        for (i, header) in headers.iter().enumerate() {
+                            ui.selectable_value(time, Some(i), header);
+                        }
+                    });
+                column_combo(ui, "Value", value);
+            }
+            TraceColumns::XY { x, y } => {
+                column_combo(ui, "X", x);
+                column_combo(ui, "Y", y);
+            }
+        });
+        if columns != trace.columns() {
+            trace.set_columns(columns);
+        }
+        if ui.button("Remove Reference Trace").clicked() {
+            self.data.set_reference_trace(None);
+        }
+    }
+
+    fn image_export_options(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.image_settings;
+        ui.horizontal(|ui| {
+            ui.label("Size: ");
+            for (value, suffix) in settings.size.iter_mut().zip([" px wide", " px high"]) {
+                ui.add(
+                    egui::DragValue::new(value)
+                        .clamp_range(1..=ImageSettings::MAX_SIZE)
+                        .suffix(suffix),
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Show: ");
+            ui.radio_value(&mut settings.is_fitted_to_points, false, "Current view");
+            ui.radio_value(&mut settings.is_fitted_to_points, true, "All points");
+        });
+        ui.checkbox(&mut settings.should_show_legend, "Show legend");
+        ui.label("Export from the File menu");
+    }
+
+    fn split_options(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(
+            &mut self.should_show_splits,
+            "Show splits as marker outlines",
+        );
+        ui.horizontal(|ui| {
+            for (split, fraction) in Split::ALL
+                .iter()
+                .zip(self.split_settings.fractions.iter_mut())
+            {
+                ui.add(
+                    egui::DragValue::new(fraction)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0)
+                        .prefix(format!("{}: ", split.as_str())),
+                );
+            }
+            ui.add(egui::DragValue::new(&mut self.split_settings.seed).prefix("Seed: "));
+            ui.checkbox(&mut self.split_settings.is_stratified, "Stratify by label");
+            if ui
+                .add_enabled(!self.data.is_empty(), Button::new("Assign Randomly"))
+                .on_hover_text("Replaces the split of every point")
+                .clicked()
+            {
+                self.data
+                    .assign_splits(&self.split_settings, &mut self.status_msg);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Set selected points to: ");
+            ui.add_enabled_ui(!self.data.selection().is_empty(), |ui| {
+                for split in Split::ALL.map(Some).into_iter().chain([None]) {
+                    if ui.button(split.map_or("None", |s| s.as_str())).clicked() {
+                        self.data
+                            .set_split_of_selection(split, &mut self.status_msg);
+                    }
+                }
+            });
+        });
+    }
+
+    fn axes_options(&mut self, ui: &mut egui::Ui) {
+        let mut axes = self.data.axes().clone();
+        ui.horizontal(|ui| {
+            let mut is_aspect_locked = axes.aspect.is_locked();
+            ui.checkbox(&mut is_aspect_locked, "Lock aspect ratio");
+            match (is_aspect_locked, &mut axes.aspect) {
+                (true, AspectMode::Locked(aspect)) => {
+                    ui.add(
+                        egui::DragValue::new(aspect)
+                            .speed(0.01)
+                            .clamp_range(f64::EPSILON..=f64::INFINITY)
+                            .prefix("X units per Y unit: "),
+                    );
+                    if axes.has_fixed_range() {
+                        ui.label("(Ignored while an axis has a fixed range)");
+                    }
+                }
+                (true, AspectMode::Free) => {
+                    axes.aspect = AspectMode::Locked(AxesSettings::DEFAULT_ASPECT)
+                }
+                (false, AspectMode::Locked(_)) => axes.aspect = AspectMode::Free,
+                (false, AspectMode::Free) => (), // Do nothing already in correct state
+            }
+        });
+        Self::axis_options(ui, "X Axis", &mut axes.x);
+        Self::axis_options(ui, "Y Axis", &mut axes.y);
+        if &axes != self.data.axes() {
+            *self.data.axes_mut() = axes;
+        }
+    }
+
+    fn dimensions_options(&mut self, ui: &mut egui::Ui) {
+        let mut dimensions = self.data.dimensions();
+        ui.horizontal(|ui| {
+            if ui
+                .add(
+                    egui::DragValue::new(&mut dimensions)
+                        .speed(0.1)
+                        .clamp_range(2..=Data::MAX_DIMENSIONS)
+                        .prefix("Number of Dimensions: "),
+                )
+                .on_hover_text("Values of removed dimensions can be brought back with undo")
+                .changed()
+            {
+                self.data.set_dimensions(dimensions, &mut self.status_msg);
+            }
+        });
+        if dimensions == 2 {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Projection: ");
+            let mut shown_axes = match self.data.projection() {
+                Projection::Axes(shown_axes) => Some(*shown_axes),
+                Projection::Pca(_) => None,
+            };
+            if ui.radio(shown_axes.is_some(), "Pick Dimensions").clicked() && shown_axes.is_none() {
+                self.data
+                    .set_projection(Projection::default(), &mut self.status_msg);
+            }
+            let mut should_calculate_pca =
+                ui.radio(shown_axes.is_none(), "PCA").clicked() && shown_axes.is_some();
+            if shown_axes.is_none() {
+                should_calculate_pca |= ui.button("Recalculate PCA").clicked();
+            }
+            if should_calculate_pca {
+                self.data.set_projection_to_pca(&mut self.status_msg);
+            }
+            if let Some(shown_axes) = &mut shown_axes {
+                let before = *shown_axes;
+                for (i, axis_name) in ["X", "Y"].iter().enumerate() {
+                    egui::ComboBox::from_id_source(("projection_axis", i))
+                        .selected_text(format!("{axis_name}: Dim {}", shown_axes[i] + 1))
+                        .show_ui(ui, |ui| {
+                            for dim in 0..dimensions {
+                                ui.selectable_value(
+                                    &mut shown_axes[i],
+                                    dim,
+                                    format!("Dim {}", dim + 1),
+                                );
+                            }
+                        });
+                }
+                if before != *shown_axes {
+                    self.data
+                        .set_projection(Projection::Axes(*shown_axes), &mut self.status_msg);
+                }
+            }
+        });
+        if let Projection::Pca(basis) = self.data.projection() {
+            ui.label(format!(
+                "Explained variance: {:.1}% and {:.1}%. New points use the mean for the remaining dimensions",
+                basis.explained_variance[0] * 100.0,
+                basis.explained_variance[1] * 100.0
+            ));
+            return;
+        }
+
+        ui.label("Values of hidden dimensions for new points:");
+        let shown_axes = match self.data.projection() {
+            Projection::Axes(shown_axes) => *shown_axes,
+            Projection::Pca(_) => unreachable!("returned above for PCA"),
+        };
+        let ranges: Vec<_> = (0..dimensions)
+            .map(|dim| self.data.dimension_range(dim))
+            .collect();
+        let defaults = self.data.new_point_defaults_mut();
+        for (dim, (value, range)) in defaults.iter_mut().zip(ranges).enumerate() {
+            if shown_axes.contains(&dim) {
+                continue;
+            }
+            // Slider covers the values already used with some room on either side
+            let (min, max) = range.unwrap_or((-1.0, 1.0));
+            let margin = ((max - min) * 0.5).max(1.0);
+            ui.add(
+                egui::Slider::new(value, (min - margin)..=(max + margin))
+                    .clamp_to_range(false)
+                    .text(format!("Dim {}", dim + 1)),
+            );
+        }
+    }
+
+    fn axis_options(ui: &mut egui::Ui, heading: &str, axis: &mut AxisSettings) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{heading}: "));
+            ui.label("Name");
+            ui.add(egui::TextEdit::singleline(&mut axis.name).desired_width(80.0));
+            ui.label("Unit");
+            ui.add(egui::TextEdit::singleline(&mut axis.unit).desired_width(50.0));
+            ui.checkbox(&mut axis.is_log, "Log scale");
+            let mut is_fixed = axis.fixed_range.is_some();
+            ui.checkbox(&mut is_fixed, "Fixed range");
+            if !is_fixed {
+                axis.fixed_range = None;
+            } else {
+                let [min, max] = axis.fixed_range.get_or_insert(if axis.is_log {
+                    [1.0, 10.0]
+                } else {
+                    [-1.0, 1.0]
+                });
+                ui.add(egui::DragValue::new(min).speed(0.01).prefix("Min: "));
+                ui.add(egui::DragValue::new(max).speed(0.01).prefix("Max: "));
+                if axis.fixed_range_in_plot().is_none() {
+                    ui.label(
+                        "(Invalid range, min must be less than max and positive on a log scale)",
+                    );
+                }
+            }
+        });
+    }
+
     fn undo_redo_controls(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             if ui
@@ -308,17 +863,38 @@ impl ManualDataCreatorApp {
                 .add_enabled(!self.data.is_empty(), Button::new("Delete all points"))
                 .clicked()
             {
-                self.data.clear_points();
+                self.data.clear_points(&mut self.status_msg);
             }
             if ui
                 .add_enabled(
-                    self.state_reset_plot_zoom.is_stopped(),
-                    Button::new("Reset Plot Zoom"),
+                    !self.data.selection().is_empty(),
+                    Button::new("Edit Selected Points"),
                 )
                 .clicked()
             {
-                self.state_reset_plot_zoom.start_reset();
+                let points = self.data.points();
+                self.point_details_edits = Some(
+                    self.data
+                        .selection()
+                        .iter()
+                        .map(|&i| (i, points[i].meta.clone()))
+                        .collect(),
+                );
             }
+            if ui
+                .add_enabled(!self.data.is_empty(), Button::new("Augment…"))
+                .clicked()
+            {
+                self.is_augment_window_open = true;
+            }
+            if ui
+                .button("Script…")
+                .on_hover_text("Edit the points with a script")
+                .clicked()
+            {
+                self.is_script_window_open = true;
+            }
+            self.zoom_controls(ui);
             if let Some(bounds) = self.plot_bounds {
                 ui.label(format!(
                     "Plot bounds: min: {:.02?}, max: {:.02?}",
@@ -327,13 +903,16 @@ impl ManualDataCreatorApp {
                 ));
             }
             match &self.state_reset_plot_zoom {
-                StatePlotResetZoom::Set => {
+                StatePlotResetZoom::Set { .. } => {
                     ui.label("Plot reset: In Progress");
                 }
-                StatePlotResetZoom::Wait(_) => {
+                StatePlotResetZoom::Animate(_) => {
+                    ui.label("Plot reset: Moving");
+                }
+                StatePlotResetZoom::Wait(..) => {
                     ui.label("Plot reset: Waiting for next step to verify");
                 }
-                StatePlotResetZoom::Verify(_) => {
+                StatePlotResetZoom::Verify(..) => {
                     ui.label("Plot reset: Verifying");
                 }
                 StatePlotResetZoom::NotRunning => (),
@@ -344,40 +923,614 @@ impl ManualDataCreatorApp {
         });
     }
 
+    fn window_point_details(&mut self, ctx: &egui::Context) {
+        let Some(edits) = &mut self.point_details_edits else {
+            return;
+        };
+        if edits
+            .iter()
+            .any(|(i, _)| !self.data.selection().contains(i))
+        {
+            // Selection changed (points changed) so the indices may no longer match
+            self.point_details_edits = None;
+            return;
+        }
+        let mut is_open = true;
+        let mut should_apply = false;
+        egui::Window::new("Point Details")
+            .open(&mut is_open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                egui::Grid::new("point_details_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for heading in [
+                            "Index",
+                            "Coordinates",
+                            "Label",
+                            "Note",
+                            "Weight",
+                            "Group",
+                            "Created",
+                            "Split",
+                        ] {
+                            ui.strong(heading);
+                        }
+                        ui.end_row();
+                        let points = self.data.points();
+                        for (index, meta) in edits.iter_mut() {
+                            let point = &points[*index];
+                            ui.label(index.to_string());
+                            ui.label(point.format_coords());
+                            ui.label(format!("{:?}", point.label));
+                            ui.add(egui::TextEdit::singleline(&mut meta.note).desired_width(150.0));
+                            ui.add(egui::DragValue::new(&mut meta.weight).speed(0.01));
+                            ui.horizontal(|ui| {
+                                let mut has_group = meta.group.is_some();
+                                ui.checkbox(&mut has_group, "");
+                                if has_group {
+                                    ui.add(egui::DragValue::new(meta.group.get_or_insert(0)));
+                                } else {
+                                    meta.group = None;
+                                }
+                            });
+                            ui.label(meta.created.map_or("Unknown".to_string(), format_timestamp));
+                            egui::ComboBox::from_id_source(("point_split", *index))
+                                .selected_text(meta.split.map_or("None", |s| s.as_str()))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut meta.split, None, "None");
+                                    for split in Split::ALL {
+                                        ui.selectable_value(
+                                            &mut meta.split,
+                                            Some(split),
+                                            split.as_str(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                if ui.button("Save Changes").clicked() {
+                    should_apply = true;
+                }
+            });
+        if should_apply {
+            if let Some(edits) = self.point_details_edits.take() {
+                self.data.edit_metadata(edits, &mut self.status_msg);
+            }
+        } else if !is_open {
+            self.point_details_edits = None;
+        }
+    }
+
+    fn window_augment(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.is_augment_window_open;
+        egui::Window::new("Augment Data")
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                let augmentation = &mut self.augmentation;
+                egui::ComboBox::from_label("Transform")
+                    .selected_text(augmentation.transform.as_str())
+                    .show_ui(ui, |ui| {
+                        for transform in Transform::ALL {
+                            ui.selectable_value(
+                                &mut augmentation.transform,
+                                transform,
+                                transform.as_str(),
+                            );
+                        }
+                    });
+                ui.horizontal(|ui| match augmentation.transform {
+                    Transform::Jitter => {
+                        ui.add(
+                            egui::DragValue::new(&mut augmentation.jitter_std_dev)
+                                .speed(0.01)
+                                .clamp_range(0.0..=f64::INFINITY)
+                                .prefix("Standard Deviation: "),
+                        );
+                    }
+                    Transform::Rotate => {
+                        Self::range_options(ui, "Degrees", &mut augmentation.rotation_degrees, 1.0)
+                    }
+                    Transform::Scale => {
+                        Self::range_options(ui, "Factor", &mut augmentation.scale_range, 0.01)
+                    }
+                    Transform::Shear => {
+                        Self::range_options(ui, "Factor", &mut augmentation.shear_range, 0.01)
+                    }
+                    Transform::Mirror => {
+                        ui.radio_value(&mut augmentation.mirror, Mirror::FlipX, "Flip X");
+                        ui.radio_value(&mut augmentation.mirror, Mirror::FlipY, "Flip Y");
+                    }
+                    Transform::Bootstrap => {
+                        ui.add(
+                            egui::DragValue::new(&mut augmentation.bootstrap_count)
+                                .speed(1)
+                                .prefix("Points to draw: "),
+                        );
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut augmentation.seed).prefix("Seed: "));
+                    if ui.button("Next Seed").clicked() {
+                        augmentation.seed = augmentation.seed.wrapping_add(1);
+                    }
+                });
+                ui.checkbox(
+                    &mut augmentation.is_selection_only,
+                    format!("Selected points only ({})", self.data.selection().len()),
+                );
+                ui.horizontal(|ui| {
+                    ui.checkbox(
+                        &mut augmentation.should_keep_originals,
+                        "Keep originals and add new points",
+                    );
+                    if augmentation.should_keep_originals
+                        && augmentation.transform != Transform::Bootstrap
+                    {
+                        ui.add(
+                            egui::DragValue::new(&mut augmentation.copies)
+                                .speed(0.1)
+                                .clamp_range(1..=100)
+                                .prefix("Copies: "),
+                        );
+                    }
+                });
+                ui.checkbox(&mut self.should_preview_augmentation, "Show preview");
+                if ui.button("Apply").clicked() {
+                    self.data.augment(&self.augmentation, &mut self.status_msg);
+                }
+            });
+        self.is_augment_window_open = is_open;
+    }
+
+    fn range_options(ui: &mut egui::Ui, name: &str, range: &mut [f64; 2], speed: f64) {
+        ui.add(
+            egui::DragValue::new(&mut range[0])
+                .speed(speed)
+                .prefix(format!("Min {name}: ")),
+        );
+        ui.add(
+            egui::DragValue::new(&mut range[1])
+                .speed(speed)
+                .prefix(format!("Max {name}: ")),
+        );
+    }
+
+    fn zoom_controls(&mut self, ui: &mut egui::Ui) {
+        let is_stopped = self.state_reset_plot_zoom.is_stopped();
+        if ui
+            .add_enabled(is_stopped, Button::new("Reset Plot Zoom"))
+            .clicked()
+        {
+            self.start_zoom(ZoomTarget::AllPoints);
+        }
+        if ui
+            .add_enabled(
+                is_stopped && !self.data.selection().is_empty(),
+                Button::new("Zoom to Selection"),
+            )
+            .clicked()
+        {
+            self.start_zoom(ZoomTarget::Selection);
+        }
+        ui.add_enabled_ui(is_stopped, |ui| {
+            ui.menu_button("Zoom to Label", |ui| {
+                for (label, text) in [
+                    (DataLabel::Normal, "Normal"),
+                    (DataLabel::Anomaly, "Anomalies"),
+                ] {
+                    if ui.button(text).clicked() {
+                        self.start_zoom(ZoomTarget::Label(label));
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.menu_button("Bookmarks", |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.bookmark_name)
+                            .hint_text("Bookmark name")
+                            .desired_width(120.0),
+                    );
+                    if ui
+                        .add_enabled(self.plot_bounds.is_some(), Button::new("Save Current View"))
+                        .clicked()
+                    {
+                        if let Some(bounds) = self.plot_bounds {
+                            let name = if self.bookmark_name.is_empty() {
+                                format!("View {}", self.data.bookmarks().len() + 1)
+                            } else {
+                                std::mem::take(&mut self.bookmark_name)
+                            };
+                            self.data.add_bookmark(name, bounds.into());
+                        }
+                    }
+                });
+                let mut index_to_remove = None;
+                for (i, bookmark) in self.data.bookmarks().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.button(&bookmark.name).clicked() {
+                            self.state_reset_plot_zoom
+                                .start_reset(ZoomTarget::Bookmark(i), self.is_zoom_animated);
+                            ui.close_menu();
+                        }
+                        if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                            index_to_remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(index) = index_to_remove {
+                    self.data.remove_bookmark(index);
+                }
+            });
+        });
+    }
+
+    fn start_zoom(&mut self, target: ZoomTarget) {
+        self.state_reset_plot_zoom
+            .start_reset(target, self.is_zoom_animated);
+    }
+
     fn panel_center(&mut self, ui: &mut egui::Ui) -> egui::Response {
-        let markers_plot = Plot::new("markers")
-            .data_aspect(1.0)
+        let axes = self.data.axes().clone();
+        let mut markers_plot = Plot::new("markers")
             .legend(Legend::default())
             .min_size(egui::Vec2 { x: 100.0, y: 100.0 })
             .allow_boxed_zoom(self.allow_boxed_zoom)
-            .allow_double_click_reset(false);
+            .allow_drag(
+                !self.click_mode.is_select()
+                    && !self.click_mode.is_label_range()
+                    && !self.click_mode.is_erase()
+                    && !self.click_mode.is_relabel(),
+            )
+            .allow_double_click_reset(false)
+            .x_axis_label(axes.x.label())
+            .y_axis_label(axes.y.label());
+        if let Some(aspect) = axes.effective_aspect() {
+            markers_plot = markers_plot.data_aspect(aspect as f32);
+        }
+        if axes.x.is_log {
+            markers_plot = markers_plot.x_axis_formatter(|mark, _, _| log_tick_label(mark.value));
+        }
+        if axes.y.is_log {
+            markers_plot = markers_plot.y_axis_formatter(|mark, _, _| log_tick_label(mark.value));
+        }
+        if axes.x.is_log || axes.y.is_log {
+            // Show the hovered coordinate in data space instead of plot space
+            let label_axes = axes.clone();
+            markers_plot = markers_plot.label_formatter(move |name, value| {
+                let [x, y] = label_axes.to_data([value.x, value.y]);
+                if name.is_empty() {
+                    format!("x = {x:.3}\ny = {y:.3}")
+                } else {
+                    format!("{name}\nx = {x:.3}\ny = {y:.3}")
+                }
+            });
+        }
 
+        let grid_color = ui.visuals().weak_text_color().gamma_multiply(0.5);
+        let reference_color = ui.visuals().weak_text_color();
+        let visuals = ui.visuals().clone();
         let PlotResponse {
             response,
             inner: pointer_coordinate,
             ..
         } = markers_plot.show(ui, |plot_ui| {
-            for marker in self.markers() {
-                plot_ui.points(marker);
+            let plot_bounds: MinMaxPair = plot_ui.plot_bounds().into();
+            for line in self
+                .data
+                .snap_settings()
+                .grid_lines(axes.bounds_to_data(plot_bounds))
+            {
+                let line: Vec<[f64; 2]> =
+                    line.into_iter().filter_map(|p| axes.to_plot(p)).collect();
+                plot_ui.line(Line::new(line).color(grid_color));
+            }
+            if let Some(trace) = self.data.reference_trace() {
+                let samples: Vec<[f64; 2]> = trace
+                    .samples()
+                    .iter()
+                    .filter_map(|sample| axes.to_plot(*sample))
+                    .collect();
+                plot_ui.line(
+                    Line::new(samples)
+                        .color(reference_color)
+                        .name(format!("Reference: {}", trace.name)),
+                );
             }
-            if !self.state_reset_plot_zoom.is_stopped() {
-                self.state_reset_plot_zoom
-                    .step(plot_ui, self.data.get_points_min_max_w_margin())
+            if self.data.is_time_series() {
+                let series: Vec<[f64; 2]> = self
+                    .data
+                    .points()
+                    .iter()
+                    .filter(|point| self.data.is_shown(point))
+                    .filter_map(|point| axes.to_plot(self.data.project(point)))
+                    .collect();
+                plot_ui.line(Line::new(series).name("Series"));
+            }
+            for marker in self.marker_series(true) {
+                plot_ui.points(marker.to_plot_points());
+            }
+            if self.is_augment_window_open && self.should_preview_augmentation {
+                let preview: Vec<[f64; 2]> = self
+                    .data
+                    .augmented_points(&self.augmentation)
+                    .iter()
+                    .filter_map(|point| axes.to_plot(self.data.project(point)))
+                    .collect();
+                plot_ui.points(
+                    Points::new(preview)
+                        .name("Augmentation Preview")
+                        .radius(self.marker_radius)
+                        .shape(MarkerShape::Diamond)
+                        .filled(false),
+                );
+            }
+            self.plot_diff(plot_ui, &axes);
+            if let (Some((start, end)), true) =
+                (self.selection_drag, self.click_mode.is_label_range())
+            {
+                // Range covers the full height of the plot
+                plot_ui.polygon(
+                    Polygon::new(vec![
+                        [start.x, plot_bounds.min[1]],
+                        [end.x, plot_bounds.min[1]],
+                        [end.x, plot_bounds.max[1]],
+                        [start.x, plot_bounds.max[1]],
+                    ])
+                    .name(format!("Label Range ({:?})", self.range_drag_label)),
+                );
+            } else if let Some((start, end)) = self.selection_drag {
+                plot_ui.polygon(
+                    Polygon::new(vec![
+                        [start.x, start.y],
+                        [end.x, start.y],
+                        [end.x, end.y],
+                        [start.x, end.y],
+                    ])
+                    .name(if self.click_mode.is_relabel() {
+                        format!("Relabel Region ({:?})", self.range_drag_label)
+                    } else {
+                        "Selection".to_string()
+                    }),
+                );
+            }
+            if let Some(bounds) = self.restore_plot_bounds.take() {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            if axes.has_fixed_range() {
+                let fixed_bounds = axes.apply_fixed_ranges(plot_bounds);
+                if fixed_bounds != plot_bounds {
+                    plot_ui.set_plot_bounds(fixed_bounds.into());
+                }
+            }
+            if let Some(target) = self.state_reset_plot_zoom.target() {
+                match self.data.get_zoom_target_bounds(target) {
+                    Some(target_bounds) => self.state_reset_plot_zoom.step(
+                        plot_ui,
+                        target_bounds,
+                        axes.effective_aspect().is_some(),
+                    ),
+                    None => {
+                        self.state_reset_plot_zoom = StatePlotResetZoom::Error(format!(
+                            "Zoom target no longer available: {target:?}"
+                        ))
+                    }
+                }
+                plot_ui.ctx().request_repaint(); // Keep stepping even without user input
             }
             self.plot_bounds = Some(plot_ui.plot_bounds());
-            plot_ui.pointer_coordinate()
+            let pointer_coordinate = plot_ui.pointer_coordinate();
+            let inspected = pointer_coordinate
+                .map(|coord| self.inspected_points(plot_ui, &axes, coord))
+                .unwrap_or_default();
+            if let (Some(coord), true) = (pointer_coordinate, self.click_mode.is_erase()) {
+                self.plot_eraser(plot_ui, &axes, coord);
+            }
+            for (index, highlight) in &inspected {
+                if let Some(position) = axes.to_plot(self.data.project(&self.data.points()[*index]))
+                {
+                    plot_ui.points(
+                        Points::new(vec![position])
+                            .radius(self.marker_radius * 1.8)
+                            .shape(MarkerShape::Circle)
+                            .color(highlight.color(&visuals))
+                            .filled(false),
+                    );
+                }
+            }
+            (pointer_coordinate, inspected)
         });
+        let (pointer_coordinate, inspected) = pointer_coordinate;
+        let response = if inspected.is_empty() {
+            response
+        } else {
+            response.on_hover_ui_at_pointer(|ui| {
+                for (i, (index, highlight)) in inspected.iter().enumerate() {
+                    if i > 0 {
+                        ui.separator();
+                    }
+                    if let Some(action) = highlight.action() {
+                        ui.strong(action);
+                    }
+                    point_details_ui(ui, *index, &self.data.points()[*index]);
+                }
+            })
+        };
+
+        if self.click_mode.is_select() {
+            self.selection_drag_handler(&response, pointer_coordinate);
+        }
+        if self.click_mode.is_label_range() {
+            self.label_range_drag_handler(&response, pointer_coordinate);
+        }
+        if self.click_mode.is_relabel() {
+            self.relabel_drag_handler(&response, pointer_coordinate);
+        }
 
+        // Points are stored in data space
+        let pointer_coordinate = pointer_coordinate.map(|coord| axes.to_data([coord.x, coord.y]));
+        if self.click_mode.is_erase() {
+            self.eraser_drag_handler(&response, pointer_coordinate);
+        }
         self.click_handler(&response, pointer_coordinate);
 
         response
     }
 
-    fn click_handler(
+    /// Points to highlight and describe for the pointer at `pointer_coordinate` (plot space)
+    ///
+    /// When deleting these are the points each click would remove and when relabelling the point a click would change,
+    /// otherwise the nearest point if it is under the pointer
+    fn inspected_points(
+        &self,
+        plot_ui: &egui_plot::PlotUi,
+        axes: &AxesSettings,
+        pointer_coordinate: PlotPoint,
+    ) -> Vec<(usize, Highlight)> {
+        let target = axes.to_data([pointer_coordinate.x, pointer_coordinate.y]);
+        if self.click_mode == ClickMode::DeletePoints {
+            return [
+                (self.primary_click_label, Highlight::DeletePrimary),
+                (self.secondary_click_label(), Highlight::DeleteSecondary),
+            ]
+            .into_iter()
+            .filter_map(|(label, highlight)| {
+                Some((self.data.delete_target(target, label)?, highlight))
+            })
+            .collect();
+        }
+        if self.click_mode.is_relabel() {
+            return self
+                .data
+                .get_closest_point(target, None)
+                .map(|index| (index, Highlight::Relabel))
+                .into_iter()
+                .collect();
+        }
+        // Searched on screen so the point found is the one drawn under the pointer
+        let pointer_pos = plot_ui.screen_from_plot(pointer_coordinate);
+        self.data
+            .points()
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| self.data.is_shown(point))
+            .filter_map(|(i, point)| {
+                let [x, y] = axes.to_plot(self.data.project(point))?;
+                let distance = plot_ui
+                    .screen_from_plot(PlotPoint::new(x, y))
+                    .distance(pointer_pos);
+                (distance <= self.marker_radius.max(Self::MIN_HOVER_DISTANCE))
+                    .then_some((i, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| (i, Highlight::Nearest))
+            .into_iter()
+            .collect()
+    }
+
+    /// `pointer_coordinate` is in plot space
+    fn selection_drag_handler(
         &mut self,
         response: &egui::Response,
-        pointer_coordinate: Option<egui_plot::PlotPoint>,
+        pointer_coordinate: Option<PlotPoint>,
     ) {
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            self.selection_drag = pointer_coordinate.map(|coord| (coord, coord));
+        }
+        if let (Some((_, end)), Some(coord)) = (&mut self.selection_drag, pointer_coordinate) {
+            *end = coord;
+        }
+        if response.drag_released() {
+            if let Some((start, end)) = self.selection_drag.take() {
+                let bounds = MinMaxPair {
+                    min: [start.x.min(end.x), start.y.min(end.y)],
+                    max: [start.x.max(end.x), start.y.max(end.y)],
+                };
+                let should_keep_existing = response.ctx.input(|i| i.modifiers.shift);
+                self.data.select_in_bounds(
+                    self.data.axes().bounds_to_data(bounds),
+                    should_keep_existing,
+                );
+                self.status_msg.add_msg(&format!(
+                    "{} point(s) selected",
+                    self.data.selection().len()
+                ));
+            }
+        }
+    }
+
+    /// `pointer_coordinate` is in plot space
+    fn relabel_drag_handler(
+        &mut self,
+        response: &egui::Response,
+        pointer_coordinate: Option<PlotPoint>,
+    ) {
+        for (button, label) in [
+            (egui::PointerButton::Primary, self.primary_click_label),
+            (egui::PointerButton::Secondary, self.secondary_click_label()),
+        ] {
+            if response.drag_started_by(button) {
+                self.selection_drag = pointer_coordinate.map(|coord| (coord, coord));
+                self.range_drag_label = label;
+            }
+        }
+        if let (Some((_, end)), Some(coord)) = (&mut self.selection_drag, pointer_coordinate) {
+            *end = coord;
+        }
+        if response.drag_released() {
+            if let Some((start, end)) = self.selection_drag.take() {
+                let bounds = MinMaxPair {
+                    min: [start.x.min(end.x), start.y.min(end.y)],
+                    max: [start.x.max(end.x), start.y.max(end.y)],
+                };
+                self.data.relabel_in_bounds(
+                    self.data.axes().bounds_to_data(bounds),
+                    self.range_drag_label,
+                    &mut self.status_msg,
+                );
+            }
+        }
+    }
+
+    /// `pointer_coordinate` is in plot space
+    fn label_range_drag_handler(
+        &mut self,
+        response: &egui::Response,
+        pointer_coordinate: Option<PlotPoint>,
+    ) {
+        for (button, label) in [
+            (egui::PointerButton::Primary, DataLabel::Anomaly),
+            (egui::PointerButton::Secondary, DataLabel::Normal),
+        ] {
+            if response.drag_started_by(button) {
+                self.selection_drag = pointer_coordinate.map(|coord| (coord, coord));
+                self.range_drag_label = label;
+            }
+        }
+        if let (Some((_, end)), Some(coord)) = (&mut self.selection_drag, pointer_coordinate) {
+            *end = coord;
+        }
+        if response.drag_released() {
+            if let Some((start, end)) = self.selection_drag.take() {
+                let axes = self.data.axes();
+                let (min_x, max_x) = (
+                    axes.x.to_data(start.x.min(end.x)),
+                    axes.x.to_data(start.x.max(end.x)),
+                );
+                self.data.relabel_time_range(
+                    min_x,
+                    max_x,
+                    self.range_drag_label,
+                    &mut self.status_msg,
+                );
+            }
+        }
+    }
+
+    fn click_handler(&mut self, response: &egui::Response, pointer_coordinate: Option<[f64; 2]>) {
         if response.clicked() {
             match self.click_mode {
                 ClickMode::AddPoints => self.data.add(
@@ -390,6 +1543,17 @@ impl ManualDataCreatorApp {
                     self.primary_click_label,
                     &mut self.status_msg,
                 ),
+                ClickMode::Erase => self.data.erase(
+                    pointer_coordinate,
+                    self.primary_click_label,
+                    true,
+                    &mut self.status_msg,
+                ),
+                ClickMode::Relabel => self
+                    .data
+                    .cycle_label(pointer_coordinate, &mut self.status_msg),
+                ClickMode::Select => self.data.clear_selection(),
+                ClickMode::LabelRange => (), // Labelling is done by dragging
             }
         }
         if response.secondary_clicked() {
@@ -404,28 +1568,90 @@ impl ManualDataCreatorApp {
                     self.secondary_click_label(),
                     &mut self.status_msg,
                 ),
+                ClickMode::Erase => self.data.erase(
+                    pointer_coordinate,
+                    self.secondary_click_label(),
+                    true,
+                    &mut self.status_msg,
+                ),
+                ClickMode::Relabel | ClickMode::Select | ClickMode::LabelRange => (), // Do not use the label of a click
             }
         }
         if response.middle_clicked() {
             self.click_mode = match self.click_mode {
                 ClickMode::AddPoints => ClickMode::DeletePoints,
-                ClickMode::DeletePoints => ClickMode::AddPoints,
+                ClickMode::DeletePoints => ClickMode::Erase,
+                ClickMode::Erase => ClickMode::Relabel,
+                ClickMode::Relabel => ClickMode::Select,
+                ClickMode::Select if self.data.is_time_series() => ClickMode::LabelRange,
+                ClickMode::Select | ClickMode::LabelRange => ClickMode::AddPoints,
             }
         }
     }
 
     fn secondary_click_label(&self) -> DataLabel {
-        match self.primary_click_label {
-            DataLabel::Normal => DataLabel::Anomaly,
-            DataLabel::Anomaly => DataLabel::Normal,
+        self.primary_click_label.next()
+    }
+}
+
+/// Why a point under the pointer is highlighted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Highlight {
+    Nearest,
+    /// Removed by a primary click
+    DeletePrimary,
+    /// Removed by a secondary click
+    DeleteSecondary,
+    /// Gets the next label on click
+    Relabel,
+}
+
+impl Highlight {
+    fn color(&self, visuals: &egui::Visuals) -> Color32 {
+        match self {
+            Highlight::Nearest => visuals.strong_text_color(),
+            Highlight::DeletePrimary => visuals.error_fg_color,
+            Highlight::DeleteSecondary => visuals.error_fg_color.gamma_multiply(0.5),
+            Highlight::Relabel => visuals.warn_fg_color,
+        }
+    }
+
+    fn action(&self) -> Option<&'static str> {
+        match self {
+            Highlight::Nearest => None,
+            Highlight::DeletePrimary => Some("Click deletes"),
+            Highlight::DeleteSecondary => Some("Secondary click deletes"),
+            Highlight::Relabel => Some("Click switches the label"),
         }
     }
 }
 
-fn calculate_distance(p1: [f64; 2], p2: [f64; 2]) -> f64 {
-    let diff0 = p1[0] - p2[0];
-    let diff1 = p1[1] - p2[1];
-    ((diff0 * diff0) + (diff1 * diff1)).sqrt()
+/// Shows the index, exact coordinates, label and metadata of a point
+fn point_details_ui(ui: &mut egui::Ui, index: usize, point: &DataPoint) {
+    ui.label(format!("Point {index} ({:?})", point.label));
+    for (dim, value) in point.coords().iter().enumerate() {
+        let name = match dim {
+            0 => "x".to_string(),
+            1 => "y".to_string(),
+            _ => format!("Dim {}", dim + 1),
+        };
+        ui.label(format!("{name} = {value}"));
+    }
+    ui.label(point.meta.to_string().trim_end());
+}
+
+/// Tick labels for a log scaled axis, `value` is the exponent
+fn log_tick_label(value: f64) -> String {
+    let data_value = 10f64.powf(value);
+    if (1e-3..1e4).contains(&data_value) {
+        let result = format!("{data_value:.3}");
+        result
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        format!("{data_value:.2e}")
+    }
 }
 
 impl eframe::App for ManualDataCreatorApp {
@@ -448,28 +1674,92 @@ impl eframe::App for ManualDataCreatorApp {
             // The central panel the region left after adding TopPanel and BottomPanel
             self.panel_center(ui)
         });
+
+        self.window_point_details(ctx);
+        self.window_augment(ctx);
+        self.window_script(ctx);
+        self.window_diff(ctx);
+
+        self.receive_dropped_files(ctx);
+        self.window_dropped_data(ctx);
+        #[cfg(target_arch = "wasm32")]
+        self.receive_uploads();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.update_remote_control(ctx);
+            self.update_file_watch(ctx);
+            self.window_file_changed(ctx);
+        }
     }
 }
 
 impl ManualDataCreatorApp {
-    fn markers(&self) -> [Points; 2] {
+    /// The markers shown on the plot, the selection is left out of exported images
+    fn marker_series(&self, should_include_selection: bool) -> Vec<MarkerSeries> {
         use data_conversion::ConvertToSeries as _;
-        let series_normal = self.data.points().array_of_normal();
+        let axes = self.data.axes();
+        let series_normal = self.data.array_of_normal();
         let len_normal = series_normal.len();
-        let normal_points = Points::new(series_normal)
-            .name(format!("Normal ({len_normal})"))
-            .radius(self.marker_radius)
-            .shape(MarkerShape::Plus)
-            .color(self.normal_color);
+        let mut result = self.label_styles.normal.series(
+            format!("Normal ({len_normal})"),
+            series_normal,
+            self.marker_radius,
+            self.label_color(DataLabel::Normal),
+        );
 
-        let series_anom = self.data.points().array_of_anom();
+        let series_anom = self.data.array_of_anom();
         let len_anom = series_anom.len();
-        let anom_points = Points::new(series_anom)
-            .name(format!("Anomalies ({len_anom})"))
-            .radius(self.marker_radius)
-            .shape(MarkerShape::Asterisk)
-            .color(self.anom_color);
+        result.extend(self.label_styles.anomaly.series(
+            format!("Anomalies ({len_anom})"),
+            series_anom,
+            self.marker_radius,
+            self.label_color(DataLabel::Anomaly),
+        ));
+
+        if self.should_show_splits {
+            let points = self.data.points();
+            for (split, shape) in Split::ALL.into_iter().zip([
+                MarkerShape::Square,
+                MarkerShape::Diamond,
+                MarkerShape::Up,
+            ]) {
+                let series: Vec<[f64; 2]> = points
+                    .iter()
+                    .filter(|point| point.meta.split == Some(split) && self.data.is_shown(point))
+                    .filter_map(|point| axes.to_plot(self.data.project(point)))
+                    .collect();
+                let len = series.len();
+                if len > 0 {
+                    result.push(MarkerSeries {
+                        name: format!("{} ({len})", split.as_str()),
+                        points: series,
+                        radius: self.marker_radius * 1.3,
+                        shape,
+                        color: None,
+                        is_filled: false,
+                    });
+                }
+            }
+        }
+
+        let selection = self.data.selection();
+        if should_include_selection && !selection.is_empty() {
+            let points = self.data.points();
+            let series_selected: Vec<[f64; 2]> = selection
+                .iter()
+                .filter(|&&i| self.data.is_shown(&points[i]))
+                .filter_map(|&i| axes.to_plot(self.data.project(&points[i])))
+                .collect();
+            result.push(MarkerSeries {
+                name: format!("Selected ({})", selection.len()),
+                points: series_selected,
+                radius: self.marker_radius * 1.5,
+                shape: MarkerShape::Circle,
+                color: None,
+                is_filled: false,
+            });
+        }
 
-        [normal_points, anom_points]
+        result
     }
 }
@@ -1,16 +1,17 @@
-use super::data_definition::DataPoint;
+use crate::dataset::Data;
 
 pub trait ConvertToSeries {
     fn array_of_normal(&self) -> Vec<[f64; 2]>;
     fn array_of_anom(&self) -> Vec<[f64; 2]>;
 }
 
-impl ConvertToSeries for &[DataPoint] {
+impl ConvertToSeries for Data {
     fn array_of_normal(&self) -> Vec<[f64; 2]> {
-        self.iter()
+        self.points()
+            .iter()
             .filter_map(|point| {
-                if point.label.is_normal() {
-                    Some([point.x, point.y])
+                if point.label.is_normal() && self.is_shown(point) {
+                    self.axes().to_plot(self.project(point))
                 } else {
                     None
                 }
@@ -19,10 +20,11 @@ impl ConvertToSeries for &[DataPoint] {
     }
 
     fn array_of_anom(&self) -> Vec<[f64; 2]> {
-        self.iter()
+        self.points()
+            .iter()
             .filter_map(|point| {
-                if point.label.is_anomaly() {
-                    Some([point.x, point.y])
+                if point.label.is_anomaly() && self.is_shown(point) {
+                    self.axes().to_plot(self.project(point))
                 } else {
                     None
                 }
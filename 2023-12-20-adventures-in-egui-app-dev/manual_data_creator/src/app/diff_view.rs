@@ -0,0 +1,189 @@
+//! Comparing the points with another version of the dataset and accepting its changes one by one
+
+use ecolor::Color32;
+use egui_plot::{Line, MarkerShape, PlotUi, Points};
+
+use super::ManualDataCreatorApp;
+use crate::dataset::{diff, AxesSettings, Data, DataPoint, Difference};
+
+/// The other version of the points and how it differs from the current points
+#[derive(Debug, PartialEq)]
+pub struct DiffView {
+    /// Where the other version was loaded from
+    source: String,
+    points: Vec<DataPoint>,
+    /// Points closer than this (over all dimensions) are treated as the same point
+    tolerance: f64,
+    differences: Vec<Difference>,
+    /// [`crate::dataset::UndoManager::change_count`] and tolerance that `differences` were found for
+    found_for: Option<(u64, f64)>,
+}
+
+impl DiffView {
+    const DEFAULT_TOLERANCE: f64 = 0.01;
+    /// Larger tolerances match nearly every pair of points, which makes finding the differences slow
+    const MAX_TOLERANCE: f64 = 1.0;
+    const ADDED_COLOR: Color32 = Color32::from_rgb(60, 170, 80);
+    const REMOVED_COLOR: Color32 = Color32::from_rgb(220, 60, 60);
+    const CHANGED_COLOR: Color32 = Color32::from_rgb(150, 100, 220);
+
+    /// Finds the differences again if the points or the tolerance changed since they were last found
+    fn refresh(&mut self, data: &Data) {
+        let key = (data.undo_manager().change_count(), self.tolerance);
+        if self.found_for != Some(key) {
+            self.differences = diff(data.points(), &self.points, self.tolerance);
+            self.found_for = Some(key);
+        }
+    }
+
+    fn count(&self, is_kind: fn(&Difference) -> bool) -> usize {
+        self.differences.iter().filter(|d| is_kind(d)).count()
+    }
+}
+
+impl ManualDataCreatorApp {
+    /// Starts comparing the current points with `points` read from `source`
+    pub(super) fn start_diff(&mut self, source: String, points: Vec<DataPoint>, dimensions: usize) {
+        if dimensions != self.data.dimensions() {
+            self.status_msg.add_err(&format!(
+                "Unable to compare with {source}. Points have {dimensions} dimensions but the current points have {}",
+                self.data.dimensions()
+            ));
+            return;
+        }
+        self.status_msg.add_msg(&format!(
+            "Comparing with {} points from {source}",
+            points.len()
+        ));
+        let tolerance = self
+            .diff_view
+            .as_ref()
+            .map_or(DiffView::DEFAULT_TOLERANCE, |view| view.tolerance);
+        self.diff_view = Some(DiffView {
+            source,
+            points,
+            tolerance,
+            differences: vec![],
+            found_for: None,
+        });
+    }
+
+    pub(super) fn window_diff(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.diff_view else {
+            return;
+        };
+        view.refresh(&self.data);
+        let mut is_open = true;
+        let mut accepted = None;
+        egui::Window::new(format!("Compare With {}", view.source))
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut view.tolerance)
+                        .speed(0.001)
+                        .clamp_range(0.0..=DiffView::MAX_TOLERANCE)
+                        .prefix("Tolerance: "),
+                )
+                .on_hover_text(
+                    "Points closer than this (over all dimensions) are treated as the same point",
+                );
+                ui.label(format!(
+                    "Added: {}, Removed: {}, Moved or Relabelled: {}",
+                    view.count(|d| matches!(d, Difference::Added(_))),
+                    view.count(|d| matches!(d, Difference::Removed(_))),
+                    view.count(|d| matches!(d, Difference::Changed(..))),
+                ));
+                if view.differences.is_empty() {
+                    ui.label("No differences");
+                } else if ui
+                    .button("Accept All")
+                    .on_hover_text("Makes the current points the same as the other version")
+                    .clicked()
+                {
+                    accepted = Some(view.differences.clone());
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for difference in &view.differences {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Accept").clicked() {
+                                    accepted = Some(vec![difference.clone()]);
+                                }
+                                ui.label(difference.describe(self.data.points()));
+                            });
+                        }
+                    });
+            });
+        if let Some(accepted) = accepted {
+            let source = view.source.clone();
+            let change_count = self.data.undo_manager().change_count();
+            self.data
+                .accept_differences(&accepted, &source, &mut self.status_msg);
+            self.stop_watching_if_changed(change_count);
+        }
+        if !is_open {
+            self.diff_view = None;
+        }
+    }
+
+    /// Shows the differences on the plot, changes are drawn as lines from the current point to the other version
+    pub(super) fn plot_diff(&self, plot_ui: &mut PlotUi, axes: &AxesSettings) {
+        let Some(view) = &self.diff_view else {
+            return;
+        };
+        if view.found_for.map(|(change_count, _)| change_count)
+            != Some(self.data.undo_manager().change_count())
+        {
+            return; // Indices may no longer be valid, found again by the window later this frame
+        }
+        let to_plot = |point: &DataPoint| axes.to_plot(self.data.project(point));
+        let points = self.data.points();
+        let mut added = vec![];
+        let mut removed = vec![];
+        let mut changed = vec![];
+        for difference in &view.differences {
+            match difference {
+                Difference::Added(point) => added.extend(to_plot(point)),
+                Difference::Removed(index) => removed.extend(to_plot(&points[*index])),
+                Difference::Changed(index, other) => {
+                    if let (Some(start), Some(end)) = (to_plot(&points[*index]), to_plot(other)) {
+                        plot_ui.line(
+                            Line::new(vec![start, end])
+                                .color(DiffView::CHANGED_COLOR)
+                                .name("Diff: Changed"),
+                        );
+                        changed.push(end);
+                    }
+                }
+            }
+        }
+        for (series, name, shape, color) in [
+            (added, "Added", MarkerShape::Circle, DiffView::ADDED_COLOR),
+            (
+                removed,
+                "Removed",
+                MarkerShape::Cross,
+                DiffView::REMOVED_COLOR,
+            ),
+            (
+                changed,
+                "Changed",
+                MarkerShape::Diamond,
+                DiffView::CHANGED_COLOR,
+            ),
+        ] {
+            if !series.is_empty() {
+                plot_ui.points(
+                    Points::new(series)
+                        .name(format!("Diff: {name}"))
+                        .radius(self.marker_radius * 1.5)
+                        .shape(shape)
+                        .color(color)
+                        .filled(false),
+                );
+            }
+        }
+    }
+}
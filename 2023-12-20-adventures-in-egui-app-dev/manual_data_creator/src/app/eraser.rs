@@ -0,0 +1,83 @@
+//! Removing every point within a radius of the pointer while dragging and limiting how far away a click can delete
+
+use std::f64::consts::TAU;
+
+use egui_plot::{Line, PlotPoint, PlotUi};
+
+use super::ManualDataCreatorApp;
+use crate::dataset::AxesSettings;
+
+impl ManualDataCreatorApp {
+    /// Number of segments used to draw the outline of the eraser
+    const ERASER_OUTLINE_SEGMENTS: usize = 64;
+
+    pub(super) fn erase_options(&mut self, ui: &mut egui::Ui) {
+        let settings = self.data.erase_settings_mut();
+        ui.horizontal(|ui| {
+            ui.checkbox(
+                &mut settings.should_limit_delete_distance,
+                "Limit delete distance",
+            )
+            .on_hover_text("Clicks further away than this from every point delete nothing");
+            ui.add_enabled(
+                settings.should_limit_delete_distance,
+                egui::DragValue::new(&mut settings.max_delete_distance)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::INFINITY)
+                    .prefix("Max Distance: "),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut settings.radius)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::INFINITY)
+                    .prefix("Eraser Radius: "),
+            );
+            ui.checkbox(&mut settings.should_ignore_label, "Erase all labels")
+                .on_hover_text(
+                    "When off, the primary button erases its label and the secondary button the other label",
+                );
+        });
+    }
+
+    /// Erases while the plot is dragged, each drag is undone at once
+    ///
+    /// `pointer_coordinate` is in data space
+    pub(super) fn eraser_drag_handler(
+        &mut self,
+        response: &egui::Response,
+        pointer_coordinate: Option<[f64; 2]>,
+    ) {
+        for (button, label) in [
+            (egui::PointerButton::Primary, self.primary_click_label),
+            (egui::PointerButton::Secondary, self.secondary_click_label()),
+        ] {
+            if response.drag_started_by(button) {
+                self.data
+                    .erase(pointer_coordinate, label, true, &mut self.status_msg);
+            } else if response.dragged_by(button) {
+                self.data
+                    .erase(pointer_coordinate, label, false, &mut self.status_msg);
+            }
+        }
+    }
+
+    /// Draws the area that would be erased around `pointer_coordinate` (plot space)
+    pub(super) fn plot_eraser(
+        &self,
+        plot_ui: &mut PlotUi,
+        axes: &AxesSettings,
+        pointer_coordinate: PlotPoint,
+    ) {
+        let [x, y] = axes.to_data([pointer_coordinate.x, pointer_coordinate.y]);
+        let radius = self.data.erase_settings().radius;
+        let outline: Vec<[f64; 2]> = (0..=Self::ERASER_OUTLINE_SEGMENTS)
+            .filter_map(|i| {
+                let angle = TAU * i as f64 / Self::ERASER_OUTLINE_SEGMENTS as f64;
+                axes.to_plot([x + radius * angle.cos(), y + radius * angle.sin()])
+            })
+            .collect();
+        plot_ui.line(Line::new(outline).name("Eraser"));
+    }
+}
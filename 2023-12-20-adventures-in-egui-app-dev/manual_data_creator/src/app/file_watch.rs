@@ -0,0 +1,274 @@
+//! Watching the files points were imported from, so the app can be used as a live viewer of files written by other programs
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use egui::Checkbox;
+
+use super::{
+    files::{self, NamedFile},
+    ManualDataCreatorApp,
+};
+use crate::dataset::FileFormat;
+
+/// Persisted choices for keeping watched files and the points in sync
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Default)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct WatchSettings {
+    /// Reload without asking when another program changes the file
+    pub should_auto_reload: bool,
+    /// Save the points to the file after every change
+    pub should_write_back: bool,
+}
+
+/// Modification time and size of a file, `None` if it cannot be read (for example while it is being replaced)
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Files imported from disk that are checked for changes made by other programs
+#[derive(Debug, PartialEq)]
+pub struct WatchedFiles {
+    /// One data file or an `X.npy` file with its `y.npy` file
+    paths: Vec<PathBuf>,
+    /// Stamps of `paths` when the app last read or wrote them
+    stamps: Vec<FileStamp>,
+    /// Format to use when writing back, `None` if the files cannot be written (MAT-files and NumPy pairs)
+    write_format: Option<FileFormat>,
+    /// [`crate::dataset::UndoManager::change_count`] when the points were last the same as in the files
+    saved_change_count: u64,
+    /// App time of the last check for changes
+    last_check: f64,
+    /// Set when another program changed the files and the user has not chosen whether to reload yet
+    is_reload_offered: bool,
+}
+
+impl WatchedFiles {
+    /// Seconds between checks for changes (and writing back)
+    const CHECK_INTERVAL: f64 = 1.0;
+
+    /// `files` are the names and contents read from `paths`
+    pub fn new(paths: Vec<PathBuf>, files: &[NamedFile], change_count: u64) -> Self {
+        let write_format = match files {
+            [(name, bytes)] => FileFormat::from_file_name(name, bytes),
+            _ => None,
+        };
+        Self {
+            stamps: paths.iter().map(|path| file_stamp(path)).collect(),
+            paths,
+            write_format,
+            saved_change_count: change_count,
+            last_check: f64::NEG_INFINITY,
+            is_reload_offered: false,
+        }
+    }
+
+    /// Returns `true` if any of the files changed since the app last read or wrote them
+    fn is_changed_on_disk(&self) -> bool {
+        self.paths
+            .iter()
+            .zip(&self.stamps)
+            .any(|(path, stamp)| file_stamp(path) != *stamp)
+    }
+
+    /// Remembers the files as they are now so that only later changes are noticed
+    fn acknowledge_disk_changes(&mut self) {
+        self.stamps = self.paths.iter().map(|path| file_stamp(path)).collect();
+        self.is_reload_offered = false;
+    }
+
+    /// Records that the points and the files are the same
+    fn mark_in_sync(&mut self, change_count: u64) {
+        self.acknowledge_disk_changes();
+        self.saved_change_count = change_count;
+    }
+
+    pub fn name(&self) -> String {
+        self.paths
+            .iter()
+            .map(|path| format!("{:?}", path.display()))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+}
+
+impl ManualDataCreatorApp {
+    pub(super) fn file_watch_options(&mut self, ui: &mut egui::Ui) {
+        match self.watched_files.as_ref().map(WatchedFiles::name) {
+            Some(name) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Watching {name}"));
+                    if ui.button("Stop Watching").clicked() {
+                        self.watched_files = None;
+                    }
+                });
+            }
+            None => {
+                ui.label("Files loaded with File → Import… or dropped onto the window are watched for changes made by other programs");
+            }
+        }
+        ui.checkbox(
+            &mut self.watch_settings.should_auto_reload,
+            "Reload without asking when the file changes",
+        )
+        .on_hover_text("Reloading keeps the current zoom and can be undone");
+        let can_write_back = self
+            .watched_files
+            .as_ref()
+            .is_none_or(|watched| watched.write_format.is_some());
+        ui.add_enabled(
+            can_write_back,
+            Checkbox::new(
+                &mut self.watch_settings.should_write_back,
+                "Write every change back to the file",
+            ),
+        )
+        .on_disabled_hover_text("Only possible for files in a format that can be exported");
+    }
+
+    /// Watches `paths` (read as `files`) if the points were replaced with their contents since `change_count`
+    pub(super) fn watch_if_replaced(
+        &mut self,
+        paths: Vec<PathBuf>,
+        files: &[NamedFile],
+        change_count: u64,
+    ) {
+        let new_change_count = self.data.undo_manager().change_count();
+        if new_change_count != change_count {
+            self.watched_files = Some(WatchedFiles::new(paths, files, new_change_count));
+        }
+    }
+
+    /// Checks the watched files for changes and writes changed points back to them
+    pub(super) fn update_file_watch(&mut self, ctx: &egui::Context) {
+        let change_count = self.data.undo_manager().change_count();
+        let Some(watched) = &mut self.watched_files else {
+            return;
+        };
+        ctx.request_repaint_after(Duration::from_secs_f64(WatchedFiles::CHECK_INTERVAL));
+        let now = ctx.input(|i| i.time);
+        if watched.is_reload_offered || now - watched.last_check < WatchedFiles::CHECK_INTERVAL {
+            return;
+        }
+        watched.last_check = now;
+        if watched.is_changed_on_disk() {
+            if self.watch_settings.should_auto_reload {
+                self.reload_watched_files();
+            } else {
+                watched.is_reload_offered = true;
+            }
+        } else if self.watch_settings.should_write_back
+            && change_count != watched.saved_change_count
+        {
+            self.write_back_watched_file();
+        }
+    }
+
+    /// Asks whether to reload the watched files after another program changed them
+    pub(super) fn window_file_changed(&mut self, ctx: &egui::Context) {
+        let Some(watched) = &mut self.watched_files else {
+            return;
+        };
+        if !watched.is_reload_offered {
+            return;
+        }
+        let mut should_reload = false;
+        egui::Window::new("File Changed")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{} was changed by another program", watched.name()));
+                ui.horizontal(|ui| {
+                    should_reload = ui
+                        .button("Reload")
+                        .on_hover_text("Replaces the points (can be undone), the zoom is kept")
+                        .clicked();
+                    if ui.button("Keep Current Points").clicked() {
+                        watched.acknowledge_disk_changes();
+                    }
+                });
+            });
+        if should_reload {
+            self.reload_watched_files();
+        }
+    }
+
+    /// Replaces the points with the contents of the watched files, keeping the current zoom
+    fn reload_watched_files(&mut self) {
+        let Some(watched) = &self.watched_files else {
+            return;
+        };
+        match files::read_paths(&watched.paths) {
+            Ok(files) => {
+                self.restore_plot_bounds = self.plot_bounds;
+                self.import_files(&files);
+            }
+            Err(e) => self.status_msg.add_err(&e),
+        }
+        let change_count = self.data.undo_manager().change_count();
+        if let Some(watched) = &mut self.watched_files {
+            watched.mark_in_sync(change_count);
+        }
+    }
+
+    fn write_back_watched_file(&mut self) {
+        let change_count = self.data.undo_manager().change_count();
+        let Some(watched) = &mut self.watched_files else {
+            return;
+        };
+        let (Some(format), [path]) = (watched.write_format, watched.paths.as_slice()) else {
+            return;
+        };
+        let result = format
+            .write(self.data.points(), self.data.dimensions())
+            .and_then(|contents| std::fs::write(path, contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.status_msg.add_err(&format!(
+                "Stopped writing back to {}. Failed to write: {e}",
+                watched.name()
+            ));
+            self.watch_settings.should_write_back = false;
+        }
+        watched.mark_in_sync(change_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_on_disk_noticed_until_acknowledged() {
+        let path = std::env::temp_dir().join(format!(
+            "manual_data_creator_watch_{}.csv",
+            std::process::id()
+        ));
+        let contents = b"x,y,label\n1,2,Normal\n".to_vec();
+        std::fs::write(&path, &contents).expect("writes");
+        let files = [("points.csv".to_string(), contents)];
+        let mut watched = WatchedFiles::new(vec![path.clone()], &files, 0);
+        assert_eq!(watched.write_format, Some(FileFormat::Csv));
+        assert!(!watched.is_changed_on_disk());
+
+        std::fs::write(&path, b"x,y,label\n1,2,Normal\n3,4,Anomaly\n").expect("writes");
+        assert!(watched.is_changed_on_disk());
+        watched.acknowledge_disk_changes();
+        assert!(!watched.is_changed_on_disk());
+
+        std::fs::remove_file(&path).expect("removes");
+        assert!(watched.is_changed_on_disk());
+    }
+
+    #[test]
+    fn numpy_pairs_not_written_back() {
+        let files = [("X.npy".to_string(), vec![]), ("y.npy".to_string(), vec![])];
+        let watched = WatchedFiles::new(vec!["X.npy".into(), "y.npy".into()], &files, 0);
+        assert_eq!(watched.write_format, None);
+    }
+}
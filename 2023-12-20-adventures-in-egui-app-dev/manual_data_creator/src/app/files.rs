@@ -0,0 +1,875 @@
+//! Loading and saving files, using dialogs on native and downloads / uploads on web
+
+use super::{
+    plot_export::{Figure, ImageFormat},
+    ManualDataCreatorApp,
+};
+#[cfg(target_arch = "wasm32")]
+use crate::dataset::data_io::share_link::{self, SharedData};
+use crate::dataset::{
+    data_io::{self, FileFormat},
+    DataPoint, ReferenceTrace, SnapMode,
+};
+
+#[cfg(target_arch = "wasm32")]
+pub use self::web::{FilePurpose, PendingUpload};
+
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+/// File name and contents
+pub type NamedFile = (String, Vec<u8>);
+
+/// Points read from dropped files, waiting for the user to choose between replacing and appending to the current points
+#[derive(PartialEq, Debug)]
+pub struct DroppedData {
+    source: String,
+    points: Vec<DataPoint>,
+    dimensions: usize,
+    /// Paths and contents of the dropped files, `None` if any of them were not read from disk
+    #[cfg(not(target_arch = "wasm32"))]
+    files_on_disk: Option<(Vec<std::path::PathBuf>, Vec<NamedFile>)>,
+}
+
+/// What to do with points read from dropped files
+enum DropAction {
+    Replace,
+    Append,
+    Compare,
+}
+
+impl ManualDataCreatorApp {
+    pub(super) fn file_menu(&mut self, ui: &mut egui::Ui) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if ui.button("Load Reference Trace (CSV)…").clicked() {
+                ui.close_menu();
+                self.load_reference_trace();
+            }
+            if ui.button("Import…").clicked() {
+                ui.close_menu();
+                self.import_file();
+            }
+            if ui
+                .button("Compare With…")
+                .on_hover_text(
+                    "Shows how another version of the dataset differs from the current points",
+                )
+                .clicked()
+            {
+                ui.close_menu();
+                self.compare_file();
+            }
+            ui.menu_button("Export", |ui| {
+                for format in FileFormat::ALL {
+                    if ui.button(format!("{format}…")).clicked() {
+                        ui.close_menu();
+                        self.export(format, false);
+                    }
+                }
+                if ui.button("NumPy X.npy and y.npy…").clicked() {
+                    ui.close_menu();
+                    self.export_npy();
+                }
+                ui.separator();
+                for format in FileFormat::ALL {
+                    if ui.button(format!("One {format} file per split…")).clicked() {
+                        ui.close_menu();
+                        self.export(format, true);
+                    }
+                }
+                ui.separator();
+                for format in ImageFormat::ALL {
+                    if ui.button(format!("Plot as {format}…")).clicked() {
+                        ui.close_menu();
+                        self.export_image(format);
+                    }
+                }
+            });
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            if ui.button("Load Reference Trace (CSV)…").clicked() {
+                ui.close_menu();
+                self.pending_upload.pick(
+                    FilePurpose::ReferenceTrace,
+                    &["csv", "tsv", "txt"],
+                    ui.ctx(),
+                );
+            }
+            if ui
+                .button("Upload…")
+                .on_hover_text("Select X.npy and y.npy together to upload NumPy arrays. Files can also be dropped onto the page")
+                .clicked()
+            {
+                ui.close_menu();
+                let mut extensions = data_io::READABLE_EXTENSIONS.to_vec();
+                extensions.push("npy");
+                self.pending_upload
+                    .pick(FilePurpose::Data, &extensions, ui.ctx());
+            }
+            if ui
+                .button("Compare With…")
+                .on_hover_text("Upload another version of the dataset to see how it differs from the current points")
+                .clicked()
+            {
+                ui.close_menu();
+                let mut extensions = data_io::READABLE_EXTENSIONS.to_vec();
+                extensions.push("npy");
+                self.pending_upload
+                    .pick(FilePurpose::Compare, &extensions, ui.ctx());
+            }
+            if ui
+                .button("Copy Share Link")
+                .on_hover_text("Copies a link that opens this page with the current points (metadata is not included)")
+                .clicked()
+            {
+                ui.close_menu();
+                self.copy_share_link(ui.ctx());
+            }
+            ui.menu_button("Download", |ui| {
+                for format in FileFormat::ALL {
+                    if ui.button(format.to_string()).clicked() {
+                        ui.close_menu();
+                        self.download(format, false);
+                    }
+                }
+                if ui.button("NumPy X.npy and y.npy").clicked() {
+                    ui.close_menu();
+                    self.download_npy();
+                }
+                ui.separator();
+                for format in FileFormat::ALL {
+                    if ui.button(format!("One {format} file per split")).clicked() {
+                        ui.close_menu();
+                        self.download(format, true);
+                    }
+                }
+                ui.separator();
+                for format in ImageFormat::ALL {
+                    if ui.button(format!("Plot as {format}")).clicked() {
+                        ui.close_menu();
+                        self.download_image(format);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Draws the plot (without the selection) as set in the image export options
+    fn render_image(&self, format: ImageFormat) -> Result<Vec<u8>, String> {
+        let series = self.marker_series(false);
+        let axes = self.data.axes();
+        let bounds = match self.plot_bounds {
+            Some(bounds) if !self.image_settings.is_fitted_to_points => bounds.into(),
+            _ => Figure::fit_bounds(&series),
+        };
+        let figure = Figure {
+            series: &series,
+            x_axis: &axes.x,
+            y_axis: &axes.y,
+            bounds,
+            aspect: axes.effective_aspect(),
+            settings: &self.image_settings,
+        };
+        match format {
+            ImageFormat::Svg => Ok(figure.to_svg().into_bytes()),
+            ImageFormat::Png => figure.to_png(),
+        }
+    }
+
+    /// Writes all points (or one file per split) in `format`, returns the name and contents of each file
+    ///
+    /// File names start with `stem` and splits add their suffix (data_train.csv)
+    fn export_files(
+        &self,
+        format: FileFormat,
+        is_file_per_split: bool,
+        stem: &str,
+    ) -> Result<Vec<NamedFile>, String> {
+        let extension = format.extension();
+        let files = if is_file_per_split {
+            format
+                .write_splits(self.data.points(), self.data.dimensions())?
+                .into_iter()
+                .map(|(suffix, contents)| (format!("{stem}_{suffix}.{extension}"), contents))
+                .collect::<Vec<_>>()
+        } else {
+            let contents = format.write(self.data.points(), self.data.dimensions())?;
+            vec![(format!("{stem}.{extension}"), contents)]
+        };
+        if files.is_empty() {
+            Err("Nothing exported. No points have been assigned to a split".to_string())
+        } else {
+            Ok(files)
+        }
+    }
+
+    /// Replaces the points with those read from `files` (name and contents)
+    pub(super) fn import_files(&mut self, files: &[NamedFile]) {
+        let source = source_name(files);
+        match read_files(files) {
+            Ok((points, dimensions)) => {
+                self.data
+                    .import_points(points, dimensions, &source, &mut self.status_msg)
+            }
+            Err(errors) => self.add_read_errors(&source, errors),
+        }
+    }
+
+    /// Stops watching files if the points changed since `change_count`, called when they were replaced from somewhere
+    /// else as writing back would put the unrelated points over the watched files
+    pub(super) fn stop_watching_if_changed(&mut self, change_count: u64) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.data.undo_manager().change_count() != change_count {
+            if let Some(watched) = self.watched_files.take() {
+                self.status_msg.add_msg(&format!(
+                    "Stopped watching {} as the points were replaced",
+                    watched.name()
+                ));
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = change_count; // Files are not watched on web
+    }
+
+    /// Replaces the points with the dropped points, watching the dropped files if they were read from disk
+    fn replace_with_dropped(&mut self, dropped: DroppedData) {
+        let change_count = self.data.undo_manager().change_count();
+        self.data.import_points(
+            dropped.points,
+            dropped.dimensions,
+            &dropped.source,
+            &mut self.status_msg,
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((paths, files)) = dropped.files_on_disk {
+            self.watch_if_replaced(paths, &files, change_count);
+            return;
+        }
+        self.stop_watching_if_changed(change_count);
+    }
+
+    /// Shows how the points read from `files` (name and contents) differ from the current points
+    fn compare_files(&mut self, files: &[NamedFile]) {
+        let source = source_name(files);
+        match read_files(files) {
+            Ok((points, dimensions)) => self.start_diff(source, points, dimensions),
+            Err(errors) => self.add_read_errors(&source, errors),
+        }
+    }
+
+    /// Reports each problem found while reading `source` on its own line
+    fn add_read_errors(&mut self, source: &str, errors: Vec<String>) {
+        self.status_msg
+            .add_err(&format!("Unable to load points from {source:?}"));
+        for error in errors {
+            self.status_msg.add_err(&error);
+        }
+    }
+
+    /// Reads files dropped onto the window. If there are points already asks whether to replace or append to them
+    pub(super) fn receive_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        if !dropped_files.is_empty() {
+            let dropped_names: Vec<String> = dropped_files.iter().map(dropped_file_name).collect();
+            let mut files = vec![];
+            let mut paths = vec![];
+            for file in &dropped_files {
+                match read_dropped_file(file, &dropped_names) {
+                    Ok(read) => {
+                        for (file, path) in read {
+                            files.push(file);
+                            paths.extend(path);
+                        }
+                    }
+                    Err(e) => {
+                        self.status_msg.add_err(&e);
+                        return;
+                    }
+                }
+            }
+            let source = source_name(&files);
+            match read_files(&files) {
+                Ok((points, dimensions)) => {
+                    #[cfg(target_arch = "wasm32")]
+                    let _ = paths; // Dropped files are only watched on native
+                    let dropped = DroppedData {
+                        source,
+                        points,
+                        dimensions,
+                        #[cfg(not(target_arch = "wasm32"))]
+                        files_on_disk: (paths.len() == files.len()).then_some((paths, files)),
+                    };
+                    if self.data.is_empty() {
+                        self.replace_with_dropped(dropped);
+                    } else {
+                        self.dropped_data = Some(dropped);
+                    }
+                }
+                Err(errors) => self.add_read_errors(&source, errors),
+            }
+        }
+
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("file_drop_target"),
+            ));
+            let screen_rect = ctx.screen_rect();
+            painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(192));
+            painter.text(
+                screen_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop to load the points",
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+
+    /// Asks whether points read from dropped files replace or are appended to the current points
+    pub(super) fn window_dropped_data(&mut self, ctx: &egui::Context) {
+        let Some(dropped) = &self.dropped_data else {
+            return;
+        };
+        let mut choice = None;
+        let mut is_cancelled = false;
+        egui::Window::new("Load Dropped Points")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Read {} points with {} dimensions from {:?}",
+                    dropped.points.len(),
+                    dropped.dimensions,
+                    dropped.source
+                ));
+                ui.label(format!(
+                    "Replace the current {} points, append to them or compare with them?",
+                    self.data.points().len()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Replace").clicked() {
+                        choice = Some(DropAction::Replace);
+                    }
+                    let same_dimensions_hint = format!(
+                        "Only possible if the number of dimensions matches the current points ({})",
+                        self.data.dimensions()
+                    );
+                    let is_same_dimensions = dropped.dimensions == self.data.dimensions();
+                    if ui
+                        .add_enabled(is_same_dimensions, egui::Button::new("Append"))
+                        .on_disabled_hover_text(&same_dimensions_hint)
+                        .clicked()
+                    {
+                        choice = Some(DropAction::Append);
+                    }
+                    if ui
+                        .add_enabled(is_same_dimensions, egui::Button::new("Compare"))
+                        .on_hover_text("Shows the differences and lets you accept them one by one")
+                        .on_disabled_hover_text(&same_dimensions_hint)
+                        .clicked()
+                    {
+                        choice = Some(DropAction::Compare);
+                    }
+                    is_cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        if is_cancelled {
+            self.dropped_data = None;
+        } else if let Some(action) = choice {
+            let dropped = self.dropped_data.take().expect("checked above");
+            match action {
+                DropAction::Replace => self.replace_with_dropped(dropped),
+                DropAction::Append => {
+                    self.data
+                        .append_points(dropped.points, &dropped.source, &mut self.status_msg)
+                }
+                DropAction::Compare => {
+                    self.start_diff(dropped.source, dropped.points, dropped.dimensions)
+                }
+            }
+        }
+    }
+
+    /// Parses `text` as a table and snaps new points to it
+    fn load_reference_trace_text(&mut self, name: String, text: &str) {
+        match data_io::NumericTable::parse(text) {
+            Ok(table) => {
+                let trace = ReferenceTrace::new(name, table);
+                self.status_msg.add_msg(&format!(
+                    "Loaded {} samples from {:?}. New points snap to the reference trace",
+                    trace.samples().len(),
+                    trace.name
+                ));
+                self.data.set_reference_trace(Some(trace));
+                let settings = self.data.snap_settings_mut();
+                settings.is_enabled = true;
+                settings.mode = SnapMode::ReferenceTrace;
+            }
+            Err(errors) => {
+                self.status_msg
+                    .add_err(&format!("Failed to load {name:?} as a reference trace"));
+                for error in errors {
+                    self.status_msg.add_err(&error.to_string());
+                }
+            }
+        }
+    }
+
+    /// Asks where to save then writes all points (or one file per split) in `format`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export(&mut self, format: FileFormat, is_file_per_split: bool) {
+        let extension = format.extension();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.to_string(), &[extension])
+            .set_file_name(format!("data.{extension}"))
+            .save_file()
+        else {
+            return; // Cancelled by user
+        };
+        let stem = path
+            .file_stem()
+            .map_or_else(|| "data".into(), |stem| stem.to_string_lossy());
+        let files = match self.export_files(format, is_file_per_split, &stem) {
+            Ok(files) => files,
+            Err(e) => {
+                self.status_msg.add_err(&e);
+                return;
+            }
+        };
+        for (name, contents) in files {
+            let path = path.with_file_name(name);
+            match std::fs::write(&path, contents) {
+                Ok(()) => self
+                    .status_msg
+                    .add_msg(&format!("Exported to {:?}", path.display())),
+                Err(e) => self
+                    .status_msg
+                    .add_err(&format!("Failed to write {:?}: {e}", path.display())),
+            }
+        }
+    }
+
+    /// Asks where to save then writes an image of the plot
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_image(&mut self, format: ImageFormat) {
+        let extension = format.extension();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.to_string(), &[extension])
+            .set_file_name(format!("plot.{extension}"))
+            .save_file()
+        else {
+            return; // Cancelled by user
+        };
+        let result = self
+            .render_image(format)
+            .and_then(|contents| std::fs::write(&path, contents).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => self
+                .status_msg
+                .add_msg(&format!("Exported plot to {:?}", path.display())),
+            Err(e) => self.status_msg.add_err(&format!(
+                "Failed to export plot to {:?}: {e}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Asks for a folder then writes `X.npy` and `y.npy` into it
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_npy(&mut self) {
+        let Some(folder) = rfd::FileDialog::new().pick_folder() else {
+            return; // Cancelled by user
+        };
+        let (features, labels) = data_io::write_npy(self.data.points(), self.data.dimensions());
+        for (name, contents) in [
+            (data_io::NPY_FEATURES_FILE_NAME, features),
+            (data_io::NPY_LABELS_FILE_NAME, labels),
+        ] {
+            let path = folder.join(name);
+            if let Err(e) = std::fs::write(&path, contents) {
+                self.status_msg
+                    .add_err(&format!("Failed to write {:?}: {e}", path.display()));
+                return;
+            }
+        }
+        self.status_msg.add_msg(&format!(
+            "Exported X.npy and y.npy to {:?}",
+            folder.display()
+        ));
+    }
+
+    /// Asks for a file in one of the readable formats (or an `X.npy` file with `y.npy` next to it) and replaces the points with its contents
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_file(&mut self) {
+        let Some(paths) = pick_data_paths() else {
+            return; // Cancelled by user
+        };
+        let files = match read_paths(&paths) {
+            Ok(files) => files,
+            Err(e) => {
+                self.status_msg.add_err(&e);
+                return;
+            }
+        };
+        let change_count = self.data.undo_manager().change_count();
+        self.import_files(&files);
+        self.watch_if_replaced(paths, &files, change_count);
+    }
+
+    /// Asks for another version of the dataset (like [`Self::import_file`]) and shows how it differs from the current points
+    #[cfg(not(target_arch = "wasm32"))]
+    fn compare_file(&mut self) {
+        let Some(paths) = pick_data_paths() else {
+            return; // Cancelled by user
+        };
+        match read_paths(&paths) {
+            Ok(files) => self.compare_files(&files),
+            Err(e) => self.status_msg.add_err(&e),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_reference_trace(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv", "tsv", "txt"])
+            .pick_file()
+        else {
+            return; // Cancelled by user
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let name = path.file_name().map_or_else(
+                    || "Reference".to_string(),
+                    |name| name.to_string_lossy().into(),
+                );
+                self.load_reference_trace_text(name, &text);
+            }
+            Err(e) => self
+                .status_msg
+                .add_err(&format!("Failed to read {:?}: {e}", path.display())),
+        }
+    }
+
+    /// Downloads all points (or one file per split) in `format`
+    #[cfg(target_arch = "wasm32")]
+    fn download(&mut self, format: FileFormat, is_file_per_split: bool) {
+        let files = match self.export_files(format, is_file_per_split, "data") {
+            Ok(files) => files,
+            Err(e) => {
+                self.status_msg.add_err(&e);
+                return;
+            }
+        };
+        for (name, contents) in files {
+            match web::download(&name, &contents) {
+                Ok(()) => self.status_msg.add_msg(&format!("Downloaded {name:?}")),
+                Err(e) => self
+                    .status_msg
+                    .add_err(&format!("Failed to download {name:?}: {e}")),
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn download_npy(&mut self) {
+        let (features, labels) = data_io::write_npy(self.data.points(), self.data.dimensions());
+        for (name, contents) in [
+            (data_io::NPY_FEATURES_FILE_NAME, features),
+            (data_io::NPY_LABELS_FILE_NAME, labels),
+        ] {
+            if let Err(e) = web::download(name, &contents) {
+                self.status_msg
+                    .add_err(&format!("Failed to download {name:?}: {e}"));
+                return;
+            }
+        }
+        self.status_msg.add_msg("Downloaded X.npy and y.npy");
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn download_image(&mut self, format: ImageFormat) {
+        let name = format!("plot.{}", format.extension());
+        match self
+            .render_image(format)
+            .and_then(|contents| web::download(&name, &contents))
+        {
+            Ok(()) => self.status_msg.add_msg(&format!("Downloaded {name:?}")),
+            Err(e) => self
+                .status_msg
+                .add_err(&format!("Failed to download {name:?}: {e}")),
+        }
+    }
+
+    /// Copies a link to this page with the points encoded in the fragment
+    #[cfg(target_arch = "wasm32")]
+    fn copy_share_link(&mut self, ctx: &egui::Context) {
+        let shared = SharedData::new(
+            self.data.points(),
+            self.data.dimensions(),
+            self.data.is_time_series(),
+            self.data.snap_settings().clone(),
+        );
+        let encoded = match shared.encode() {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                self.status_msg.add_err(&e);
+                return;
+            }
+        };
+        let Some(page_url) = web::page_url() else {
+            self.status_msg
+                .add_err("Unable to get the URL of this page");
+            return;
+        };
+        let link = format!("{page_url}{}{encoded}", share_link::FRAGMENT_PREFIX);
+        self.status_msg.add_msg(&format!(
+            "Share link for {} points copied to the clipboard ({} characters)",
+            self.data.points().len(),
+            link.len()
+        ));
+        ctx.output_mut(|o| o.copied_text = link);
+    }
+
+    /// Replaces the points with those encoded in the URL fragment (if any) then removes them from the URL
+    #[cfg(target_arch = "wasm32")]
+    pub(super) fn load_share_link(&mut self) {
+        let Some(encoded) = web::fragment().and_then(|fragment| {
+            fragment
+                .strip_prefix(share_link::FRAGMENT_PREFIX)
+                .map(String::from)
+        }) else {
+            return;
+        };
+        // Otherwise reloading the page would load the shared points again
+        web::clear_fragment();
+        match SharedData::decode(&encoded) {
+            Ok(shared) => {
+                self.data
+                    .set_time_series(shared.is_time_series, &mut self.status_msg);
+                *self.data.snap_settings_mut() = shared.snap_settings.clone();
+                self.data.import_points(
+                    shared.points(),
+                    shared.dimensions,
+                    "share link",
+                    &mut self.status_msg,
+                );
+            }
+            Err(e) => self
+                .status_msg
+                .add_err(&format!("Unable to load the shared link. {e}")),
+        }
+    }
+
+    /// Loads files picked with the upload dialog
+    #[cfg(target_arch = "wasm32")]
+    pub(super) fn receive_uploads(&mut self) {
+        if let Some((purpose, files)) = self.pending_upload.take() {
+            self.load_uploaded(purpose, files);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load_uploaded(&mut self, purpose: FilePurpose, files: Vec<NamedFile>) {
+        match purpose {
+            FilePurpose::Data => self.import_files(&files),
+            FilePurpose::Compare => self.compare_files(&files),
+            FilePurpose::ReferenceTrace => {
+                for (name, bytes) in files {
+                    match String::from_utf8(bytes) {
+                        Ok(text) => self.load_reference_trace_text(name, &text),
+                        Err(_) => self
+                            .status_msg
+                            .add_err(&format!("{name:?} is not a text file")),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Asks for a file in one of the readable formats, for an `X.npy` file the path of its `y.npy` file is included
+///
+/// Returns `None` if cancelled
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_data_paths() -> Option<Vec<std::path::PathBuf>> {
+    let mut extensions = data_io::READABLE_EXTENSIONS.to_vec();
+    extensions.push("npy");
+    let path = rfd::FileDialog::new()
+        .add_filter("Data", &extensions)
+        .pick_file()?;
+    let mut paths = vec![path.clone()];
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    if let (Some(labels_name), true) = (
+        labels_file_name(&file_name),
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("npy")),
+    ) {
+        paths.push(path.with_file_name(labels_name));
+    }
+    Some(paths)
+}
+
+/// Reads the files at `paths`, returns their names and contents
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_paths(paths: &[std::path::PathBuf]) -> Result<Vec<NamedFile>, String> {
+    paths
+        .iter()
+        .map(|path| {
+            let bytes = std::fs::read(path)
+                .map_err(|e| format!("Failed to read {:?}: {e}", path.display()))?;
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            Ok((name.into_owned(), bytes))
+        })
+        .collect()
+}
+
+/// Labels are expected in the file with the same name but starting with y instead of X (X_train.npy -> y_train.npy)
+fn labels_file_name(features_file_name: &str) -> Option<String> {
+    features_file_name
+        .strip_prefix('X')
+        .map(|rest| format!("y{rest}"))
+}
+
+/// Describes where points were read from for messages and the undo history
+fn source_name(files: &[NamedFile]) -> String {
+    files
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Reads points from a single file in one of the readable formats or an `X.npy` file with its `y.npy` file
+fn read_files(files: &[NamedFile]) -> Result<(Vec<DataPoint>, usize), Vec<String>> {
+    let is_npy = |(name, bytes): &NamedFile| data_io::detect_extension(name, bytes) == Some("npy");
+    match files {
+        [file] if !is_npy(file) => data_io::read_file(&file.0, &file.1),
+        _ if !files.is_empty() && files.iter().all(is_npy) => {
+            read_npy_pair(files).map_err(|e| vec![e])
+        }
+        _ => Err(vec![
+            "Expected one data file or an X.npy file with its y.npy file".to_string(),
+        ]),
+    }
+}
+
+fn dropped_file_name(file: &egui::DroppedFile) -> String {
+    match &file.path {
+        Some(path) if file.name.is_empty() => path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        _ => file.name.clone(),
+    }
+}
+
+/// Returns the contents of a dropped file, on web they are included and on native read from its path (returned too)
+///
+/// On native the labels file next to a dropped `X.npy` file is included (if it was not dropped too)
+fn read_dropped_file(
+    file: &egui::DroppedFile,
+    dropped_names: &[String],
+) -> Result<Vec<(NamedFile, Option<std::path::PathBuf>)>, String> {
+    let name = dropped_file_name(file);
+    if let Some(bytes) = &file.bytes {
+        return Ok(vec![((name, bytes.to_vec()), None)]);
+    }
+    let Some(path) = &file.path else {
+        return Err(format!("Unable to read dropped file {name:?}"));
+    };
+    let read = |path: &std::path::Path| {
+        std::fs::read(path).map_err(|e| format!("Failed to read {:?}: {e}", path.display()))
+    };
+    let bytes = read(path)?;
+    let mut result = vec![];
+    if let Some(labels_name) = labels_file_name(&name)
+        .filter(|labels_name| !dropped_names.contains(labels_name))
+        .filter(|_| data_io::detect_extension(&name, &bytes) == Some("npy"))
+    {
+        let labels_path = path.with_file_name(&labels_name);
+        if labels_path.exists() {
+            result.push(((labels_name, read(&labels_path)?), Some(labels_path)));
+        }
+    }
+    result.insert(0, ((name, bytes), Some(path.clone())));
+    Ok(result)
+}
+
+/// Finds the features file and its labels file among `files` and reads them
+fn read_npy_pair(files: &[NamedFile]) -> Result<(Vec<DataPoint>, usize), String> {
+    let find = |name: &str| {
+        files
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, bytes)| bytes)
+    };
+    let (features_name, features) = files
+        .iter()
+        .find(|(name, _)| name.starts_with('X'))
+        .ok_or_else(|| {
+            "Expected the features file name to start with X (for example X.npy)".to_string()
+        })?;
+    let labels_name = labels_file_name(features_name).unwrap_or_default();
+    let labels = find(&labels_name)
+        .ok_or_else(|| format!("Expected {labels_name:?} with the labels for {features_name:?}"))?;
+    data_io::read_npy(features, labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("X.npy", Some("y.npy"))]
+    #[case("X_train.npy", Some("y_train.npy"))]
+    #[case("features.npy", None)]
+    fn labels_file_is_found_from_features_file(
+        #[case] features_file_name: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(labels_file_name(features_file_name).as_deref(), expected);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn dropped_files_watched_until_replaced_from_elsewhere() {
+        let path = std::env::temp_dir().join(format!(
+            "manual_data_creator_drop_{}.csv",
+            std::process::id()
+        ));
+        let contents = b"x,y,label\n1,2,Normal\n".to_vec();
+        std::fs::write(&path, &contents).expect("writes");
+        let files = vec![("points.csv".to_string(), contents.clone())];
+        let (points, dimensions) = read_files(&files).expect("reads");
+        let mut app = ManualDataCreatorApp::default();
+        app.watch_settings.should_write_back = true;
+        app.replace_with_dropped(DroppedData {
+            source: "points.csv".to_string(),
+            points: points.clone(),
+            dimensions,
+            files_on_disk: Some((vec![path.clone()], files)),
+        });
+        assert!(app.watched_files.is_some());
+
+        // Dropped without a path, like points from a script or the remote control API
+        let mut other = points;
+        other[0].x = 9.0;
+        app.replace_with_dropped(DroppedData {
+            source: "other.csv".to_string(),
+            points: other,
+            dimensions,
+            files_on_disk: None,
+        });
+        assert!(app.watched_files.is_none());
+        app.update_file_watch(&egui::Context::default());
+        assert_eq!(std::fs::read(&path).expect("reads"), contents);
+        std::fs::remove_file(&path).expect("removes");
+    }
+}
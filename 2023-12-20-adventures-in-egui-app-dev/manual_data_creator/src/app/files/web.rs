@@ -0,0 +1,105 @@
+//! Browser downloads, uploads and access to the page URL
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::JsCast as _;
+
+use super::NamedFile;
+
+/// What an uploaded file is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilePurpose {
+    Data,
+    ReferenceTrace,
+    /// Another version of the dataset to compare with
+    Compare,
+}
+
+type Upload = (FilePurpose, Vec<NamedFile>);
+
+/// Files picked in the browser, filled in asynchronously once they have been read
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PendingUpload(Rc<RefCell<Option<Upload>>>);
+
+impl PendingUpload {
+    /// Opens the browser file picker, the files are available from [`Self::take`] after they have been read
+    pub fn pick(&self, purpose: FilePurpose, extensions: &[&str], ctx: &egui::Context) {
+        let dialog = rfd::AsyncFileDialog::new().add_filter("Data", extensions);
+        let slot = self.0.clone();
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let Some(handles) = dialog.pick_files().await else {
+                return; // Cancelled by user
+            };
+            let mut files = vec![];
+            for handle in handles {
+                files.push((handle.file_name(), handle.read().await));
+            }
+            *slot.borrow_mut() = Some((purpose, files));
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn take(&self) -> Option<Upload> {
+        self.0.borrow_mut().take()
+    }
+}
+
+/// How long a download can take to start before its object URL is released
+const REVOKE_DELAY_MS: i32 = 60_000;
+
+/// Has the browser save `contents` as `file_name` in the downloads folder
+pub fn download(file_name: &str, contents: &[u8]) -> Result<(), String> {
+    let error = |e: wasm_bindgen::JsValue| format!("{e:?}");
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document found")?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+    let anchor: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(error)?
+        .dyn_into()
+        .map_err(|_| "Created element is not an anchor")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    // The browser may still be reading the blob after the click returns, so it is only released later
+    let revoke = wasm_bindgen::closure::Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    web_sys::window()
+        .ok_or("No window found")?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            revoke.unchecked_ref(),
+            REVOKE_DELAY_MS,
+        )
+        .map_err(error)?;
+    Ok(())
+}
+
+/// The URL of this page without the fragment
+pub fn page_url() -> Option<String> {
+    let href = web_sys::window()?.location().href().ok()?;
+    Some(href.split('#').next().unwrap_or_default().to_string())
+}
+
+/// The fragment of the URL of this page including the leading `#`
+pub fn fragment() -> Option<String> {
+    web_sys::window()?
+        .location()
+        .hash()
+        .ok()
+        .filter(|hash| !hash.is_empty())
+}
+
+/// Removes the fragment from the URL without reloading the page or adding to the browser history
+pub fn clear_fragment() {
+    let (Some(window), Some(url)) = (web_sys::window(), page_url()) else {
+        return;
+    };
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&url));
+    }
+}
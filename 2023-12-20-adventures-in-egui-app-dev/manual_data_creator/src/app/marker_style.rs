@@ -0,0 +1,387 @@
+//! How the points of each label are drawn, with saved presets and a palette that stays distinguishable with color blindness
+
+use ecolor::Color32;
+use egui_plot::MarkerShape;
+
+use super::{plot_export::MarkerSeries, ManualDataCreatorApp};
+use crate::dataset::DataLabel;
+
+/// Shapes a marker can have, mirrors [`MarkerShape`] which cannot be saved
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Shape {
+    Circle,
+    Diamond,
+    Square,
+    Cross,
+    Plus,
+    Up,
+    Down,
+    Left,
+    Right,
+    Asterisk,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 10] = [
+        Self::Circle,
+        Self::Diamond,
+        Self::Square,
+        Self::Cross,
+        Self::Plus,
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Asterisk,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Shape::Circle => "Circle",
+            Shape::Diamond => "Diamond",
+            Shape::Square => "Square",
+            Shape::Cross => "Cross",
+            Shape::Plus => "Plus",
+            Shape::Up => "Triangle Up",
+            Shape::Down => "Triangle Down",
+            Shape::Left => "Triangle Left",
+            Shape::Right => "Triangle Right",
+            Shape::Asterisk => "Asterisk",
+        }
+    }
+
+    pub fn marker_shape(&self) -> MarkerShape {
+        match self {
+            Shape::Circle => MarkerShape::Circle,
+            Shape::Diamond => MarkerShape::Diamond,
+            Shape::Square => MarkerShape::Square,
+            Shape::Cross => MarkerShape::Cross,
+            Shape::Plus => MarkerShape::Plus,
+            Shape::Up => MarkerShape::Up,
+            Shape::Down => MarkerShape::Down,
+            Shape::Left => MarkerShape::Left,
+            Shape::Right => MarkerShape::Right,
+            Shape::Asterisk => MarkerShape::Asterisk,
+        }
+    }
+
+    /// Returns `true` if the shape is drawn only with lines, so filling and outlining make no difference
+    pub fn is_lines_only(&self) -> bool {
+        matches!(self, Shape::Cross | Shape::Plus | Shape::Asterisk)
+    }
+}
+
+/// How the points of one label are drawn, the color is kept separately
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct MarkerStyle {
+    pub shape: Shape,
+    /// Multiplies the point display radius
+    pub size: f32,
+    pub is_filled: bool,
+    /// Draw a border of `outline_color` around the marker
+    pub has_outline: bool,
+    pub outline_color: Color32,
+    /// From 0 (invisible) to 1 (opaque)
+    pub opacity: f32,
+}
+
+impl Default for MarkerStyle {
+    fn default() -> Self {
+        Self {
+            shape: Shape::Circle,
+            size: 1.0,
+            is_filled: true,
+            has_outline: false,
+            outline_color: Color32::BLACK,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl MarkerStyle {
+    /// Series (one for the marker and possibly one for its outline) drawing `points` (plot space)
+    pub fn series(
+        &self,
+        name: String,
+        points: Vec<[f64; 2]>,
+        radius: f32,
+        color: Color32,
+    ) -> Vec<MarkerSeries> {
+        let with_opacity = |color: Color32| {
+            let [r, g, b, a] = color.to_srgba_unmultiplied();
+            let alpha = f32::from(a) * self.opacity.clamp(0.0, 1.0);
+            Color32::from_rgba_unmultiplied(r, g, b, alpha.round() as u8)
+        };
+        let radius = radius * self.size;
+        let outline = (self.has_outline && !self.shape.is_lines_only()).then(|| MarkerSeries {
+            name: String::new(), // Kept out of the legend
+            points: points.clone(),
+            radius,
+            shape: self.shape.marker_shape(),
+            color: Some(with_opacity(self.outline_color)),
+            is_filled: false,
+        });
+        let mut result = vec![MarkerSeries {
+            name,
+            points,
+            radius,
+            shape: self.shape.marker_shape(),
+            color: Some(with_opacity(color)),
+            is_filled: self.is_filled,
+        }];
+        result.extend(outline); // Drawn on top so it stays visible on filled markers
+        result
+    }
+}
+
+/// Where the colors of the labels come from
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Palette {
+    /// The colors chosen by the user
+    #[default]
+    Custom,
+    /// Colors from the Okabe-Ito palette, distinguishable with the common forms of color blindness
+    ColorblindSafe,
+}
+
+impl Palette {
+    const COLORBLIND_NORMAL: Color32 = Color32::from_rgb(0, 114, 178);
+    const COLORBLIND_ANOMALY: Color32 = Color32::from_rgb(213, 94, 0);
+}
+
+/// The styles of all labels
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct LabelStyles {
+    pub normal: MarkerStyle,
+    pub anomaly: MarkerStyle,
+    pub palette: Palette,
+}
+
+impl Default for LabelStyles {
+    fn default() -> Self {
+        Self {
+            normal: MarkerStyle {
+                shape: Shape::Plus,
+                ..Default::default()
+            },
+            anomaly: MarkerStyle {
+                shape: Shape::Asterisk,
+                ..Default::default()
+            },
+            palette: Palette::Custom,
+        }
+    }
+}
+
+impl LabelStyles {
+    pub fn get_mut(&mut self, label: DataLabel) -> &mut MarkerStyle {
+        match label {
+            DataLabel::Normal => &mut self.normal,
+            DataLabel::Anomaly => &mut self.anomaly,
+        }
+    }
+}
+
+/// Named marker settings that can be applied again later
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+pub struct StylePreset {
+    pub name: String,
+    pub marker_radius: f32,
+    pub normal_color: Color32,
+    pub anom_color: Color32,
+    pub styles: LabelStyles,
+}
+
+impl ManualDataCreatorApp {
+    /// Color the points with `label` are drawn with (before opacity is applied)
+    pub(super) fn label_color(&self, label: DataLabel) -> Color32 {
+        match (self.label_styles.palette, label) {
+            (Palette::Custom, DataLabel::Normal) => self.normal_color,
+            (Palette::Custom, DataLabel::Anomaly) => self.anom_color,
+            (Palette::ColorblindSafe, DataLabel::Normal) => Palette::COLORBLIND_NORMAL,
+            (Palette::ColorblindSafe, DataLabel::Anomaly) => Palette::COLORBLIND_ANOMALY,
+        }
+    }
+
+    pub(super) fn marker_style_options(&mut self, ui: &mut egui::Ui) {
+        let is_custom_palette = self.label_styles.palette == Palette::Custom;
+        ui.horizontal(|ui| {
+            ui.label("Colors: ");
+            ui.radio_value(&mut self.label_styles.palette, Palette::Custom, "Custom");
+            ui.radio_value(
+                &mut self.label_styles.palette,
+                Palette::ColorblindSafe,
+                "Colorblind Safe",
+            )
+            .on_hover_text("Blue and orange that stay distinguishable with the common forms of color blindness");
+        });
+        for label in [DataLabel::Normal, DataLabel::Anomaly] {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}: ", label.as_str()));
+                let color = match label {
+                    DataLabel::Normal => &mut self.normal_color,
+                    DataLabel::Anomaly => &mut self.anom_color,
+                };
+                ui.add_enabled_ui(is_custom_palette, |ui| ui.color_edit_button_srgba(color));
+                let style = self.label_styles.get_mut(label);
+                egui::ComboBox::from_id_source(("marker_shape", label.as_str()))
+                    .selected_text(style.shape.as_str())
+                    .show_ui(ui, |ui| {
+                        for shape in Shape::ALL {
+                            ui.selectable_value(&mut style.shape, shape, shape.as_str());
+                        }
+                    });
+                ui.add(
+                    egui::DragValue::new(&mut style.size)
+                        .speed(0.05)
+                        .clamp_range(0.1..=10.0)
+                        .prefix("Size: ")
+                        .suffix("×"),
+                );
+                ui.add(egui::Slider::new(&mut style.opacity, 0.0..=1.0).text("Opacity"));
+                ui.add_enabled_ui(!style.shape.is_lines_only(), |ui| {
+                    ui.checkbox(&mut style.is_filled, "Filled");
+                    ui.checkbox(&mut style.has_outline, "Outline");
+                    if style.has_outline {
+                        ui.color_edit_button_srgba(&mut style.outline_color);
+                    }
+                })
+                .response
+                .on_disabled_hover_text("The shape is drawn with lines only");
+            });
+        }
+        ui.separator();
+        self.style_presets_ui(ui);
+    }
+
+    fn style_presets_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.style_preset_name)
+                    .hint_text("Preset name")
+                    .desired_width(120.0),
+            );
+            if ui.button("Save Style Preset").clicked() {
+                let name = if self.style_preset_name.is_empty() {
+                    unused_preset_name(&self.style_presets)
+                } else {
+                    std::mem::take(&mut self.style_preset_name)
+                };
+                let preset = StylePreset {
+                    name,
+                    marker_radius: self.marker_radius,
+                    normal_color: self.normal_color,
+                    anom_color: self.anom_color,
+                    styles: self.label_styles.clone(),
+                };
+                match self
+                    .style_presets
+                    .iter_mut()
+                    .find(|p| p.name == preset.name)
+                {
+                    Some(existing) => *existing = preset,
+                    None => self.style_presets.push(preset),
+                }
+            }
+        });
+        let mut index_to_remove = None;
+        for (i, preset) in self.style_presets.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button(&preset.name)
+                    .on_hover_text("Apply this style")
+                    .clicked()
+                {
+                    self.marker_radius = preset.marker_radius;
+                    self.normal_color = preset.normal_color;
+                    self.anom_color = preset.anom_color;
+                    self.label_styles = preset.styles.clone();
+                }
+                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                    index_to_remove = Some(i);
+                }
+            });
+        }
+        if let Some(index) = index_to_remove {
+            self.style_presets.remove(index);
+        }
+    }
+}
+
+/// First `Style N` name that no preset has, so saving without a name never replaces a preset
+fn unused_preset_name(presets: &[StylePreset]) -> String {
+    (1..)
+        .map(|number| format!("Style {number}"))
+        .find(|name| presets.iter().all(|preset| preset.name != *name))
+        .expect("there are fewer presets than numbers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outline_drawn_over_marker_with_opacity() {
+        let style = MarkerStyle {
+            shape: Shape::Square,
+            size: 2.0,
+            has_outline: true,
+            opacity: 0.5,
+            ..Default::default()
+        };
+        let series = style.series(
+            "Normal (1)".to_string(),
+            vec![[0.0, 0.0]],
+            4.0,
+            Color32::RED,
+        );
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].name, "Normal (1)");
+        assert!(
+            series[1].name.is_empty(),
+            "outline is left out of the legend"
+        );
+        assert_eq!(series[0].radius, 8.0);
+        assert_eq!(
+            series[0].color.map(|c| c.to_srgba_unmultiplied()),
+            Some([255, 0, 0, 128])
+        );
+
+        let lines = MarkerStyle {
+            shape: Shape::Plus,
+            has_outline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            lines.series(String::new(), vec![], 1.0, Color32::RED).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn defaults_match_previous_markers() {
+        let styles = LabelStyles::default();
+        assert_eq!(styles.normal.shape.marker_shape(), MarkerShape::Plus);
+        assert_eq!(styles.anomaly.shape.marker_shape(), MarkerShape::Asterisk);
+        assert_eq!(Shape::ALL.len(), MarkerShape::all().len());
+    }
+
+    #[test]
+    fn unnamed_preset_gets_unused_name() {
+        let preset = |name: &str| StylePreset {
+            name: name.to_string(),
+            marker_radius: 1.0,
+            normal_color: Color32::BLUE,
+            anom_color: Color32::RED,
+            styles: LabelStyles::default(),
+        };
+        assert_eq!(unused_preset_name(&[]), "Style 1");
+        // The second preset was removed and the first renamed
+        assert_eq!(
+            unused_preset_name(&[preset("Mine"), preset("Style 3"), preset("Style 1")]),
+            "Style 2"
+        );
+    }
+}
@@ -0,0 +1,606 @@
+//! Rendering the plot to SVG and PNG images for figures, done on the CPU so no window or GPU is needed
+
+use std::fmt::{Display, Write as _};
+
+use ecolor::Color32;
+use egui_plot::{MarkerShape, Points};
+
+use super::log_tick_label;
+use crate::dataset::{AxisSettings, MinMaxPair};
+
+/// A series of markers shown on the plot, kept separate from [`Points`] so it can also be drawn into images
+pub struct MarkerSeries {
+    pub name: String,
+    /// Positions in plot space
+    pub points: Vec<[f64; 2]>,
+    pub radius: f32,
+    pub shape: MarkerShape,
+    /// Picked automatically by the plot if not set
+    pub color: Option<Color32>,
+    pub is_filled: bool,
+}
+
+impl MarkerSeries {
+    pub fn to_plot_points(&self) -> Points {
+        let result = Points::new(self.points.clone())
+            .name(&self.name)
+            .radius(self.radius)
+            .shape(self.shape)
+            .filled(self.is_filled);
+        match self.color {
+            Some(color) => result.color(color),
+            None => result,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    pub const ALL: [ImageFormat; 2] = [Self::Svg, Self::Png];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl Display for ImageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageFormat::Svg => write!(f, "SVG"),
+            ImageFormat::Png => write!(f, "PNG"),
+        }
+    }
+}
+
+/// Options for exported images
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct ImageSettings {
+    /// Show all points instead of the current view of the plot
+    pub is_fitted_to_points: bool,
+    /// Width and height in pixels
+    pub size: [u32; 2],
+    pub should_show_legend: bool,
+}
+
+impl ImageSettings {
+    pub const MAX_SIZE: u32 = 8_000;
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            is_fitted_to_points: false,
+            size: [1200, 900],
+            should_show_legend: true,
+        }
+    }
+}
+
+/// Everything drawn into an image of the plot
+pub struct Figure<'a> {
+    pub series: &'a [MarkerSeries],
+    pub x_axis: &'a AxisSettings,
+    pub y_axis: &'a AxisSettings,
+    /// Plot space area shown
+    pub bounds: MinMaxPair,
+    /// Ratio of the size of a unit on the x axis to a unit on the y axis (as used by the plot), the bounds are expanded to keep it
+    pub aspect: Option<f64>,
+    pub settings: &'a ImageSettings,
+}
+
+/// Space around the plotted area for the axes (left, top, right, bottom)
+const MARGINS: [f64; 4] = [80.0, 15.0, 20.0, 60.0];
+const FONT_SIZE: f64 = 14.0;
+const TEXT_COLOR: &str = "#222222";
+const GRID_COLOR: &str = "#DDDDDD";
+/// Used for series that let the plot pick their color
+const DEFAULT_MARKER_COLOR: Color32 = Color32::from_gray(60);
+
+impl Figure<'_> {
+    /// Returns bounds around all points with a margin (to not cut markers in half)
+    pub fn fit_bounds(series: &[MarkerSeries]) -> MinMaxPair {
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for point in series.iter().flat_map(|s| &s.points) {
+            for i in 0..2 {
+                min[i] = min[i].min(point[i]);
+                max[i] = max[i].max(point[i]);
+            }
+        }
+        for i in 0..2 {
+            if min[i] > max[i] {
+                // No points
+                (min[i], max[i]) = (-1.0, 1.0);
+            } else if min[i] == max[i] {
+                min[i] -= 1.0;
+                max[i] += 1.0;
+            }
+            let margin = (max[i] - min[i]) * 0.05;
+            min[i] -= margin;
+            max[i] += margin;
+        }
+        MinMaxPair { min, max }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let [width, height] = self.settings.size.map(f64::from);
+        let [left, top, right, bottom] = MARGINS;
+        let area = [
+            left,
+            top,
+            (width - right).max(left + 1.0),
+            (height - bottom).max(top + 1.0),
+        ];
+        let bounds = self.expanded_bounds(area);
+        let to_screen = |[x, y]: [f64; 2]| {
+            [
+                area[0]
+                    + (x - bounds.min[0]) / (bounds.max[0] - bounds.min[0]) * (area[2] - area[0]),
+                area[3]
+                    - (y - bounds.min[1]) / (bounds.max[1] - bounds.min[1]) * (area[3] - area[1]),
+            ]
+        };
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="{FONT_SIZE}">"#
+        );
+        svg.push_str(r#"<rect width="100%" height="100%" fill="white"/>"#);
+        svg.push('\n');
+        let _ = writeln!(
+            svg,
+            r#"<clipPath id="plot-area"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            area[0],
+            area[1],
+            area[2] - area[0],
+            area[3] - area[1]
+        );
+
+        // Grid lines and tick labels
+        for (axis, settings) in [self.x_axis, self.y_axis].into_iter().enumerate() {
+            let (min, max) = (bounds.min[axis], bounds.max[axis]);
+            let step = tick_step(min, max);
+            for tick in ticks(min, max, step) {
+                let label = escape(&if settings.is_log {
+                    log_tick_label(tick)
+                } else {
+                    format_tick(tick, step)
+                });
+                if axis == 0 {
+                    let [x, _] = to_screen([tick, bounds.min[1]]);
+                    let _ = writeln!(
+                        svg,
+                        r#"<line x1="{x:.2}" y1="{}" x2="{x:.2}" y2="{}" stroke="{GRID_COLOR}"/><text x="{x:.2}" y="{:.2}" text-anchor="middle" fill="{TEXT_COLOR}">{label}</text>"#,
+                        area[1],
+                        area[3],
+                        area[3] + FONT_SIZE + 4.0
+                    );
+                } else {
+                    let [_, y] = to_screen([bounds.min[0], tick]);
+                    let _ = writeln!(
+                        svg,
+                        r#"<line x1="{}" y1="{y:.2}" x2="{}" y2="{y:.2}" stroke="{GRID_COLOR}"/><text x="{:.2}" y="{:.2}" text-anchor="end" fill="{TEXT_COLOR}">{label}</text>"#,
+                        area[0],
+                        area[2],
+                        area[0] - 6.0,
+                        y + FONT_SIZE * 0.35
+                    );
+                }
+            }
+        }
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{TEXT_COLOR}"/>"#,
+            area[0],
+            area[1],
+            area[2] - area[0],
+            area[3] - area[1]
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" fill="{TEXT_COLOR}">{}</text>"#,
+            (area[0] + area[2]) / 2.0,
+            height - 12.0,
+            escape(&self.x_axis.label())
+        );
+        let y_label_position = [20.0, (area[1] + area[3]) / 2.0];
+        let _ = writeln!(
+            svg,
+            r#"<text x="{0:.2}" y="{1:.2}" transform="rotate(-90 {0:.2} {1:.2})" text-anchor="middle" fill="{TEXT_COLOR}">{2}</text>"#,
+            y_label_position[0],
+            y_label_position[1],
+            escape(&self.y_axis.label())
+        );
+
+        // Markers
+        svg.push_str(r#"<g clip-path="url(#plot-area)">"#);
+        svg.push('\n');
+        for series in self.series {
+            for &point in &series.points {
+                marker(&mut svg, series, to_screen(point));
+            }
+        }
+        svg.push_str("</g>\n");
+
+        if self.settings.should_show_legend {
+            self.legend(&mut svg, area);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Rasterizes [`Self::to_svg`] using the fonts built into egui (so the result does not depend on the installed fonts)
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let [width, height] = self.settings.size;
+        let mut fonts = resvg::usvg::fontdb::Database::new();
+        let mut definitions = egui::FontDefinitions::default();
+        // Proportional fonts first so they are used for sans-serif
+        let mut names = definitions
+            .families
+            .remove(&egui::FontFamily::Proportional)
+            .unwrap_or_default();
+        names.extend(definitions.font_data.keys().cloned());
+        for name in names {
+            if let Some(font) = definitions.font_data.remove(&name) {
+                fonts.load_font_data(font.font.into_owned());
+            }
+        }
+        let first_family = fonts
+            .faces()
+            .next()
+            .and_then(|face| face.families.first())
+            .map(|(family, _)| family.clone());
+        if let Some(family) = first_family {
+            fonts.set_sans_serif_family(family);
+        }
+        let options = resvg::usvg::Options {
+            fontdb: std::sync::Arc::new(fonts),
+            ..Default::default()
+        };
+        let tree = resvg::usvg::Tree::from_str(&self.to_svg(), &options)
+            .map_err(|e| format!("Failed to prepare image: {e}"))?;
+        let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+            .ok_or_else(|| format!("Invalid image size {width}×{height}"))?;
+        resvg::render(
+            &tree,
+            resvg::tiny_skia::Transform::default(),
+            &mut pixmap.as_mut(),
+        );
+        pixmap
+            .encode_png()
+            .map_err(|e| format!("Failed to encode PNG: {e}"))
+    }
+
+    /// Grows `self.bounds` to keep the aspect when drawn into `area` (left, top, right, bottom)
+    fn expanded_bounds(&self, area: [f64; 4]) -> MinMaxPair {
+        let mut bounds = self.bounds;
+        let Some(aspect) = self.aspect else {
+            return bounds;
+        };
+        let size = [area[2] - area[0], area[3] - area[1]];
+        let units_per_pixel = [
+            (bounds.max[0] - bounds.min[0]) / size[0],
+            (bounds.max[1] - bounds.min[1]) / size[1],
+        ];
+        // Expand the axis with fewer units per pixel
+        let (axis, new_units_per_pixel) = if units_per_pixel[1] > units_per_pixel[0] * aspect {
+            (0, units_per_pixel[1] / aspect)
+        } else {
+            (1, units_per_pixel[0] * aspect)
+        };
+        let center = (bounds.min[axis] + bounds.max[axis]) / 2.0;
+        let half_range = new_units_per_pixel * size[axis] / 2.0;
+        bounds.min[axis] = center - half_range;
+        bounds.max[axis] = center + half_range;
+        bounds
+    }
+
+    /// Lists the series in the top right corner of `area`
+    fn legend(&self, svg: &mut String, area: [f64; 4]) {
+        let entries: Vec<&MarkerSeries> =
+            self.series.iter().filter(|s| !s.name.is_empty()).collect();
+        if entries.is_empty() {
+            return;
+        }
+        let line_height = FONT_SIZE * 1.5;
+        let longest_name = entries
+            .iter()
+            .map(|s| s.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        // Approximate as the text is only measured when rendered
+        let width = longest_name as f64 * FONT_SIZE * 0.6 + 40.0;
+        let height = entries.len() as f64 * line_height + 10.0;
+        let [x, y] = [area[2] - width - 10.0, area[1] + 10.0];
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{height:.2}" fill="white" fill-opacity="0.85" stroke="{GRID_COLOR}"/>"#
+        );
+        for (i, series) in entries.into_iter().enumerate() {
+            let center_y = y + 5.0 + (i as f64 + 0.5) * line_height;
+            // Limit the size so large markers don't overlap
+            let radius = series.radius.min((line_height * 0.4) as f32);
+            let legend_series = MarkerSeries {
+                name: String::new(),
+                points: vec![],
+                radius,
+                shape: series.shape,
+                color: series.color,
+                is_filled: series.is_filled,
+            };
+            marker(svg, &legend_series, [x + 15.0, center_y]);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" fill="{TEXT_COLOR}">{}</text>"#,
+                x + 30.0,
+                center_y + FONT_SIZE * 0.35,
+                escape(&series.name)
+            );
+        }
+    }
+}
+
+/// Draws one marker of `series` centered on `center`, matching the shapes drawn by the plot
+fn marker(svg: &mut String, series: &MarkerSeries, center: [f64; 2]) {
+    use std::f64::consts::FRAC_1_SQRT_2;
+    let sqrt_3 = 3f64.sqrt();
+    let color = series.color.unwrap_or(DEFAULT_MARKER_COLOR);
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let opacity = f64::from(a) / 255.0;
+    let color = format!("rgb({r},{g},{b})");
+    let radius = f64::from(series.radius);
+    let stroke_width = radius / 5.0;
+    let (fill, stroke) = if series.is_filled {
+        (color.as_str(), "none")
+    } else {
+        ("none", color.as_str())
+    };
+    let point = |dx: f64, dy: f64| {
+        format!(
+            "{:.2},{:.2}",
+            center[0] + radius * dx,
+            center[1] + radius * dy
+        )
+    };
+    let polygon = |svg: &mut String, corners: &[(f64, f64)]| {
+        let corners: Vec<String> = corners.iter().map(|&(dx, dy)| point(dx, dy)).collect();
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{fill}" stroke="{stroke}" stroke-width="{stroke_width:.2}" opacity="{opacity:.3}"/>"#,
+            corners.join(" ")
+        );
+    };
+    let lines = |svg: &mut String, segments: &[[(f64, f64); 2]]| {
+        for [(x1, y1), (x2, y2)] in segments {
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{} {}" stroke="{color}" stroke-width="{stroke_width:.2}" opacity="{opacity:.3}"/>"#,
+                point(*x1, *y1),
+                point(*x2, *y2)
+            );
+        }
+    };
+    match series.shape {
+        MarkerShape::Circle => {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{radius:.2}" fill="{fill}" stroke="{stroke}" stroke-width="{stroke_width:.2}" opacity="{opacity:.3}"/>"#,
+                center[0], center[1]
+            );
+        }
+        MarkerShape::Diamond => polygon(svg, &[(0.0, 1.0), (-1.0, 0.0), (0.0, -1.0), (1.0, 0.0)]),
+        MarkerShape::Square => {
+            let d = FRAC_1_SQRT_2;
+            polygon(svg, &[(-d, d), (-d, -d), (d, -d), (d, d)])
+        }
+        MarkerShape::Cross => {
+            let d = FRAC_1_SQRT_2;
+            lines(svg, &[[(-d, -d), (d, d)], [(d, -d), (-d, d)]])
+        }
+        MarkerShape::Plus => lines(svg, &[[(-1.0, 0.0), (1.0, 0.0)], [(0.0, -1.0), (0.0, 1.0)]]),
+        MarkerShape::Up => polygon(
+            svg,
+            &[(0.0, -1.0), (0.5 * sqrt_3, 0.5), (-0.5 * sqrt_3, 0.5)],
+        ),
+        MarkerShape::Down => polygon(
+            svg,
+            &[(0.0, 1.0), (-0.5 * sqrt_3, -0.5), (0.5 * sqrt_3, -0.5)],
+        ),
+        MarkerShape::Left => polygon(
+            svg,
+            &[(-1.0, 0.0), (0.5, -0.5 * sqrt_3), (0.5, 0.5 * sqrt_3)],
+        ),
+        MarkerShape::Right => polygon(
+            svg,
+            &[(1.0, 0.0), (-0.5, 0.5 * sqrt_3), (-0.5, -0.5 * sqrt_3)],
+        ),
+        MarkerShape::Asterisk => {
+            let d = sqrt_3 / 2.0;
+            lines(
+                svg,
+                &[
+                    [(0.0, -1.0), (0.0, 1.0)],
+                    [(-d, 0.5), (d, -0.5)],
+                    [(-d, -0.5), (d, 0.5)],
+                ],
+            )
+        }
+    }
+}
+
+/// Distance between ticks giving about 6 ticks with steps of 1, 2 or 5 times a power of 10
+fn tick_step(min: f64, max: f64) -> f64 {
+    let rough = (max - min) / 6.0;
+    if !(rough.is_finite() && rough > 0.0) {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn ticks(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Shows as many decimal places as the step between ticks needs
+fn format_tick(value: f64, step: f64) -> String {
+    let decimal_places = (-step.log10().floor()).max(0.0) as usize;
+    let result = format!("{value:.decimal_places$}");
+    if result
+        .trim_start_matches('-')
+        .chars()
+        .all(|c| c == '0' || c == '.')
+    {
+        result.trim_start_matches('-').to_string() // Avoid showing -0
+    } else {
+        result
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn series() -> Vec<MarkerSeries> {
+        vec![
+            MarkerSeries {
+                name: "Normal (2)".to_string(),
+                points: vec![[0.0, 0.0], [10.0, 5.0]],
+                radius: 8.0,
+                shape: MarkerShape::Plus,
+                color: Some(Color32::from_rgb(100, 150, 230)),
+                is_filled: true,
+            },
+            MarkerSeries {
+                name: "Train <1> & more".to_string(),
+                points: vec![[2.0, 3.0]],
+                radius: 10.4,
+                shape: MarkerShape::Square,
+                color: None,
+                is_filled: false,
+            },
+        ]
+    }
+
+    fn figure<'a>(
+        series: &'a [MarkerSeries],
+        axis: &'a AxisSettings,
+        settings: &'a ImageSettings,
+    ) -> Figure<'a> {
+        Figure {
+            series,
+            x_axis: axis,
+            y_axis: axis,
+            bounds: Figure::fit_bounds(series),
+            aspect: None,
+            settings,
+        }
+    }
+
+    #[test]
+    fn svg_contains_markers_and_legend() {
+        let series = series();
+        let axis = AxisSettings::default();
+        let settings = ImageSettings::default();
+        let svg = figure(&series, &axis, &settings).to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="1200" height="900""#));
+        // Plus markers are 2 lines each, one in the legend
+        assert_eq!(svg.matches("<polyline").count(), 2 * 3);
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert!(svg.contains("Train &lt;1&gt; &amp; more"));
+
+        let settings = ImageSettings {
+            should_show_legend: false,
+            ..Default::default()
+        };
+        let svg = figure(&series, &axis, &settings).to_svg();
+        assert!(!svg.contains("Normal (2)"));
+    }
+
+    #[test]
+    fn png_has_requested_size() {
+        let series = series();
+        let axis = AxisSettings::default();
+        let settings = ImageSettings {
+            size: [320, 200],
+            ..Default::default()
+        };
+        let png = figure(&series, &axis, &settings).to_png().expect("renders");
+        let image = resvg::tiny_skia::Pixmap::decode_png(&png).expect("valid PNG");
+        assert_eq!((image.width(), image.height()), (320, 200));
+        assert!(
+            image.pixels().iter().any(|p| p.red() != 255),
+            "something was drawn"
+        );
+    }
+
+    #[test]
+    fn fit_bounds_include_all_points() {
+        let bounds = Figure::fit_bounds(&series());
+        assert!(bounds.min[0] < 0.0 && bounds.max[0] > 10.0);
+        assert!(bounds.min[1] < 0.0 && bounds.max[1] > 5.0);
+        let empty = Figure::fit_bounds(&[]);
+        assert!(empty.min[0] < empty.max[0]);
+    }
+
+    #[test]
+    fn aspect_kept_by_expanding() {
+        let series = series();
+        let axis = AxisSettings::default();
+        let settings = ImageSettings::default();
+        let figure = Figure {
+            aspect: Some(1.0),
+            bounds: MinMaxPair {
+                min: [0.0, 0.0],
+                max: [1.0, 1.0],
+            },
+            ..figure(&series, &axis, &settings)
+        };
+        let area = [0.0, 0.0, 200.0, 100.0];
+        let bounds = figure.expanded_bounds(area);
+        assert_eq!(bounds.min, [-0.5, 0.0]);
+        assert_eq!(bounds.max, [1.5, 1.0]);
+    }
+
+    #[rstest]
+    #[case(0.0, 10.0, 2.0)]
+    #[case(0.0, 1.0, 0.2)]
+    #[case(-300.0, 300.0, 100.0)]
+    fn tick_steps(#[case] min: f64, #[case] max: f64, #[case] expected: f64) {
+        assert!((tick_step(min, max) - expected).abs() < 1e-12);
+    }
+
+    #[rstest]
+    #[case(0.30000000000000004, 0.1, "0.3")]
+    #[case(-0.0, 0.5, "0.0")]
+    #[case(1500.0, 500.0, "1500")]
+    fn tick_labels(#[case] value: f64, #[case] step: f64, #[case] expected: &str) {
+        assert_eq!(format_tick(value, step), expected);
+    }
+}
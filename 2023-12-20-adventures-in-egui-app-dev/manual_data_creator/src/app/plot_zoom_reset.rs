@@ -2,43 +2,33 @@ use egui_plot::PlotBounds;
 use log::warn;
 use std::fmt::Debug;
 
+use crate::dataset::{MinMaxPair, ZoomTarget};
+
 #[derive(Debug, PartialEq, Default)]
 pub enum StatePlotResetZoom {
-    /// Signals that we should set the new size in this step
-    Set,
+    /// Signals that we should calculate the bounds to move to in this step
+    Set {
+        target: ZoomTarget,
+        is_animated: bool,
+    },
+    /// Moving the plot bounds towards the new bounds over several steps
+    Animate(Animation),
     /// Signals that we should wait for the changes to take effect before doing a verification
-    Wait(MinMaxPair),
+    Wait(ZoomTarget, MinMaxPair),
     /// Verify that the target was achieved
-    Verify(MinMaxPair),
+    Verify(ZoomTarget, MinMaxPair),
     #[default]
     NotRunning,
     Error(String),
 }
 
-#[derive(PartialEq, Copy, Clone)]
-pub struct MinMaxPair {
-    pub min: [f64; 2],
-    pub max: [f64; 2],
-}
-
-impl MinMaxPair {
-    /// Returns true if `self` can fit in `outer_bounds`
-    fn is_contained(&self, outer_bounds: &MinMaxPair) -> bool {
-        fn is_less_equal(a: f64, b: f64) -> bool {
-            // Return true if a <= b
-            a - b <= f64::EPSILON
-        }
-        is_less_equal(outer_bounds.min[0], self.min[0])
-            && is_less_equal(outer_bounds.min[1], self.min[1])
-            && is_less_equal(self.max[0], outer_bounds.max[0])
-            && is_less_equal(self.max[1], outer_bounds.max[1])
-    }
-}
-
-impl Debug for MinMaxPair {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "min: {:.02?}, max: {:.02?}", self.min, self.max)
-    }
+#[derive(Debug, PartialEq)]
+pub struct Animation {
+    target: ZoomTarget,
+    target_bounds: MinMaxPair,
+    start: MinMaxPair,
+    end: MinMaxPair,
+    frame: u16,
 }
 
 impl From<MinMaxPair> for PlotBounds {
@@ -57,7 +47,18 @@ impl From<PlotBounds> for MinMaxPair {
 }
 
 impl StatePlotResetZoom {
-    fn calculate_new_size(plot_bounds: MinMaxPair, target_bounds: MinMaxPair) -> MinMaxPair {
+    /// Calculates the bounds to set on the plot so that `target_bounds` is visible
+    ///
+    /// With a free aspect each axis can be set independently so the target is used as is.
+    /// With a locked aspect the plot keeps its ratio so only one axis can usefully be widened.
+    fn calculate_new_size(
+        plot_bounds: MinMaxPair,
+        target_bounds: MinMaxPair,
+        is_aspect_locked: bool,
+    ) -> MinMaxPair {
+        if !is_aspect_locked {
+            return target_bounds;
+        }
         let [plot_min_x, plot_min_y] = plot_bounds.min;
         let [plot_max_x, plot_max_y] = plot_bounds.max;
         let [target_min_x, target_min_y] = target_bounds.min;
@@ -72,7 +73,7 @@ impl StatePlotResetZoom {
         if target_ratio >= plot_ratio {
             target_bounds
         } else {
-            // We can only usefully change the x values because the plot is set to a data_aspect of 1 (meaning one unit of x = one unit of y).
+            // We can only usefully change the x values because the plot has a locked data_aspect (meaning one unit of x = a fixed number of units of y).
             // We need to adjust the x values in the target such that the ratio matches the plot ratio_ratio because changing x only also leads to change to y
             // but changes to y only don't lead to changes in x
             // So since we know the target_ratio is < plot_ratio we just need to see what value of x will make them equal
@@ -88,19 +89,42 @@ impl StatePlotResetZoom {
             }
         }
     }
-    pub fn start_reset(&mut self) {
+    /// Number of steps (frames) used to move to the new bounds when animated
+    const ANIMATION_FRAMES: u16 = 20;
+
+    pub fn start_reset(&mut self, target: ZoomTarget, is_animated: bool) {
         debug_assert!(
             self.is_stopped(),
             "Only time start should be called is if we are not running"
         );
-        *self = Self::Set;
+        *self = Self::Set {
+            target,
+            is_animated,
+        };
     }
 
-    pub fn step(&mut self, plot_ui: &mut egui_plot::PlotUi, target: MinMaxPair) {
+    /// The target being zoomed to if running
+    pub fn target(&self) -> Option<ZoomTarget> {
         match self {
-            StatePlotResetZoom::Set => self.set(plot_ui, target),
-            StatePlotResetZoom::Wait(x) => *self = Self::Verify(*x), // Skip this step and verify next time
-            StatePlotResetZoom::Verify(_) => self.verify(plot_ui, target),
+            StatePlotResetZoom::Set { target, .. }
+            | StatePlotResetZoom::Animate(Animation { target, .. })
+            | StatePlotResetZoom::Wait(target, _)
+            | StatePlotResetZoom::Verify(target, _) => Some(*target),
+            StatePlotResetZoom::NotRunning | StatePlotResetZoom::Error(_) => None,
+        }
+    }
+
+    pub fn step(
+        &mut self,
+        plot_ui: &mut egui_plot::PlotUi,
+        target: MinMaxPair,
+        is_aspect_locked: bool,
+    ) {
+        match self {
+            StatePlotResetZoom::Set { .. } => self.set(plot_ui, target, is_aspect_locked),
+            StatePlotResetZoom::Animate(_) => self.animate(plot_ui, target),
+            StatePlotResetZoom::Wait(zoom_target, x) => *self = Self::Verify(*zoom_target, *x), // Skip this step and verify next time
+            StatePlotResetZoom::Verify(..) => self.verify(plot_ui, target),
             StatePlotResetZoom::NotRunning | StatePlotResetZoom::Error(_) => debug_assert!(
                 false,
                 "We shouldn't be taking steps when we are not running"
@@ -113,18 +137,67 @@ impl StatePlotResetZoom {
         matches!(self, Self::Error(..) | Self::NotRunning)
     }
 
-    fn set(&mut self, plot_ui: &mut egui_plot::PlotUi, target_bounds: MinMaxPair) {
+    fn set(
+        &mut self,
+        plot_ui: &mut egui_plot::PlotUi,
+        target_bounds: MinMaxPair,
+        is_aspect_locked: bool,
+    ) {
+        let Self::Set {
+            target,
+            is_animated,
+        } = *self
+        else {
+            unreachable!("set should not be called outside of Set State. Current state: {self:?}");
+        };
         let plot_bounds: MinMaxPair = plot_ui.plot_bounds().into();
-        let new_bounds = Self::calculate_new_size(plot_bounds, target_bounds);
-        plot_ui.set_plot_bounds(new_bounds.into());
-        *self = Self::Wait(target_bounds);
+        let new_bounds = Self::calculate_new_size(plot_bounds, target_bounds, is_aspect_locked);
+        if is_animated {
+            *self = Self::Animate(Animation {
+                target,
+                target_bounds,
+                start: plot_bounds,
+                end: new_bounds,
+                frame: 0,
+            });
+            self.animate(plot_ui, target_bounds);
+        } else {
+            plot_ui.set_plot_bounds(new_bounds.into());
+            *self = Self::Wait(target, target_bounds);
+        }
+    }
+
+    fn animate(&mut self, plot_ui: &mut egui_plot::PlotUi, new_target: MinMaxPair) {
+        if let Self::Animate(animation) = self {
+            if animation.target_bounds != new_target {
+                warn!("Target bounds changed during animation. Restarting from the current bounds. Previous target {:?}. Current Target {new_target:?}", animation.target_bounds);
+                *self = Self::Set {
+                    target: animation.target,
+                    is_animated: true,
+                };
+                return;
+            }
+            animation.frame += 1;
+            let t = animation.frame as f64 / Self::ANIMATION_FRAMES as f64;
+            plot_ui.set_plot_bounds(animation.start.interpolate(&animation.end, ease(t)).into());
+            if animation.frame >= Self::ANIMATION_FRAMES {
+                *self = Self::Wait(animation.target, animation.target_bounds);
+            }
+        } else {
+            unreachable!(
+                "animate should not be called outside of Animate State. Current state: {self:?}"
+            );
+        }
     }
 
     fn verify(&mut self, plot_ui: &egui_plot::PlotUi, new_target: MinMaxPair) {
-        if let Self::Verify(expected_target) = self {
+        if let Self::Verify(zoom_target, expected_target) = self {
             if expected_target != &new_target {
                 warn!("Target target bounds changed during reset. Only reason I can think of this would happen is the data changed but that shouldn't happen during reset, as reset should be short. Recovering by restarting reset. Previous target {expected_target:?}. Current Target {new_target:?}");
-                *self = Self::Set;
+                *self = Self::Set {
+                    target: *zoom_target,
+                    is_animated: false,
+                };
                 return;
             }
             let plot_bounds: MinMaxPair = plot_ui.plot_bounds().into();
@@ -141,6 +214,12 @@ impl StatePlotResetZoom {
     }
 }
 
+/// Smooth step easing so the animation starts and ends slowly
+fn ease(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -192,6 +271,9 @@ mod tests {
 
     #[rstest]
     #[case([[-2.0,-1.0],[2.0,1.0]],[[-1.0,-1.0],[1.0,1.0]])]
+    #[case([[-1.0,-2.0],[1.0,2.0]],[[-1.0,-1.0],[1.0,1.0]])]
+    #[case([[0.0,0.0],[10.0,1.0]],[[-5.0,-5.0],[5.0,5.0]])]
+    #[case([[0.0,0.0],[1.0,10.0]],[[2.0,-5.0],[3.0,50.0]])]
     fn converted_bounds_at_least_big_enough(
         #[case] plot_bounds: [[f64; 2]; 2],
         #[case] target_bounds: [[f64; 2]; 2],
@@ -208,7 +290,7 @@ mod tests {
             max: plot_bounds[1],
         };
 
-        let new_bounds = StatePlotResetZoom::calculate_new_size(plot_bounds, target_bounds);
+        let new_bounds = StatePlotResetZoom::calculate_new_size(plot_bounds, target_bounds, true);
         assert!(
             target_bounds.is_contained(&new_bounds),
             "Target doesn't seem to fit in the new bounds.
@@ -217,6 +299,49 @@ target    : {target_bounds:?}"
         );
         let plot_ratio = ratio(&plot_bounds);
         let new_ratio = ratio(&new_bounds);
-        assert!(plot_ratio <= new_ratio);
+        // The new width is calculated from the plot ratio, so it can only be smaller by rounding
+        let rounding = plot_ratio * 1e-12;
+        assert!(
+            plot_ratio <= new_ratio + rounding,
+            "New bounds are narrower than the plot. plot ratio: {plot_ratio} new ratio: {new_ratio}"
+        );
+    }
+
+    #[rstest]
+    #[case([[-2.0,-1.0],[2.0,1.0]],[[-1.0,-1.0],[1.0,1.0]])]
+    #[case([[0.0,0.0],[10.0,1.0]],[[-5.0,-50.0],[5.0,5.0]])]
+    fn free_aspect_uses_target_exactly(
+        #[case] plot_bounds: [[f64; 2]; 2],
+        #[case] target_bounds: [[f64; 2]; 2],
+    ) {
+        let target_bounds = MinMaxPair {
+            min: target_bounds[0],
+            max: target_bounds[1],
+        };
+        let plot_bounds = MinMaxPair {
+            min: plot_bounds[0],
+            max: plot_bounds[1],
+        };
+
+        let new_bounds = StatePlotResetZoom::calculate_new_size(plot_bounds, target_bounds, false);
+        assert_eq!(new_bounds, target_bounds);
+    }
+
+    #[rstest]
+    #[case(0.0, [[0.0, 0.0], [1.0, 1.0]])]
+    #[case(0.5, [[-1.0, 0.5], [2.0, 3.0]])]
+    #[case(1.0, [[-2.0, 1.0], [3.0, 5.0]])]
+    fn interpolate_bounds(#[case] t: f64, #[case] expected: [[f64; 2]; 2]) {
+        let start = MinMaxPair {
+            min: [0.0, 0.0],
+            max: [1.0, 1.0],
+        };
+        let end = MinMaxPair {
+            min: [-2.0, 1.0],
+            max: [3.0, 5.0],
+        };
+        let result = start.interpolate(&end, ease(t));
+        assert_eq!(result.min, expected[0]);
+        assert_eq!(result.max, expected[1]);
     }
 }
@@ -0,0 +1,639 @@
+//! Optional HTTP server on localhost that lets other programs (a notebook, a test runner) read and change the points
+//!
+//! Requests are received on a background thread and handed to the app, so remote changes go through
+//! [`Data`](crate::dataset::Data) like any other change and can be undone by the user.
+//!
+//! | Request         | Effect                                                                        |
+//! |-----------------|-------------------------------------------------------------------------------|
+//! | `GET /data`     | Returns the points in the same JSON layout as exported JSON files             |
+//! | `POST /points`  | Appends the points in the body (same JSON layout) after the existing points   |
+//! | `PUT /data`     | Replaces all points with the points in the body (same JSON layout)            |
+//! | `GET /events`   | Server-sent events, a `change` event now and each time the points change      |
+//!
+//! Bodies must be sent as `application/json`. Browsers do not allow other sites to send that without asking first (which
+//! is never allowed), so web pages opened on the same computer are not able to change the points. Requests also need a
+//! `Host` of `127.0.0.1:<port>` or `localhost:<port>`, which stops pages that point their own host name at this computer
+//! (DNS rebinding) from reading or replacing the points.
+
+use std::{
+    io::{Read, Write},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use super::ManualDataCreatorApp;
+use crate::dataset::{
+    data_io::{self, FileFormat},
+    Data, DataPoint, StatusMsg,
+};
+
+/// Persisted choices for the remote control server
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct RemoteControlSettings {
+    pub is_enabled: bool,
+    pub port: u16,
+}
+
+impl Default for RemoteControlSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            port: 7878,
+        }
+    }
+}
+
+/// Used as the source in the status bar and the undo history
+const SOURCE: &str = "remote control";
+
+/// How long a request waits for the app to handle it (the app only handles requests while it is running its update)
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest request body accepted, larger bodies get a 413 reply without being read into memory
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Most `GET /events` clients listening at the same time, each one has its own thread
+const MAX_SUBSCRIBERS: usize = 16;
+
+/// A request that has been checked and needs the app to handle it
+#[derive(Debug, PartialEq)]
+enum Command {
+    GetData,
+    Append(Vec<DataPoint>),
+    Replace {
+        points: Vec<DataPoint>,
+        dimensions: usize,
+    },
+}
+
+/// What the server thread asks of the app
+enum Message {
+    Command(Command, mpsc::Sender<Reply>),
+    /// Each change event is sent to the subscriber until it stops listening
+    Subscribe(mpsc::Sender<String>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Route {
+    Command(Command),
+    Events,
+}
+
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn text(status: u16, msg: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: msg.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// The running server, stopped when dropped
+pub struct RemoteServer {
+    address: SocketAddr,
+    server: Arc<tiny_http::Server>,
+    messages: mpsc::Receiver<Message>,
+    subscribers: Vec<mpsc::Sender<String>>,
+    /// Value of [`crate::dataset::UndoManager::change_count`] when subscribers were last told about a change
+    last_change_count: u64,
+}
+
+impl PartialEq for RemoteServer {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+impl RemoteServer {
+    /// Starts listening on `port` of localhost, `ctx` is used to wake up the app when a request arrives
+    fn start(port: u16, ctx: &egui::Context, data: &Data) -> Result<Self, String> {
+        let server = tiny_http::Server::http(("127.0.0.1", port))
+            .map_err(|e| format!("Unable to start remote control on port {port}: {e}"))?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or("Remote control is not listening on an IP address")?;
+        let server = Arc::new(server);
+        let (sender, messages) = mpsc::channel();
+        let thread_server = Arc::clone(&server);
+        let ctx = ctx.clone();
+        let port = address.port();
+        let subscriber_count = Arc::new(AtomicUsize::new(0));
+        thread::Builder::new()
+            .name("remote control".to_string())
+            .spawn(move || {
+                for request in thread_server.incoming_requests() {
+                    handle_request(request, port, &subscriber_count, &sender, &ctx);
+                }
+            })
+            .map_err(|e| format!("Unable to start remote control thread: {e}"))?;
+        Ok(Self {
+            address,
+            server,
+            messages,
+            subscribers: vec![],
+            last_change_count: data.undo_manager().change_count(),
+        })
+    }
+
+    /// Tells all subscribers about the current points if they changed since the last time
+    fn notify_changes(&mut self, data: &Data) {
+        let change_count = data.undo_manager().change_count();
+        if change_count != self.last_change_count {
+            self.last_change_count = change_count;
+            let event = change_event(data);
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+impl ManualDataCreatorApp {
+    pub(super) fn remote_control_options(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.remote_control.is_enabled, "Enable remote control")
+                .on_hover_text("Lets programs on this computer read and change the points over HTTP, changes can be undone");
+            ui.add_enabled(
+                self.remote_server.is_none(),
+                egui::DragValue::new(&mut self.remote_control.port)
+                    .clamp_range(1..=u16::MAX)
+                    .prefix("Port: "),
+            );
+        });
+        if let Some(server) = &self.remote_server {
+            ui.label(format!("Listening on http://{}", server.address));
+            ui.label("GET /data, POST /points, PUT /data (JSON as in exported files), GET /events (server-sent events)");
+        }
+    }
+
+    /// Starts or stops the server to match the settings and handles the requests received since the last frame
+    pub(super) fn update_remote_control(&mut self, ctx: &egui::Context) {
+        if self.remote_control.is_enabled != self.remote_server.is_some() {
+            self.remote_server = None;
+            if self.remote_control.is_enabled {
+                match RemoteServer::start(self.remote_control.port, ctx, &self.data) {
+                    Ok(server) => {
+                        self.status_msg.add_msg(&format!(
+                            "Remote control listening on http://{}",
+                            server.address
+                        ));
+                        self.remote_server = Some(server);
+                    }
+                    Err(e) => {
+                        self.status_msg.add_err(&e);
+                        self.remote_control.is_enabled = false;
+                    }
+                }
+            }
+        }
+
+        let Some(server) = &self.remote_server else {
+            return;
+        };
+        let messages: Vec<Message> = server.messages.try_iter().collect();
+        for message in messages {
+            match message {
+                Message::Command(command, reply) => {
+                    let _ = reply.send(self.run_remote_command(command)); // The client may have given up waiting
+                }
+                Message::Subscribe(subscriber) => {
+                    if subscriber.send(change_event(&self.data)).is_ok() {
+                        if let Some(server) = &mut self.remote_server {
+                            server.subscribers.push(subscriber);
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(server) = &mut self.remote_server {
+            server.notify_changes(&self.data);
+        }
+    }
+
+    fn run_remote_command(&mut self, command: Command) -> Reply {
+        let change_count = self.data.undo_manager().change_count();
+        let mut status_msg = StatusMsg::default();
+        match command {
+            Command::GetData => {
+                return match FileFormat::Json.write(self.data.points(), self.data.dimensions()) {
+                    Ok(body) => Reply::json(body),
+                    Err(e) => Reply::text(500, &e),
+                };
+            }
+            Command::Append(points) => self.data.append_points(points, SOURCE, &mut status_msg),
+            Command::Replace { points, dimensions } => {
+                self.data
+                    .import_points(points, dimensions, SOURCE, &mut status_msg);
+                self.stop_watching_if_changed(change_count);
+            }
+        }
+        self.status_msg.add_msg(status_msg.get_msg());
+        let is_changed = self.data.undo_manager().change_count() != change_count;
+        Reply::text(if is_changed { 200 } else { 422 }, status_msg.get_msg())
+    }
+}
+
+/// Runs on the server thread, passes the request on to the app and sends back its reply
+///
+/// `port` is the port the server is listening on and `subscriber_count` the number of `GET /events` clients
+fn handle_request(
+    mut request: tiny_http::Request,
+    port: u16,
+    subscriber_count: &Arc<AtomicUsize>,
+    sender: &mpsc::Sender<Message>,
+    ctx: &egui::Context,
+) {
+    let header_value = |name: &'static str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.to_string())
+    };
+    let content_type = header_value("Content-Type");
+    let host = header_value("Host");
+    let route = if is_local_host(host.as_deref(), port) {
+        let body = match request.body_length() {
+            Some(length) if length > MAX_BODY_SIZE => Err(body_too_large()),
+            _ => read_body(request.as_reader()),
+        };
+        body.and_then(|body| {
+            parse_request(
+                request.method().as_str(),
+                request.url(),
+                content_type.as_deref(),
+                &body,
+            )
+        })
+    } else {
+        Err(Reply::text(
+            403,
+            &format!("Host must be 127.0.0.1:{port} or localhost:{port}"),
+        ))
+    };
+    let reply = match route {
+        Ok(Route::Events) => match SubscriberSlot::take(subscriber_count) {
+            None => Reply::text(
+                503,
+                &format!("Already sending events to {MAX_SUBSCRIBERS} clients"),
+            ),
+            Some(slot) => {
+                let (subscriber, events) = mpsc::channel();
+                if sender.send(Message::Subscribe(subscriber)).is_ok() {
+                    ctx.request_repaint();
+                    // Events are written as they happen, so this response has its own thread
+                    let writer = request.into_writer();
+                    let _ = thread::Builder::new()
+                        .name("remote control events".to_string())
+                        .spawn(move || {
+                            stream_events(writer, &events);
+                            drop(slot);
+                        });
+                    return;
+                }
+                Reply::text(503, "Remote control has been stopped")
+            }
+        },
+        Ok(Route::Command(command)) => {
+            let (reply_sender, reply) = mpsc::channel();
+            if sender.send(Message::Command(command, reply_sender)).is_ok() {
+                ctx.request_repaint();
+            }
+            reply
+                .recv_timeout(REPLY_TIMEOUT)
+                .unwrap_or_else(|_| Reply::text(503, "The app did not handle the request"))
+        }
+        Err(reply) => reply,
+    };
+    let header = tiny_http::Header::from_bytes("Content-Type", reply.content_type)
+        .expect("content type should be a valid header");
+    let response = tiny_http::Response::from_data(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        log::warn!("Failed to respond to remote control request: {e}");
+    }
+}
+
+fn body_too_large() -> Reply {
+    Reply::text(
+        413,
+        &format!("Body must not be larger than {MAX_BODY_SIZE} bytes"),
+    )
+}
+
+/// Reads the whole body unless it is larger than [`MAX_BODY_SIZE`]
+fn read_body(reader: impl Read) -> Result<Vec<u8>, Reply> {
+    let mut body = vec![];
+    reader
+        .take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| Reply::text(400, &format!("Unable to read request: {e}")))?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(body_too_large());
+    }
+    Ok(body)
+}
+
+/// One of the [`MAX_SUBSCRIBERS`] places for event clients, given back when dropped
+struct SubscriberSlot(Arc<AtomicUsize>);
+
+impl SubscriberSlot {
+    /// Returns `None` if all places are taken
+    fn take(count: &Arc<AtomicUsize>) -> Option<Self> {
+        count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < MAX_SUBSCRIBERS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(Arc::clone(count)))
+    }
+}
+
+impl Drop for SubscriberSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns `true` if `host` (the `Host` header) names this server on localhost
+fn is_local_host(host: Option<&str>, port: u16) -> bool {
+    host.is_some_and(|host| {
+        let host = host.trim().to_ascii_lowercase();
+        host == format!("127.0.0.1:{port}") || host == format!("localhost:{port}")
+    })
+}
+
+/// Writes each event to the client until either side stops
+fn stream_events(mut writer: Box<dyn Write + Send>, events: &mpsc::Receiver<String>) {
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if writer.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+    for event in events {
+        if writer
+            .write_all(event.as_bytes())
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            return; // Client disconnected
+        }
+    }
+}
+
+/// Works out what is being asked for, returns the reply to send instead if the request is not valid
+fn parse_request(
+    method: &str,
+    url: &str,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<Route, Reply> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let read_body = || {
+        if !content_type.is_some_and(|content_type| {
+            content_type
+                .trim()
+                .to_ascii_lowercase()
+                .starts_with("application/json")
+        }) {
+            return Err(Reply::text(415, "Body must be sent as application/json"));
+        }
+        data_io::read_file("request.json", body)
+            .map_err(|errors| Reply::text(400, &errors.join("\n")))
+    };
+    match (path, method) {
+        ("/data", "GET") => Ok(Route::Command(Command::GetData)),
+        ("/data", "PUT") => {
+            let (points, dimensions) = read_body()?;
+            Ok(Route::Command(Command::Replace { points, dimensions }))
+        }
+        ("/points", "POST") => {
+            let (points, _) = read_body()?;
+            Ok(Route::Command(Command::Append(points)))
+        }
+        ("/events", "GET") => Ok(Route::Events),
+        ("/data" | "/points" | "/events", _) => Err(Reply::text(
+            405,
+            &format!("{method} is not supported for {path}"),
+        )),
+        _ => Err(Reply::text(404, &format!("Unknown path {path}"))),
+    }
+}
+
+/// Server-sent event describing the current points
+fn change_event(data: &Data) -> String {
+    let summary = serde_json::json!({
+        "change_count": data.undo_manager().change_count(),
+        "dimensions": data.dimensions(),
+        "points": data.points().len(),
+    });
+    format!("event: change\ndata: {summary}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead as _, BufReader},
+        net::TcpStream,
+    };
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dataset::DataLabel;
+
+    const BODY: &str = r#"{"dimensions": 2, "points": [{"x": 1.0, "y": 2.0, "label": "Anomaly"}]}"#;
+
+    #[rstest]
+    #[case("GET", "/data", None, "", Ok(Route::Command(Command::GetData)))]
+    #[case(
+        "GET",
+        "/data?format=json",
+        None,
+        "",
+        Ok(Route::Command(Command::GetData))
+    )]
+    #[case("GET", "/events", None, "", Ok(Route::Events))]
+    #[case("DELETE", "/data", None, "", Err(405))]
+    #[case("GET", "/nothing", None, "", Err(404))]
+    #[case("POST", "/points", None, BODY, Err(415))]
+    #[case("POST", "/points", Some("text/plain"), BODY, Err(415))]
+    #[case("POST", "/points", Some("application/json"), "[1, 2]", Err(400))]
+    fn requests_checked(
+        #[case] method: &str,
+        #[case] url: &str,
+        #[case] content_type: Option<&str>,
+        #[case] body: &str,
+        #[case] expected: Result<Route, u16>,
+    ) {
+        let actual =
+            parse_request(method, url, content_type, body.as_bytes()).map_err(|reply| reply.status);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn large_bodies_rejected() {
+        assert_eq!(read_body(&b"{}"[..]).map(|body| body.len()), Ok(2));
+        let too_large = std::io::repeat(b' ').take(MAX_BODY_SIZE as u64 + 1);
+        assert_eq!(read_body(too_large).map_err(|reply| reply.status), Err(413));
+    }
+
+    #[test]
+    fn subscribers_limited() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut slots: Vec<SubscriberSlot> = (0..MAX_SUBSCRIBERS)
+            .map(|_| SubscriberSlot::take(&count).expect("place left"))
+            .collect();
+        assert!(SubscriberSlot::take(&count).is_none());
+        slots.pop();
+        assert!(SubscriberSlot::take(&count).is_some());
+    }
+
+    #[rstest]
+    #[case(Some("localhost:7878"), true)]
+    #[case(Some("127.0.0.1:7878"), true)]
+    #[case(Some(" LocalHost:7878 "), true)]
+    #[case(Some("localhost"), false)]
+    #[case(Some("localhost:8080"), false)]
+    #[case(Some("attacker.example:7878"), false)]
+    #[case(None, false)]
+    fn host_must_be_localhost(#[case] host: Option<&str>, #[case] expected: bool) {
+        assert_eq!(is_local_host(host, 7878), expected);
+    }
+
+    #[test]
+    fn body_read_as_json_dataset() {
+        let expected = DataPoint {
+            meta: Default::default(),
+            ..DataPoint::new(vec![1.0, 2.0], DataLabel::Anomaly)
+        };
+        let content_type = Some("Application/JSON; charset=utf-8");
+        assert_eq!(
+            parse_request("PUT", "/data", content_type, BODY.as_bytes()),
+            Ok(Route::Command(Command::Replace {
+                points: vec![expected.clone()],
+                dimensions: 2
+            }))
+        );
+        assert_eq!(
+            parse_request("POST", "/points", content_type, BODY.as_bytes()),
+            Ok(Route::Command(Command::Append(vec![expected])))
+        );
+    }
+
+    /// Sends `request` and keeps the app handling requests until the response arrives
+    fn send(app: &mut ManualDataCreatorApp, ctx: &egui::Context, request: String) -> String {
+        let address = app.remote_server.as_ref().expect("started").address;
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).expect("connects");
+            stream.write_all(request.as_bytes()).expect("sends");
+            let mut response = String::new();
+            stream.read_to_string(&mut response).expect("receives");
+            response
+        });
+        while !client.is_finished() {
+            app.update_remote_control(ctx);
+            thread::sleep(Duration::from_millis(5));
+        }
+        client.join().expect("client does not panic")
+    }
+
+    #[test]
+    fn remote_changes_can_be_undone() {
+        let ctx = egui::Context::default();
+        let mut app = ManualDataCreatorApp {
+            remote_control: RemoteControlSettings {
+                is_enabled: true,
+                port: 0, // Any free port
+            },
+            ..Default::default()
+        };
+        app.update_remote_control(&ctx);
+        let address = app.remote_server.as_ref().expect("started").address;
+        let port = address.port();
+
+        let events = TcpStream::connect(address).expect("connects");
+        let mut events = BufReader::new(events);
+        events
+            .get_mut()
+            .write_all(format!("GET /events HTTP/1.1\r\nHost: localhost:{port}\r\n\r\n").as_bytes())
+            .expect("subscribes");
+        while app.remote_server.as_ref().unwrap().subscribers.is_empty() {
+            app.update_remote_control(&ctx);
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let response = send(
+            &mut app,
+            &ctx,
+            format!(
+                "POST /points HTTP/1.1\r\nHost: localhost:{port}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BODY}",
+                BODY.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert_eq!(app.data.points().len(), 1);
+        app.update_remote_control(&ctx); // Sends the change event
+
+        let response = send(
+            &mut app,
+            &ctx,
+            format!(
+                "PUT /data HTTP/1.1\r\nHost: rebound.example:{port}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BODY}",
+                BODY.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+
+        let response = send(
+            &mut app,
+            &ctx,
+            format!("GET /data HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nConnection: close\r\n\r\n"),
+        );
+        assert!(response.contains(r#""label": "Anomaly""#), "{response}");
+
+        app.data.undo(&mut app.status_msg);
+        assert!(app.data.is_empty());
+        app.update_remote_control(&ctx);
+
+        let data_lines: Vec<String> = events
+            .lines()
+            .map(|line| line.expect("reads event"))
+            .filter(|line| line.starts_with("data: "))
+            .take(3)
+            .collect();
+        let point_counts: Vec<_> = data_lines
+            .iter()
+            .map(|line| line.contains(r#""points":1"#))
+            .collect();
+        assert_eq!(point_counts, [false, true, false], "{data_lines:?}");
+    }
+}
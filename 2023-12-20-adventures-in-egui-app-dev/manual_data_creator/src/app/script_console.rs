@@ -0,0 +1,496 @@
+//! Editing the points with Rhai scripts, each run is applied as a single undoable change
+
+use std::{cell::RefCell, rc::Rc};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FLOAT, INT};
+
+use super::ManualDataCreatorApp;
+use crate::dataset::{DataLabel, DataPoint, SeededRng};
+
+/// Source and options of the script console, saved with the app
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct ScriptSettings {
+    pub source: String,
+    /// Seed of the random numbers returned by `rand` so runs can be repeated
+    pub seed: u64,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        Self {
+            source: EXAMPLE.to_string(),
+            seed: 0,
+        }
+    }
+}
+
+const EXAMPLE: &str = r#"// Move every anomaly with x > 5 left by 2
+for i in 0..count() {
+    if is_anomaly(i) && x(i) > 5.0 {
+        set_x(i, x(i) - 2.0);
+    }
+}
+"#;
+
+/// Functions available to scripts, shown as help in the console
+const FUNCTIONS: [(&str, &str); 17] = [
+    (
+        "count()",
+        "Number of points (including added and deleted ones)",
+    ),
+    ("dims()", "Number of dimensions of every point"),
+    ("x(i), y(i)", "First and second coordinate of point i"),
+    (
+        "coord(i, dim), coords(i)",
+        "One or all coordinates of point i",
+    ),
+    ("label(i)", "\"normal\" or \"anomaly\""),
+    ("is_normal(i), is_anomaly(i)", "Checks the label of point i"),
+    (
+        "is_selected(i)",
+        "If point i was selected when the script started",
+    ),
+    (
+        "set_x(i, v), set_y(i, v)",
+        "Changes a coordinate of point i",
+    ),
+    ("set_coord(i, dim, v)", "Changes any coordinate of point i"),
+    ("move_by(i, dx, dy)", "Moves point i by dx and dy"),
+    (
+        "set_label(i, label)",
+        "Labels point i as \"normal\" or \"anomaly\"",
+    ),
+    ("toggle_label(i)", "Swaps the label of point i"),
+    (
+        "add(x, y, label), add(coords, label)",
+        "Adds a point and returns its index",
+    ),
+    (
+        "delete(i)",
+        "Removes point i once the script ends (indices stay valid until then)",
+    ),
+    ("is_deleted(i)", "If delete(i) was called for point i"),
+    (
+        "rand(), rand(min, max)",
+        "Uniform random number, [0, 1) if no range is given",
+    ),
+    (
+        "rand_normal(mean, std_dev)",
+        "Normally distributed random number",
+    ),
+];
+
+/// Stops scripts that would otherwise run forever (and freeze the app)
+const MAX_OPERATIONS: u64 = 50_000_000;
+
+/// Points as seen by a running script
+struct ScriptState {
+    points: Vec<DataPoint>,
+    is_deleted: Vec<bool>,
+    dimensions: usize,
+    is_selected: Vec<bool>,
+    rng: SeededRng,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+impl ScriptState {
+    fn index(&self, i: INT) -> ScriptResult<usize> {
+        usize::try_from(i)
+            .ok()
+            .filter(|&i| i < self.points.len())
+            .ok_or_else(|| {
+                format!(
+                    "Point index {i} is out of range, there are {} points",
+                    self.points.len()
+                )
+                .into()
+            })
+    }
+
+    fn dim(&self, dim: INT) -> ScriptResult<usize> {
+        usize::try_from(dim)
+            .ok()
+            .filter(|&dim| dim < self.dimensions)
+            .ok_or_else(|| {
+                format!(
+                    "Dimension {dim} is out of range, points have {} dimensions",
+                    self.dimensions
+                )
+                .into()
+            })
+    }
+
+    fn point(&mut self, i: INT) -> ScriptResult<&mut DataPoint> {
+        let i = self.index(i)?;
+        Ok(&mut self.points[i])
+    }
+
+    fn add(&mut self, coords: Vec<f64>, label: DataLabel) -> ScriptResult<INT> {
+        if coords.len() != self.dimensions {
+            return Err(format!(
+                "Unable to add a point with {} coordinates, points have {} dimensions",
+                coords.len(),
+                self.dimensions
+            )
+            .into());
+        }
+        self.points.push(DataPoint::new(coords, label));
+        self.is_deleted.push(false);
+        self.is_selected.push(false);
+        Ok(self.points.len() as INT - 1)
+    }
+}
+
+/// Accepts integers where numbers are expected so scripts can use `1` instead of `1.0`
+fn number(value: &Dynamic) -> ScriptResult<f64> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|value| value as FLOAT))
+        .map_err(|type_name| format!("Expected a number but got {type_name}").into())
+}
+
+fn parse_label(label: &str) -> ScriptResult<DataLabel> {
+    match label.to_ascii_lowercase().as_str() {
+        "normal" => Ok(DataLabel::Normal),
+        "anomaly" => Ok(DataLabel::Anomaly),
+        _ => Err(format!("Unknown label {label:?}, expected \"normal\" or \"anomaly\"").into()),
+    }
+}
+
+fn label_name(label: DataLabel) -> &'static str {
+    match label {
+        DataLabel::Normal => "normal",
+        DataLabel::Anomaly => "anomaly",
+    }
+}
+
+/// Runs `source` on a copy of `points` and returns the edited points and anything printed
+pub fn run(
+    source: &str,
+    points: &[DataPoint],
+    dimensions: usize,
+    selection: &[usize],
+    seed: u64,
+) -> Result<(Vec<DataPoint>, Vec<String>), String> {
+    let mut is_selected = vec![false; points.len()];
+    for &i in selection {
+        is_selected[i] = true;
+    }
+    let state = Rc::new(RefCell::new(ScriptState {
+        points: points.to_vec(),
+        is_deleted: vec![false; points.len()],
+        dimensions,
+        is_selected,
+        rng: SeededRng::new(seed),
+    }));
+    let printed = Rc::new(RefCell::new(vec![]));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let output = printed.clone();
+    engine.on_print(move |text| output.borrow_mut().push(text.to_string()));
+    let output = printed.clone();
+    engine.on_debug(move |text, _, position| {
+        output.borrow_mut().push(format!("{position:?}: {text}"))
+    });
+
+    // Registers a function with access to the state as `$state`
+    macro_rules! register {
+        ($name:literal, |$state:ident $(, $arg:ident: $ty:ty)*| -> $ret:ty $body:block) => {{
+            let state = state.clone();
+            engine.register_fn($name, move |$($arg: $ty),*| -> $ret {
+                #[allow(unused_mut)]
+                let mut $state = state.borrow_mut();
+                $body
+            });
+        }};
+        ($name:literal, |$state:ident $(, $arg:ident: $ty:ty)*| $body:expr) => {{
+            let state = state.clone();
+            engine.register_fn($name, move |$($arg: $ty),*| {
+                #[allow(unused_mut)]
+                let mut $state = state.borrow_mut();
+                $body
+            });
+        }};
+    }
+
+    register!("count", |s| s.points.len() as INT);
+    register!("dims", |s| s.dimensions as INT);
+    register!("x", |s, i: INT| s.point(i).map(|p| p.x));
+    register!("y", |s, i: INT| s.point(i).map(|p| p.y));
+    register!("coord", |s, i: INT, dim: INT| {
+        let dim = s.dim(dim)?;
+        s.point(i).map(|p| p.coord(dim))
+    });
+    register!("coords", |s, i: INT| s.point(i).map(|p| p
+        .coords()
+        .into_iter()
+        .map(Dynamic::from_float)
+        .collect::<Array>()));
+    register!("label", |s, i: INT| s
+        .point(i)
+        .map(|p| label_name(p.label).to_string()));
+    register!("is_normal", |s, i: INT| s
+        .point(i)
+        .map(|p| p.label.is_normal()));
+    register!("is_anomaly", |s, i: INT| s
+        .point(i)
+        .map(|p| p.label.is_anomaly()));
+    register!("is_selected", |s, i: INT| -> ScriptResult<bool> {
+        let i = s.index(i)?;
+        Ok(s.is_selected[i])
+    });
+    register!("is_deleted", |s, i: INT| -> ScriptResult<bool> {
+        let i = s.index(i)?;
+        Ok(s.is_deleted[i])
+    });
+    register!("set_x", |s, i: INT, value: Dynamic| {
+        let value = number(&value)?;
+        s.point(i).map(|p| p.x = value)
+    });
+    register!("set_y", |s, i: INT, value: Dynamic| {
+        let value = number(&value)?;
+        s.point(i).map(|p| p.y = value)
+    });
+    register!("set_coord", |s,
+                            i: INT,
+                            dim: INT,
+                            value: Dynamic|
+     -> ScriptResult<()> {
+        let value = number(&value)?;
+        let dim = s.dim(dim)?;
+        let point = s.point(i)?;
+        let mut coords = point.coords();
+        coords[dim] = value;
+        point.set_coords(&coords);
+        Ok(())
+    });
+    register!("move_by", |s, i: INT, dx: Dynamic, dy: Dynamic| {
+        let (dx, dy) = (number(&dx)?, number(&dy)?);
+        s.point(i).map(|p| {
+            p.x += dx;
+            p.y += dy;
+        })
+    });
+    register!("set_label", |s, i: INT, label: &str| {
+        let label = parse_label(label)?;
+        s.point(i).map(|p| p.label = label)
+    });
+    register!("toggle_label", |s, i: INT| s.point(i).map(|p| {
+        p.label = match p.label {
+            DataLabel::Normal => DataLabel::Anomaly,
+            DataLabel::Anomaly => DataLabel::Normal,
+        }
+    }));
+    register!("add", |s, x: Dynamic, y: Dynamic, label: &str| {
+        let mut coords = vec![0.0; s.dimensions];
+        coords[0] = number(&x)?;
+        coords[1] = number(&y)?;
+        s.add(coords, parse_label(label)?)
+    });
+    register!("add", |s, coords: Array, label: &str| {
+        let coords = coords.iter().map(number).collect::<ScriptResult<_>>()?;
+        s.add(coords, parse_label(label)?)
+    });
+    register!("delete", |s, i: INT| -> ScriptResult<()> {
+        let i = s.index(i)?;
+        s.is_deleted[i] = true;
+        Ok(())
+    });
+    register!("rand", |s| s.rng.next_f64());
+    register!("rand", |s,
+                       min: Dynamic,
+                       max: Dynamic|
+     -> ScriptResult<f64> {
+        let range = [number(&min)?, number(&max)?];
+        Ok(s.rng.uniform(range))
+    });
+    register!("rand_normal", |s,
+                              mean: Dynamic,
+                              std_dev: Dynamic|
+     -> ScriptResult<f64> {
+        let (mean, std_dev) = (number(&mean)?, number(&std_dev)?);
+        Ok(mean + std_dev * s.rng.normal())
+    });
+
+    engine.run(source).map_err(|e| e.to_string())?;
+    drop(engine); // Releases the other references to the state
+
+    let printed = printed.take();
+    let state = Rc::try_unwrap(state)
+        .map_err(|_| "Script state is still in use".to_string())?
+        .into_inner();
+    let points = state
+        .points
+        .into_iter()
+        .zip(state.is_deleted)
+        .filter_map(|(point, is_deleted)| (!is_deleted).then_some(point))
+        .collect();
+    Ok((points, printed))
+}
+
+impl ManualDataCreatorApp {
+    pub(super) fn window_script(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.is_script_window_open;
+        let mut should_run = false;
+        egui::Window::new("Script Console")
+            .open(&mut is_open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.label("Runs on all points, the result is applied as a single change that can be undone");
+                ui.collapsing("Functions", |ui| {
+                    egui::Grid::new("script_functions")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (signature, description) in FUNCTIONS {
+                                ui.monospace(signature);
+                                ui.label(description);
+                                ui.end_row();
+                            }
+                        });
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.script.source)
+                        .code_editor()
+                        .desired_rows(10)
+                        .desired_width(f32::INFINITY),
+                );
+                ui.horizontal(|ui| {
+                    should_run = ui.button("Run").clicked();
+                    ui.add(egui::DragValue::new(&mut self.script.seed).prefix("Seed: "));
+                    if ui.button("Reset to Example").clicked() {
+                        self.script.source = EXAMPLE.to_string();
+                    }
+                });
+                if !self.script_output.is_empty() {
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .max_height(150.0)
+                        .show(ui, |ui| {
+                            for line in &self.script_output {
+                                ui.monospace(line);
+                            }
+                        });
+                }
+            });
+        self.is_script_window_open = is_open;
+        if should_run {
+            self.run_script();
+        }
+    }
+
+    fn run_script(&mut self) {
+        let change_count = self.data.undo_manager().change_count();
+        match run(
+            &self.script.source,
+            self.data.points(),
+            self.data.dimensions(),
+            self.data.selection(),
+            self.script.seed,
+        ) {
+            Ok((points, printed)) => {
+                self.script_output = printed;
+                self.data.apply_script_result(points, &mut self.status_msg);
+                self.stop_watching_if_changed(change_count);
+            }
+            Err(e) => {
+                self.script_output = vec![format!("Error: {e}")];
+                self.status_msg.add_err(&format!("Script failed: {e}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dataset::test_points;
+
+    fn points() -> Vec<DataPoint> {
+        test_points(&[
+            (&[1.0, 1.0], DataLabel::Normal),
+            (&[6.0, 2.0], DataLabel::Anomaly),
+            (&[4.0, 3.0], DataLabel::Anomaly),
+        ])
+    }
+
+    #[test]
+    fn example_moves_anomalies() {
+        let (result, printed) = run(EXAMPLE, &points(), 2, &[], 0).expect("runs");
+        let xs: Vec<f64> = result.iter().map(|p| p.x).collect();
+        assert_eq!(xs, [1.0, 4.0, 4.0]);
+        assert!(printed.is_empty());
+    }
+
+    #[test]
+    fn add_delete_and_relabel() {
+        let source = r#"
+            let i = add(10, 20, "anomaly");
+            print(i);
+            delete(0);
+            toggle_label(1);
+            set_label(2, "Normal");
+            print(count());
+        "#;
+        let (result, printed) = run(source, &points(), 2, &[], 0).expect("runs");
+        assert_eq!(
+            printed,
+            ["3", "4"],
+            "deleted points keep their index until the end"
+        );
+        let summary: Vec<(f64, DataLabel)> = result.iter().map(|p| (p.x, p.label)).collect();
+        assert_eq!(
+            summary,
+            [
+                (6.0, DataLabel::Normal),
+                (4.0, DataLabel::Normal),
+                (10.0, DataLabel::Anomaly)
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_and_random_values() {
+        let source = "for i in 0..count() { if is_selected(i) { set_y(i, rand(5, 6)); } }";
+        let points = points();
+        let (first, _) = run(source, &points, 2, &[1], 7).expect("runs");
+        let (second, _) = run(source, &points, 2, &[1], 7).expect("runs");
+        assert_eq!(first, second, "same seed gives the same result");
+        assert_eq!(first[0], points[0]);
+        assert!((5.0..6.0).contains(&first[1].y));
+    }
+
+    #[rstest]
+    #[case("x(3)", "out of range")]
+    #[case(r#"set_label(0, "odd")"#, "Unknown label")]
+    #[case("add([1.0, 2.0, 3.0], \"normal\")", "3 coordinates")]
+    #[case("coord(0, 2)", "Dimension 2")]
+    #[case("let a = ;", "Syntax error")]
+    #[case("loop {}", "Too many operations")]
+    fn errors(#[case] source: &str, #[case] expected: &str) {
+        let error = run(source, &points(), 2, &[], 0).expect_err("script fails");
+        assert!(error.contains(expected), "{error}");
+    }
+
+    #[test]
+    fn every_registered_function_listed() {
+        let source = include_str!("script_console.rs");
+        let registered = source
+            .split("register!(\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .filter(|name| !name.is_empty()); // The split in this test
+        for name in registered {
+            assert!(
+                FUNCTIONS
+                    .iter()
+                    .any(|(signature, _)| signature.contains(&format!("{name}("))),
+                "{name} is missing from the list of functions"
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Building, editing and saving datasets without the GUI
+//!
+//! [`Data`] holds the labelled points together with the settings saved with them. Every change is
+//! recorded in its [`UndoManager`] so it can be undone with [`Data::undo`]. Like in the app,
+//! problems and results are reported through a [`StatusMsg`] instead of being returned.
+//!
+//! Nothing in this module depends on egui, so it is available with `default-features = false`.
+//!
+//! ```
+//! use manual_data_creator::dataset::{Data, DataLabel, DataPoint, FileFormat, StatusMsg};
+//!
+//! let mut data = Data::default();
+//! let mut status_msg = StatusMsg::default();
+//! data.add_point(DataPoint::new(vec![1.0, 2.0], DataLabel::Normal), &mut status_msg);
+//! data.add_point(DataPoint::new(vec![3.0, 4.0], DataLabel::Anomaly), &mut status_msg);
+//! assert_eq!(data.points().len(), 2);
+//!
+//! data.undo(&mut status_msg);
+//! assert_eq!(data.points().len(), 1);
+//! assert!(data.has_redo());
+//!
+//! let csv = FileFormat::Csv.write(data.points(), data.dimensions()).unwrap();
+//! let (points, dimensions) = manual_data_creator::dataset::data_io::read_file("data.csv", &csv).unwrap();
+//! assert_eq!((points.as_slice(), dimensions), (data.points(), 2));
+//! ```
+
+#![warn(missing_docs)]
+
+pub use self::{
+    data_definition::{
+        format_timestamp, Augmentation, Data, DataLabel, DataPoint, LabelState, Mirror, PcaBasis,
+        PointMetadata, Projection, SeededRng, Split, SplitSettings, Transform, UndoManager,
+        ViewBookmark,
+    },
+    data_io::FileFormat,
+    diff::{diff, Difference},
+    erasing::EraseSettings,
+    plot_axes::{AspectMode, AxesSettings, AxisSettings},
+    reference_trace::{ReferenceTrace, TraceColumns},
+    snapping::{ExistingPointsSnap, GridSnap, PolarSnap, SnapMode, SnapSettings},
+    status_msg::StatusMsg,
+    view::{MinMaxPair, ZoomTarget},
+};
+
+#[cfg(test)]
+pub(crate) use self::data_definition::test_points;
+
+mod data_definition;
+pub mod data_io;
+mod diff;
+mod erasing;
+mod plot_axes;
+mod reference_trace;
+mod snapping;
+mod status_msg;
+mod view;
+
+fn calculate_distance(p1: &[f64], p2: &[f64]) -> f64 {
+    debug_assert_eq!(p1.len(), p2.len(), "points should have the same dimensions");
+    p1.iter()
+        .zip(p2)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f64>()
+        .sqrt()
+}
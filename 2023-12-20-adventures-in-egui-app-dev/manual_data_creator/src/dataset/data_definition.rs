@@ -0,0 +1,2215 @@
+use std::fmt::Display;
+
+use log::info;
+
+pub use self::augmentation::{Augmentation, Mirror, Transform};
+pub use self::label_state::{LabelState, LabelStates};
+pub use self::metadata::{format_timestamp, PointMetadata};
+pub use self::projection::{PcaBasis, Projection};
+pub use self::seeded_rng::SeededRng;
+pub use self::split::{Split, SplitSettings};
+pub use self::undo_manager::UndoManager;
+use self::undo_manager::{
+    AddEventData, AugmentEventData, ClearEventData, DeleteEventData, EditMetadataEventData,
+    EraseEventData, Event, ImportEventData, InsertEventData, LabelChange, MergeEventData,
+    MetadataEdit, RelabelEventData, ScriptEventData, SetDimensionsEventData,
+    SetTimeSeriesEventData,
+};
+
+use super::{
+    calculate_distance,
+    diff::{self, Difference},
+    erasing::{index_before_removal, EraseSettings},
+    plot_axes::AxesSettings,
+    reference_trace::ReferenceTrace,
+    snapping::SnapSettings,
+    status_msg::StatusMsg,
+    view::{MinMaxPair, ZoomTarget},
+};
+
+mod augmentation;
+mod label_state;
+mod metadata;
+mod projection;
+mod seeded_rng;
+mod split;
+mod undo_manager;
+
+type Points = Vec<DataPoint>;
+/// Points with their index in a list of points
+type IndexedPoints = Vec<(usize, DataPoint)>;
+
+/// A dataset of labelled points with the settings saved alongside it and the history of changes made to it
+#[derive(serde::Deserialize, serde::Serialize, PartialEq)]
+pub struct Data {
+    points: Points,
+    /// Controls how new points are snapped before being added
+    #[serde(default)]
+    snap_settings: SnapSettings,
+    /// Controls which points are removed by deleting and erasing
+    #[serde(default)]
+    erase_settings: EraseSettings,
+    /// Replaced by `snap_settings`, only kept to be able to migrate state saved before snapping was added
+    #[serde(default, skip_serializing)]
+    rounding_decimal_places: Option<u8>,
+    /// Controls how the points are shown on the plot's axes
+    #[serde(default)]
+    axes: AxesSettings,
+    /// Named plot views that can be returned to
+    #[serde(default)]
+    bookmarks: Vec<ViewBookmark>,
+    /// Number of dimensions of every point (at least 2)
+    #[serde(default = "Data::default_dimensions")]
+    dimensions: usize,
+    /// Controls which 2D view of the points is shown on the plot
+    #[serde(default)]
+    projection: Projection,
+    /// Values used for the dimensions of new points that are not set by the click position
+    #[serde(default)]
+    new_point_defaults: Vec<f64>,
+    /// When enabled the first dimension is time and points are kept sorted by it
+    #[serde(default)]
+    is_time_series: bool,
+    /// Which labels are hidden or locked against changes
+    #[serde(default)]
+    label_states: LabelStates,
+    undo_manager: UndoManager,
+    #[serde(skip)]
+    /// Loaded from a file and shown to guide labelling, not saved as it can be large and is easily loaded again
+    reference_trace: Option<ReferenceTrace>,
+    #[serde(skip)]
+    /// Caches the value from `self.points`
+    cached_points_min_max: Option<MinMaxPair>,
+    #[serde(skip)]
+    /// Indices of the selected points (sorted). Cleared whenever the points change as the indices may no longer be valid
+    selection: Vec<usize>,
+    #[serde(skip)]
+    /// [`UndoManager::change_count`] right after the current erase stroke last recorded its removals, `None` until the
+    /// stroke removes something. Later removals of the stroke are only merged into its event while this still matches.
+    erase_stroke_change_count: Option<u64>,
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self {
+            points: Default::default(),
+            snap_settings: Default::default(),
+            erase_settings: Default::default(),
+            rounding_decimal_places: None,
+            axes: Default::default(),
+            bookmarks: Default::default(),
+            dimensions: Self::default_dimensions(),
+            projection: Default::default(),
+            new_point_defaults: vec![0.0; Self::default_dimensions()],
+            is_time_series: false,
+            label_states: Default::default(),
+            undo_manager: Default::default(),
+            reference_trace: None,
+            cached_points_min_max: None,
+            selection: Default::default(),
+            erase_stroke_change_count: None,
+        }
+    }
+}
+
+/// A named area of the plot that can be zoomed back to
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+pub struct ViewBookmark {
+    /// Shown in the list of bookmarks
+    pub name: String,
+    /// Stored in data space so it stays valid if the axes are changed
+    pub bounds: MinMaxPair,
+}
+
+impl Data {
+    const BOUNDARY_MARGIN: f64 = 1.1; //10% increase
+    /// Most dimensions the points can have
+    pub const MAX_DIMENSIONS: usize = 16;
+
+    /// All points, sorted by time in time series mode
+    pub fn points(&self) -> &[DataPoint] {
+        &self.points
+    }
+
+    fn default_dimensions() -> usize {
+        2
+    }
+
+    /// Number of dimensions of every point (at least 2)
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Changes the number of dimensions of all points, recorded as a single undo event. New dimensions are filled
+    /// with 0 and the values of removed dimensions are only kept in the history
+    pub fn set_dimensions(&mut self, dimensions: usize, status_msg: &mut StatusMsg) {
+        let dimensions = dimensions.clamp(2, Self::MAX_DIMENSIONS);
+        if dimensions == self.dimensions {
+            return;
+        }
+        let dimensions_before = self.dimensions;
+        let before = self.points.clone();
+        self.resize_dimensions(dimensions);
+        if dimensions < dimensions_before {
+            status_msg.add_msg(&format!(
+                "Removed the values of dimensions {} to {dimensions_before}, undo to get them back",
+                dimensions + 1
+            ));
+        }
+        self.undo_manager
+            .add_undo(Event::SetDimensions(SetDimensionsEventData {
+                dimensions_before,
+                dimensions_after: dimensions,
+                before,
+                after: self.points.clone(),
+            }));
+    }
+
+    /// Changes the number of dimensions without recording it, new dimensions are filled with 0
+    fn resize_dimensions(&mut self, dimensions: usize) {
+        self.invalidate_cache();
+        for point in self.points.iter_mut() {
+            point.extra_dims.resize(dimensions - 2, 0.0);
+        }
+        self.new_point_defaults.resize(dimensions, 0.0);
+        self.dimensions = dimensions;
+        if !self.projection.is_valid_for(dimensions) {
+            self.projection = Default::default();
+        }
+    }
+
+    /// The 2D view of the points used for plotting and for placing new points
+    pub fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    /// Changes the 2D view of the points, reports an error if it does not fit the number of dimensions or time series mode
+    pub fn set_projection(&mut self, projection: Projection, status_msg: &mut StatusMsg) {
+        if self.is_time_series && !matches!(projection, Projection::Axes([0, _])) {
+            status_msg.add_err("Time series mode requires time (Dim 1) to be shown on the x axis");
+        } else if projection.is_valid_for(self.dimensions) {
+            self.invalidate_cache();
+            self.projection = projection;
+        } else {
+            status_msg.add_err(&format!(
+                "Projection not valid for {} dimensions",
+                self.dimensions
+            ));
+        }
+    }
+
+    /// Sets the projection to the principal components of the current points
+    pub fn set_projection_to_pca(&mut self, status_msg: &mut StatusMsg) {
+        let coords: Vec<Vec<f64>> = self.points.iter().map(DataPoint::coords).collect();
+        match PcaBasis::calculate(&coords, self.dimensions) {
+            Some(basis) => self.set_projection(Projection::Pca(basis), status_msg),
+            None => status_msg.add_err("Unable to calculate PCA. Need at least 2 distinct points"),
+        }
+    }
+
+    /// If the first dimension is time and points are kept sorted by it
+    pub fn is_time_series(&self) -> bool {
+        self.is_time_series
+    }
+
+    /// Turns time series mode on or off. Turning it on sorts the points by time (x)
+    pub fn set_time_series(&mut self, is_enabled: bool, status_msg: &mut StatusMsg) {
+        if is_enabled == self.is_time_series {
+            return;
+        }
+        self.invalidate_cache();
+        let previous_order = if is_enabled {
+            if !matches!(self.projection, Projection::Axes([0, _])) {
+                self.projection = Default::default();
+                status_msg.add_msg("Projection changed to show time (Dim 1) on the x axis");
+            }
+            let order = sort_by_time(&mut self.points);
+            let is_already_sorted = order.iter().enumerate().all(|(i, &prev)| i == prev);
+            (!is_already_sorted).then_some(order)
+        } else {
+            None
+        };
+        self.is_time_series = is_enabled;
+        self.undo_manager
+            .add_undo(Event::SetTimeSeries(SetTimeSeriesEventData {
+                is_enabled,
+                previous_order,
+            }));
+    }
+
+    /// Sets the label of all points with a time (x) in the range, recorded as a single undo event
+    ///
+    /// Points with a locked label keep it and nothing is changed if `label` is locked
+    pub fn relabel_time_range(
+        &mut self,
+        min_x: f64,
+        max_x: f64,
+        label: DataLabel,
+        status_msg: &mut StatusMsg,
+    ) {
+        let indices: Vec<usize> = (0..self.points.len())
+            .filter(|&i| (min_x..=max_x).contains(&self.points[i].x))
+            .collect();
+        self.relabel_points(&indices, label, "range", status_msg);
+    }
+
+    /// Sets the label of all shown points inside of `bounds` (data space), recorded as a single undo event
+    ///
+    /// Points with a locked label keep it and nothing is changed if `label` is locked
+    pub fn relabel_in_bounds(
+        &mut self,
+        bounds: MinMaxPair,
+        label: DataLabel,
+        status_msg: &mut StatusMsg,
+    ) {
+        let indices: Vec<usize> = (0..self.points.len())
+            .filter(|&i| {
+                let point = &self.points[i];
+                let [x, y] = self.project(point);
+                self.is_shown(point)
+                    && (bounds.min[0]..=bounds.max[0]).contains(&x)
+                    && (bounds.min[1]..=bounds.max[1]).contains(&y)
+            })
+            .collect();
+        self.relabel_points(&indices, label, "region", status_msg);
+    }
+
+    /// Changes the label of the point shown closest to `pointer_coordinate` (data space) to the next label, keeping its
+    /// index
+    ///
+    /// `None` (pointer not over the plot) is reported as an error. Nothing is changed if either label is locked.
+    pub fn cycle_label(
+        &mut self,
+        pointer_coordinate: Option<[f64; 2]>,
+        status_msg: &mut StatusMsg,
+    ) {
+        let Some(pointer_coord) = pointer_coordinate else {
+            status_msg.add_err("Unable to relabel point. Cursor not detected over the plot");
+            return;
+        };
+        let Some(index) = self.get_closest_point(pointer_coord, None) else {
+            status_msg.add_msg("No suitable point available for relabelling");
+            return;
+        };
+        let before = self.points[index].label;
+        if !self.is_label_editable(before, "relabel", status_msg) {
+            return;
+        }
+        self.relabel_points(&[index], before.next(), "click", status_msg);
+    }
+
+    /// Sets the label of the points at `indices`, recorded as a single undo event
+    ///
+    /// `source` names what chose the points (for example "range") in status messages
+    fn relabel_points(
+        &mut self,
+        indices: &[usize],
+        label: DataLabel,
+        source: &str,
+        status_msg: &mut StatusMsg,
+    ) {
+        if self.label_states.get(label).is_locked {
+            status_msg.add_msg(&format!(
+                "Unable to relabel points as {} because the label is locked",
+                label.as_str()
+            ));
+            return;
+        }
+        let mut changes = vec![];
+        let mut locked_count = 0;
+        for &index in indices {
+            let point = &mut self.points[index];
+            if point.label == label {
+                continue;
+            }
+            if self.label_states.get(point.label).is_locked {
+                locked_count += 1;
+            } else {
+                changes.push(LabelChange {
+                    index,
+                    before: point.label,
+                    after: label,
+                });
+                point.label = label;
+            }
+        }
+        if locked_count > 0 {
+            status_msg.add_msg(&format!(
+                "{locked_count} point(s) in {source} kept their label because it is locked"
+            ));
+        }
+        if changes.is_empty() {
+            status_msg.add_msg(&format!("No points in {source} needed their label changed"));
+        } else {
+            self.invalidate_cache();
+            self.undo_manager
+                .add_undo(Event::Relabel(RelabelEventData { changes }));
+        }
+    }
+
+    /// Returns the 2D coordinates (data space) that `point` is shown at
+    pub fn project(&self, point: &DataPoint) -> [f64; 2] {
+        match self.projection {
+            // Avoids allocating the list of coordinates for the most common case
+            Projection::Axes([0, 1]) => [point.x, point.y],
+            _ => self.projection.project(&point.coords()),
+        }
+    }
+
+    /// Values used for the hidden dimensions of new points (one per dimension)
+    pub fn new_point_defaults_mut(&mut self) -> &mut Vec<f64> {
+        self.new_point_defaults.resize(self.dimensions, 0.0);
+        &mut self.new_point_defaults
+    }
+
+    /// Returns the range of values of dimension `dim` across all points or `None` if there are no points
+    pub fn dimension_range(&self, dim: usize) -> Option<(f64, f64)> {
+        self.points
+            .iter()
+            .map(|p| p.coord(dim))
+            .fold(None, |acc, v| {
+                Some(acc.map_or((v, v), |(min, max): (f64, f64)| (min.min(v), max.max(v))))
+            })
+    }
+
+    /// How new points are moved onto the grid or the existing points
+    pub fn snap_settings(&self) -> &SnapSettings {
+        &self.snap_settings
+    }
+
+    /// See [`Self::snap_settings`]
+    pub fn snap_settings_mut(&mut self) -> &mut SnapSettings {
+        &mut self.snap_settings
+    }
+
+    /// Which points deleting and erasing remove
+    pub fn erase_settings(&self) -> &EraseSettings {
+        &self.erase_settings
+    }
+
+    /// See [`Self::erase_settings`]
+    pub fn erase_settings_mut(&mut self) -> &mut EraseSettings {
+        &mut self.erase_settings
+    }
+
+    /// Scale and fixed ranges of the plot axes
+    pub fn axes(&self) -> &AxesSettings {
+        &self.axes
+    }
+
+    /// Also invalidates the cached bounds as they depend on the axes
+    pub fn axes_mut(&mut self) -> &mut AxesSettings {
+        self.invalidate_cache();
+        &mut self.axes
+    }
+
+    /// Converts the rounding setting from previously saved state into the equivalent snap settings
+    pub fn migrate_rounding_to_snapping(&mut self) {
+        if let Some(decimal_places) = self.rounding_decimal_places.take() {
+            info!("Migrating rounding to {decimal_places} decimal places into snap settings");
+            self.snap_settings = SnapSettings::from_decimal_places(decimal_places);
+        }
+    }
+
+    /// Needs to be called whenever the points change
+    fn invalidate_cache(&mut self) {
+        self.cached_points_min_max = None;
+        self.selection.clear();
+    }
+
+    /// Indices of the selected points (sorted)
+    pub fn selection(&self) -> &[usize] {
+        &self.selection
+    }
+
+    /// Deselects all points, the points themselves are not changed
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// Selects the points inside of `bounds` (data space), optionally keeping the existing selection
+    pub fn select_in_bounds(&mut self, bounds: MinMaxPair, should_keep_existing: bool) {
+        if !should_keep_existing {
+            self.selection.clear();
+        }
+        for (i, point) in self.points.iter().enumerate() {
+            if !self.is_shown(point) {
+                continue;
+            }
+            let [x, y] = self.project(point);
+            if (bounds.min[0]..=bounds.max[0]).contains(&x)
+                && (bounds.min[1]..=bounds.max[1]).contains(&y)
+            {
+                self.selection.push(i);
+            }
+        }
+        self.selection.sort_unstable();
+        self.selection.dedup();
+    }
+
+    /// The trace drawn behind the points to trace over, if one was loaded
+    pub fn reference_trace(&self) -> Option<&ReferenceTrace> {
+        self.reference_trace.as_ref()
+    }
+
+    /// See [`Self::reference_trace`]
+    pub fn reference_trace_mut(&mut self) -> Option<&mut ReferenceTrace> {
+        self.reference_trace.as_mut()
+    }
+
+    /// Replaces the trace drawn behind the points, `None` removes it
+    pub fn set_reference_trace(&mut self, reference_trace: Option<ReferenceTrace>) {
+        self.reference_trace = reference_trace;
+    }
+
+    /// Saved views in the order they were added
+    pub fn bookmarks(&self) -> &[ViewBookmark] {
+        &self.bookmarks
+    }
+
+    /// Stores `bounds` (plot space) as a bookmark
+    pub fn add_bookmark(&mut self, name: String, bounds: MinMaxPair) {
+        let bounds = self.axes.bounds_to_data(bounds);
+        self.bookmarks.push(ViewBookmark { name, bounds });
+    }
+
+    /// Removes the bookmark at `index`
+    ///
+    /// PANICS: Panics if there is no bookmark at `index`
+    pub fn remove_bookmark(&mut self, index: usize) {
+        self.bookmarks.remove(index);
+    }
+
+    /// Index of the point shown closest to `target_coord` (data space), only points with `label` are considered if given
+    ///
+    /// Points with a hidden label are never considered
+    pub fn get_closest_point(
+        &self,
+        target_coord: [f64; 2],
+        label: Option<DataLabel>,
+    ) -> Option<usize> {
+        let mut result = None;
+        let mut min_distance = f64::INFINITY;
+        for (i, data_point) in self
+            .points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| label.is_none() || p.label == *label.as_ref().unwrap())
+            .filter(|(_, p)| self.is_shown(p))
+        {
+            let distance = calculate_distance(&target_coord, &self.project(data_point));
+            if distance < min_distance {
+                result = Some(i);
+                min_distance = distance;
+            }
+        }
+        result
+    }
+
+    /// Adds a point at `pointer_coordinate` (data space) after snapping it, other dimensions are taken from the new point defaults
+    ///
+    /// `None` (pointer not over the plot) is reported as an error. Nothing is added if the label is locked or hidden
+    pub fn add(
+        &mut self,
+        pointer_coordinate: Option<[f64; 2]>,
+        label: DataLabel,
+        status_msg: &mut StatusMsg,
+    ) {
+        if !self.is_label_editable(label, "add", status_msg) {
+            return;
+        }
+        if let Some(pointer_coord) = pointer_coordinate {
+            let existing_points: Vec<[f64; 2]> =
+                self.points.iter().map(|p| self.project(p)).collect();
+            let point = self.snap_settings.snap(
+                pointer_coord,
+                &existing_points,
+                self.reference_trace.as_ref(),
+            );
+            self.new_point_defaults.resize(self.dimensions, 0.0);
+            let coords = self.projection.unproject(point, &self.new_point_defaults);
+            self.add_point(DataPoint::new(coords, label), status_msg);
+        } else {
+            status_msg.add_err("Unable to add point. Cursor not detected over the plot");
+        }
+    }
+
+    /// Adds `new_point` as is (no snapping), time series are kept sorted by inserting it after any points with the same time
+    pub fn add_point(&mut self, new_point: DataPoint, status_msg: &mut StatusMsg) {
+        if new_point.dimensions() != self.dimensions {
+            status_msg.add_err(&format!(
+                "Unable to add point with {} dimensions to data with {} dimensions",
+                new_point.dimensions(),
+                self.dimensions
+            ));
+            return;
+        }
+        self.invalidate_cache();
+        let index = if self.is_time_series {
+            self.points.partition_point(|p| p.x <= new_point.x)
+        } else {
+            self.points.len()
+        };
+        let event = if index == self.points.len() {
+            Event::Add(AddEventData {
+                point: new_point.clone(),
+            })
+        } else {
+            Event::Insert(InsertEventData {
+                index,
+                point: new_point.clone(),
+            })
+        };
+        self.undo_manager.add_undo(event);
+        self.points.insert(index, new_point); // Actual add action
+    }
+
+    /// Index of the point that deleting at `target_coord` (data space) would remove
+    ///
+    /// This is the closest point with `label` unless it is further away than the maximum delete distance (if limited)
+    pub fn delete_target(&self, target_coord: [f64; 2], label: DataLabel) -> Option<usize> {
+        let index = self.get_closest_point(target_coord, Some(label))?;
+        let settings = &self.erase_settings;
+        let distance = calculate_distance(&target_coord, &self.project(&self.points[index]));
+        (!settings.should_limit_delete_distance || distance <= settings.max_delete_distance)
+            .then_some(index)
+    }
+
+    /// Deletes the [`Data::delete_target`] for `pointer_coordinate` (data space), nothing is deleted if the label is
+    /// locked or hidden
+    pub fn delete(
+        &mut self,
+        pointer_coordinate: Option<[f64; 2]>,
+        label: DataLabel,
+        status_msg: &mut StatusMsg,
+    ) {
+        if !self.is_label_editable(label, "delete", status_msg) {
+            return;
+        }
+        let index_closest_point;
+        if let Some(pointer_coord) = pointer_coordinate {
+            index_closest_point = self.delete_target(pointer_coord, label);
+        } else {
+            status_msg.add_err("Unable to delete point. Cursor not detected over the plot");
+            return;
+        }
+
+        if let Some(index) = index_closest_point {
+            self.invalidate_cache();
+            let removed_point = self.points.remove(index); // Actual delete action
+            self.undo_manager.add_undo(Event::Delete(DeleteEventData {
+                index,
+                point: removed_point,
+            }));
+        } else if self.erase_settings.should_limit_delete_distance {
+            status_msg.add_msg(&format!(
+                "No {} point within {} of the click to delete",
+                label.as_str(),
+                self.erase_settings.max_delete_distance
+            ));
+        } else {
+            status_msg.add_msg("No suitable point available for deleting");
+        }
+    }
+
+    /// Indices (sorted) of the points within the eraser radius of `center` (data space), all labels are considered if
+    /// `label` is `None`
+    ///
+    /// Points with a hidden or locked label are left out
+    pub fn erase_targets(&self, center: [f64; 2], label: Option<DataLabel>) -> Vec<usize> {
+        self.points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| label.is_none_or(|label| p.label == label))
+            .filter(|(_, p)| self.label_states.edit_blocker(p.label).is_none())
+            .filter(|(_, p)| {
+                calculate_distance(&center, &self.project(p)) <= self.erase_settings.radius
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Removes the [`Data::erase_targets`] around `pointer_coordinate` (data space), `label` is ignored if the settings
+    /// say to erase every label
+    ///
+    /// Unless `is_new_stroke` the points are added to the erase event of the same stroke so a whole stroke is undone at
+    /// once. Problems are only reported at the start of a stroke to avoid repeating them while dragging.
+    pub fn erase(
+        &mut self,
+        pointer_coordinate: Option<[f64; 2]>,
+        label: DataLabel,
+        is_new_stroke: bool,
+        status_msg: &mut StatusMsg,
+    ) {
+        if is_new_stroke {
+            self.erase_stroke_change_count = None;
+        }
+        let Some(center) = pointer_coordinate else {
+            if is_new_stroke {
+                status_msg.add_err("Unable to erase points. Cursor not detected over the plot");
+            }
+            return;
+        };
+        let label = if self.erase_settings.should_ignore_label {
+            None
+        } else if is_new_stroke {
+            if !self.is_label_editable(label, "erase", status_msg) {
+                return;
+            }
+            Some(label)
+        } else if self.label_states.edit_blocker(label).is_some() {
+            return;
+        } else {
+            Some(label)
+        };
+        let targets = self.erase_targets(center, label);
+        if targets.is_empty() {
+            if is_new_stroke {
+                status_msg.add_msg("No suitable points within the eraser radius");
+            }
+            return;
+        }
+
+        self.invalidate_cache();
+        let mut removed: Vec<(usize, DataPoint)> = targets
+            .into_iter()
+            .rev()
+            .map(|index| (index, self.points.remove(index))) // Actual erase action
+            .collect();
+        removed.reverse();
+        let is_stroke_recorded = !is_new_stroke
+            && self.erase_stroke_change_count == Some(self.undo_manager.change_count());
+        // Only looked at when merging as it counts as a change and clears the redo history
+        let stroke_event = if is_stroke_recorded {
+            self.undo_manager.last_undo_mut()
+        } else {
+            None
+        };
+        match stroke_event {
+            Some(Event::Erase(event_data)) => {
+                let earlier: Vec<usize> = event_data.removed.iter().map(|(i, _)| *i).collect();
+                event_data
+                    .removed
+                    .extend(removed.into_iter().map(|(index, point)| {
+                        (index_before_removal(index, earlier.iter().copied()), point)
+                    }));
+                event_data.removed.sort_by_key(|(index, _)| *index);
+            }
+            _ => self
+                .undo_manager
+                .add_undo(Event::Erase(EraseEventData { removed })),
+        }
+        self.erase_stroke_change_count = Some(self.undo_manager.change_count());
+    }
+
+    /// Returns `false` if the label of `point` is hidden
+    pub fn is_shown(&self, point: &DataPoint) -> bool {
+        !self.label_states.get(point.label).is_hidden
+    }
+
+    /// Whether the points with `label` are hidden or locked
+    pub fn label_state(&self, label: DataLabel) -> LabelState {
+        self.label_states.get(label)
+    }
+
+    /// Points with a hidden or locked label are left unchanged by edits
+    pub fn label_state_mut(&mut self, label: DataLabel) -> &mut LabelState {
+        self.label_states.get_mut(label)
+    }
+
+    /// Returns `true` if points with `label` can be changed by a click, otherwise explains why the click was ignored
+    fn is_label_editable(
+        &self,
+        label: DataLabel,
+        action: &str,
+        status_msg: &mut StatusMsg,
+    ) -> bool {
+        match self.label_states.edit_blocker(label) {
+            Some(reason) => {
+                status_msg.add_msg(&format!(
+                    "Click ignored. Unable to {action} {} points because the label is {reason}",
+                    label.as_str()
+                ));
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Replaces the metadata of the points at the given indices, recorded as a single undo event
+    pub fn edit_metadata(
+        &mut self,
+        edits: Vec<(usize, PointMetadata)>,
+        status_msg: &mut StatusMsg,
+    ) {
+        let mut changes = vec![];
+        for (index, after) in edits {
+            let Some(point) = self.points.get_mut(index) else {
+                status_msg.add_err(&format!(
+                    "Unable to edit metadata. No point at index {index}"
+                ));
+                continue;
+            };
+            if point.meta != after {
+                let before = std::mem::replace(&mut point.meta, after.clone());
+                changes.push(MetadataEdit {
+                    index,
+                    before,
+                    after,
+                });
+            }
+        }
+        if changes.is_empty() {
+            status_msg.add_msg("No metadata changes to save");
+        } else {
+            self.undo_manager
+                .add_undo(Event::EditMetadata(EditMetadataEventData { changes }));
+        }
+    }
+
+    /// Randomly assigns every point to a split, recorded as a single undo event
+    pub fn assign_splits(&mut self, settings: &SplitSettings, status_msg: &mut StatusMsg) {
+        let labels: Vec<DataLabel> = self.points.iter().map(|p| p.label).collect();
+        let Some(splits) = settings.assign(&labels) else {
+            status_msg.add_err("Unable to assign splits. Split sizes must add up to more than 0");
+            return;
+        };
+        let edits = self.split_edits(splits.into_iter().map(Some).enumerate());
+        self.edit_metadata(edits, status_msg);
+    }
+
+    /// Sets the split of the selected points, recorded as a single undo event
+    pub fn set_split_of_selection(&mut self, split: Option<Split>, status_msg: &mut StatusMsg) {
+        if self.selection.is_empty() {
+            status_msg.add_err("Unable to set split. No points selected");
+            return;
+        }
+        let edits = self.split_edits(self.selection.iter().map(|&i| (i, split)));
+        self.edit_metadata(edits, status_msg);
+    }
+
+    fn split_edits(
+        &self,
+        splits: impl Iterator<Item = (usize, Option<Split>)>,
+    ) -> Vec<(usize, PointMetadata)> {
+        splits
+            .map(|(index, split)| {
+                let mut meta = self.points[index].meta.clone();
+                meta.split = split;
+                (index, meta)
+            })
+            .collect()
+    }
+
+    /// Returns all points as they would be after applying `augmentation` (used to preview it)
+    pub fn augmented_points(&self, augmentation: &Augmentation) -> Points {
+        let (indices, _) = self.augmentation_sources(augmentation);
+        let mut result = augmentation.apply(&self.points, &indices, &self.projection);
+        if self.is_time_series {
+            sort_by_time(&mut result);
+        }
+        result
+    }
+
+    /// Indices of the points `augmentation` uses as a source and how many were skipped because their label is hidden or locked
+    fn augmentation_sources(&self, augmentation: &Augmentation) -> (Vec<usize>, usize) {
+        let candidates: Vec<usize> = if augmentation.is_selection_only {
+            self.selection.clone()
+        } else {
+            (0..self.points.len()).collect()
+        };
+        let count = candidates.len();
+        let indices: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&i| {
+                self.label_states
+                    .edit_blocker(self.points[i].label)
+                    .is_none()
+            })
+            .collect();
+        let skipped_count = count - indices.len();
+        (indices, skipped_count)
+    }
+
+    /// Applies `augmentation` recorded as a single undo event
+    ///
+    /// Points with a hidden or locked label are not used as a source
+    pub fn augment(&mut self, augmentation: &Augmentation, status_msg: &mut StatusMsg) {
+        if augmentation.is_selection_only && self.selection.is_empty() {
+            status_msg.add_err("Unable to augment. No points selected");
+            return;
+        }
+        if self.points.is_empty() {
+            status_msg.add_err("Unable to augment. No points to use as a source");
+            return;
+        }
+        let (indices, skipped_count) = self.augmentation_sources(augmentation);
+        if indices.is_empty() {
+            status_msg
+                .add_err("Unable to augment. All source points have a hidden or locked label");
+            return;
+        }
+        if skipped_count > 0 {
+            status_msg.add_msg(&format!(
+                "{skipped_count} point(s) not augmented because their label is hidden or locked"
+            ));
+        }
+        let after = self.augmented_points(augmentation);
+        self.invalidate_cache();
+        let before = std::mem::replace(&mut self.points, after);
+        self.undo_manager.add_undo(Event::Augment(AugmentEventData {
+            description: augmentation.to_string(),
+            before,
+            after: self.points.clone(),
+        }));
+    }
+
+    /// Replaces all points with `points` (which have `dimensions` dimensions) loaded from `source`
+    ///
+    /// Recorded as a single undo event, which also restores the number of dimensions if it changed
+    pub fn import_points(
+        &mut self,
+        mut points: Points,
+        dimensions: usize,
+        source: &str,
+        status_msg: &mut StatusMsg,
+    ) {
+        if !(2..=Self::MAX_DIMENSIONS).contains(&dimensions) {
+            status_msg.add_err(&format!(
+                "Unable to import from {source}. Points have {dimensions} dimensions but only 2 to {} are supported",
+                Self::MAX_DIMENSIONS
+            ));
+            return;
+        }
+        if self.is_time_series {
+            sort_by_time(&mut points);
+        }
+        self.invalidate_cache();
+        let before = std::mem::replace(&mut self.points, points);
+        let dimensions_before = self.dimensions;
+        if dimensions != self.dimensions {
+            self.resize_dimensions(dimensions); // The new points already have this many
+        }
+        status_msg.add_msg(&format!(
+            "Imported {} points from {source}",
+            self.points.len()
+        ));
+        self.undo_manager.add_undo(Event::Import(ImportEventData {
+            source: source.to_string(),
+            before,
+            after: self.points.clone(),
+            dimensions_before: Some(dimensions_before),
+            dimensions_after: Some(dimensions),
+        }));
+    }
+
+    /// Adds `points` loaded from `source` after the existing points, recorded as a single undo event
+    ///
+    /// The points need to have the same number of dimensions as the existing points
+    pub fn append_points(&mut self, points: Points, source: &str, status_msg: &mut StatusMsg) {
+        if let Some(point) = points
+            .iter()
+            .find(|point| point.extra_dims.len() + 2 != self.dimensions)
+        {
+            status_msg.add_err(&format!(
+                "Unable to append from {source}. Points have {} dimensions but the existing points have {}",
+                point.extra_dims.len() + 2,
+                self.dimensions
+            ));
+            return;
+        }
+        self.invalidate_cache();
+        let before = self.points.clone();
+        let count = points.len();
+        self.points.extend(points);
+        if self.is_time_series {
+            sort_by_time(&mut self.points);
+        }
+        status_msg.add_msg(&format!("Appended {count} points from {source}"));
+        self.undo_manager.add_undo(Event::Import(ImportEventData {
+            source: format!("{source} (appended)"),
+            before,
+            after: self.points.clone(),
+            dimensions_before: Some(self.dimensions),
+            dimensions_after: Some(self.dimensions),
+        }));
+    }
+
+    /// Points in `points` whose label is hidden or locked, in order
+    fn protected_points<'a>(
+        &'a self,
+        points: &'a [DataPoint],
+    ) -> impl Iterator<Item = &'a DataPoint> + 'a {
+        points
+            .iter()
+            .filter(|point| self.label_states.edit_blocker(point.label).is_some())
+    }
+
+    /// Replaces the points with the result of running a script, recorded as a single undo event
+    ///
+    /// Refused if the script changes, adds or deletes points with a hidden or locked label
+    pub fn apply_script_result(&mut self, mut points: Points, status_msg: &mut StatusMsg) {
+        if let Some(index) = points
+            .iter()
+            .position(|p| p.dimensions() != self.dimensions)
+        {
+            status_msg.add_err(&format!(
+                "Script result not applied. Point at index {index} has {} dimensions instead of {}",
+                points[index].dimensions(),
+                self.dimensions
+            ));
+            return;
+        }
+        // Scripts keep the order of the existing points so a change to a protected point shows as a mismatch
+        if !self
+            .protected_points(&points)
+            .eq(self.protected_points(&self.points))
+        {
+            status_msg.add_err(
+                "Script result not applied. It changes, adds or deletes points with a hidden or locked label",
+            );
+            return;
+        }
+        if self.is_time_series {
+            sort_by_time(&mut points);
+        }
+        if points == self.points {
+            status_msg.add_msg("Script made no changes to the points");
+            return;
+        }
+        self.invalidate_cache();
+        let before = std::mem::replace(&mut self.points, points);
+        let event_data = ScriptEventData {
+            before,
+            after: self.points.clone(),
+        };
+        status_msg.add_msg(&format!("Applied {event_data}"));
+        self.undo_manager.add_undo(Event::Script(event_data));
+    }
+
+    /// Applies differences found by [`diff::diff`] for the current points, recorded as a single undo event
+    ///
+    /// `source` names the other version of the points the differences came from. Differences
+    /// involving a point with a hidden or locked label are skipped
+    pub fn accept_differences(
+        &mut self,
+        accepted: &[Difference],
+        source: &str,
+        status_msg: &mut StatusMsg,
+    ) {
+        let is_editable = |point: &DataPoint| self.label_states.edit_blocker(point.label).is_none();
+        let (accepted, skipped): (Vec<Difference>, Vec<Difference>) = accepted
+            .iter()
+            .cloned()
+            .partition(|difference| match difference {
+                Difference::Added(point) => is_editable(point),
+                Difference::Removed(index) => is_editable(&self.points[*index]),
+                Difference::Changed(index, point) => {
+                    is_editable(&self.points[*index]) && is_editable(point)
+                }
+            });
+        if !skipped.is_empty() {
+            status_msg.add_msg(&format!(
+                "{} difference(s) not accepted because they involve points with a hidden or locked label",
+                skipped.len()
+            ));
+        }
+        let accepted = &accepted[..];
+        let description = match accepted {
+            [] => {
+                status_msg.add_msg("No differences to accept");
+                return;
+            }
+            [difference] => format!("{} from {source}", difference.describe(&self.points)),
+            _ => format!("{} differences from {source}", accepted.len()),
+        };
+        let mut points = diff::apply(&self.points, accepted);
+        if self.is_time_series {
+            sort_by_time(&mut points);
+        }
+        self.invalidate_cache();
+        let before = std::mem::replace(&mut self.points, points);
+        // Only the changes are recorded as the history is saved and could otherwise grow with every accept
+        let kept: Vec<usize> = (0..before.len())
+            .filter(|i| {
+                !accepted.iter().any(|difference| {
+                    matches!(difference, Difference::Removed(index) | Difference::Changed(index, _) if index == i)
+                })
+            })
+            .collect();
+        let (removed, added) = point_changes(&before, &kept, &self.points);
+        let event_data = MergeEventData {
+            description,
+            removed,
+            added,
+        };
+        status_msg.add_msg(&format!("Accepted {}", event_data.description));
+        self.undo_manager.add_undo(Event::Merge(event_data));
+    }
+
+    /// Returns `true` if there are no points
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Removes all points (can be undone)
+    ///
+    /// Refused if any point has a hidden or locked label
+    pub fn clear_points(&mut self, status_msg: &mut StatusMsg) {
+        if let Some(point) = self.protected_points(&self.points).next() {
+            status_msg.add_err(&format!(
+                "Unable to delete all points. {} points are {}",
+                point.label.as_str(),
+                self.label_states
+                    .edit_blocker(point.label)
+                    .unwrap_or_default()
+            ));
+            return;
+        }
+        self.invalidate_cache();
+        let mut event_data = ClearEventData { points: vec![] };
+        std::mem::swap(&mut self.points, &mut event_data.points); // Move points into event_data for possible restoration
+        self.undo_manager.add_undo(Event::Clear(event_data));
+    }
+
+    /// Forgets all changes that could be undone or redone
+    pub fn clear_history(&mut self, status_msg: &mut StatusMsg) {
+        if self.undo_manager.is_empty() {
+            status_msg.add_msg("No History to clear");
+        } else {
+            self.undo_manager.clear_all();
+            status_msg.add_msg("Data History Cleared")
+        }
+    }
+
+    /// Bounds (plot space) that fit all points with a margin, cached until the points change
+    pub fn get_points_min_max_w_margin(&mut self) -> MinMaxPair {
+        if let Some(result) = self.cached_points_min_max {
+            result
+        } else {
+            let result = self.min_max_w_margin(self.points.iter());
+            self.cached_points_min_max = Some(result); // Store in cache
+            info!("Points MinMax Calculated:  {result:?}");
+            result
+        }
+    }
+
+    /// Returns the bounds (in plot space) that `target` needs to fit, `None` if the target no longer exists
+    pub fn get_zoom_target_bounds(&mut self, target: ZoomTarget) -> Option<MinMaxPair> {
+        match target {
+            ZoomTarget::AllPoints => Some(self.get_points_min_max_w_margin()),
+            ZoomTarget::Selection => {
+                Some(self.min_max_w_margin(self.selection.iter().map(|&i| &self.points[i])))
+            }
+            ZoomTarget::Label(label) => {
+                Some(self.min_max_w_margin(self.points.iter().filter(|p| p.label == label)))
+            }
+            ZoomTarget::Bookmark(index) => {
+                let bounds = self.bookmarks.get(index)?.bounds;
+                self.axes
+                    .to_plot(bounds.min)
+                    .zip(self.axes.to_plot(bounds.max))
+                    .map(|(min, max)| MinMaxPair { min, max })
+            }
+        }
+    }
+
+    fn min_max_w_margin<'a>(&self, points: impl Iterator<Item = &'a DataPoint>) -> MinMaxPair {
+        // Calculated in plot space because that is where the margin needs to be even
+        let mut min_x = f64::INFINITY;
+        let mut max_x = f64::NEG_INFINITY;
+        let mut min_y = f64::INFINITY;
+        let mut max_y = f64::NEG_INFINITY;
+        for [x, y] in points.filter_map(|point| self.axes.to_plot(self.project(point))) {
+            min_x = x.min(min_x);
+            max_x = x.max(max_x);
+            min_y = y.min(min_y);
+            max_y = y.max(max_y);
+        }
+        if min_x > max_x {
+            // No points to fit
+            (min_x, max_x, min_y, max_y) = (-1.0, 1.0, -1.0, 1.0);
+        }
+        (min_x, max_x) = Self::widen_if_empty(min_x, max_x);
+        (min_y, max_y) = Self::widen_if_empty(min_y, max_y);
+
+        // Add Margin
+        (min_x, max_x) = Self::add_margin(min_x, max_x);
+        (min_y, max_y) = Self::add_margin(min_y, max_y);
+
+        self.axes.apply_fixed_ranges(MinMaxPair {
+            min: [min_x, min_y],
+            max: [max_x, max_y],
+        })
+    }
+
+    /// Gives a range of 2 around `min` if it is the same as `max` (all points in a line)
+    fn widen_if_empty(min: f64, max: f64) -> (f64, f64) {
+        if min < max {
+            (min, max)
+        } else {
+            (min - 1.0, max + 1.0)
+        }
+    }
+
+    fn add_margin(min: f64, max: f64) -> (f64, f64) {
+        let range = max - min;
+        let new_range = range * Self::BOUNDARY_MARGIN;
+        let half_diff = (new_range - range) / 2.0;
+        (min - half_diff, max + half_diff)
+    }
+
+    /// Returns `true` if there is a change that can be undone
+    pub fn has_undo(&self) -> bool {
+        !self.undo_manager.is_undo_empty()
+    }
+
+    /// Returns `true` if there is an undone change that can be redone
+    pub fn has_redo(&self) -> bool {
+        !self.undo_manager.is_redo_empty()
+    }
+
+    /// Undoes the last change to the data or nothing if no changes
+    pub fn undo(&mut self, status_msg: &mut StatusMsg) {
+        if self.undo_manager.is_undo_empty() {
+            status_msg.add_msg("No history available to undo");
+        } else {
+            self.invalidate_cache();
+            let event = self.undo_manager.undo();
+            match event {
+                Event::Add(event_data) => {
+                    debug_assert_eq!(
+                        *self
+                            .points
+                            .last()
+                            .expect("should have a point if we are going to remove it"),
+                        event_data.point,
+                        "should be the most last point added"
+                    );
+                    self.points.pop().expect("should not be None");
+                }
+                Event::Delete(event_data) => {
+                    debug_assert!(self.points.len() >= event_data.index, "index should be less than or equal to points length because it is supposed to be able to be inserted where it came from");
+                    self.points
+                        .insert(event_data.index, event_data.point.clone());
+                }
+                Event::Erase(event_data) => {
+                    for (index, point) in &event_data.removed {
+                        self.points.insert(*index, point.clone());
+                    }
+                }
+                Event::Clear(event_data) => {
+                    debug_assert!(
+                        self.points.is_empty(),
+                        "should not have any points when undoing a clear"
+                    );
+                    std::mem::swap(&mut self.points, &mut event_data.points);
+                }
+                Event::EditMetadata(event_data) => {
+                    for change in event_data.changes.iter().rev() {
+                        self.points[change.index].meta = change.before.clone();
+                    }
+                }
+                Event::Insert(event_data) => {
+                    debug_assert_eq!(
+                        self.points[event_data.index], event_data.point,
+                        "undoing an insert but point is not the same"
+                    );
+                    self.points.remove(event_data.index);
+                }
+                Event::Relabel(event_data) => {
+                    for change in event_data.changes.iter().rev() {
+                        self.points[change.index].label = change.before;
+                    }
+                }
+                Event::Import(event_data) => {
+                    debug_assert_eq!(self.points, event_data.after);
+                    let (dimensions, points) =
+                        (event_data.dimensions_before, event_data.before.clone());
+                    if let Some(dimensions) = dimensions {
+                        self.resize_dimensions(dimensions);
+                    }
+                    self.points = points;
+                }
+                Event::Augment(AugmentEventData { before, after, .. })
+                | Event::Script(ScriptEventData { before, after }) => {
+                    debug_assert_eq!(self.points, *after);
+                    self.points = before.clone();
+                }
+                Event::Merge(event_data) => {
+                    for (index, _) in event_data.added.iter().rev() {
+                        self.points.remove(*index);
+                    }
+                    for (index, point) in &event_data.removed {
+                        self.points.insert(*index, point.clone());
+                    }
+                }
+                Event::SetDimensions(event_data) => {
+                    debug_assert_eq!(self.points, event_data.after);
+                    let (dimensions, points) =
+                        (event_data.dimensions_before, event_data.before.clone());
+                    self.resize_dimensions(dimensions);
+                    self.points = points;
+                }
+                Event::SetTimeSeries(event_data) => {
+                    self.is_time_series = !event_data.is_enabled;
+                    if let Some(previous_order) = &event_data.previous_order {
+                        restore_order(&mut self.points, previous_order);
+                    }
+                }
+            }
+            // status_msg.add_msg(&format!("Undo: {event}")); // TODO: Decide if auto removal of status_msgs is worth implementing (leaving this off pending that)
+        }
+    }
+
+    /// Redoes the last change undone or nothing of no redo available
+    pub fn redo(&mut self, status_msg: &mut StatusMsg) {
+        if self.undo_manager.is_redo_empty() {
+            status_msg.add_msg("No history available to undo");
+        } else {
+            self.invalidate_cache();
+            let event = self.undo_manager.redo();
+            match event {
+                Event::Add(event_data) => self.points.push(event_data.point.clone()),
+                Event::Delete(event_data) => {
+                    debug_assert_eq!(
+                        self.points[event_data.index], event_data.point,
+                        "redoing a delete but point is not the same"
+                    );
+                    self.points.remove(event_data.index);
+                }
+                Event::Erase(event_data) => {
+                    for (index, point) in event_data.removed.iter().rev() {
+                        debug_assert_eq!(
+                            self.points[*index], *point,
+                            "redoing an erase but point is not the same"
+                        );
+                        self.points.remove(*index);
+                    }
+                }
+                Event::Clear(event_data) => {
+                    debug_assert!(
+                        event_data.points.is_empty(),
+                        "should not have any points when redoing a clear"
+                    );
+                    std::mem::swap(&mut self.points, &mut event_data.points);
+                }
+                Event::EditMetadata(event_data) => {
+                    for change in event_data.changes.iter() {
+                        self.points[change.index].meta = change.after.clone();
+                    }
+                }
+                Event::Insert(event_data) => {
+                    self.points
+                        .insert(event_data.index, event_data.point.clone());
+                }
+                Event::Relabel(event_data) => {
+                    for change in event_data.changes.iter() {
+                        self.points[change.index].label = change.after;
+                    }
+                }
+                Event::Import(event_data) => {
+                    debug_assert_eq!(self.points, event_data.before);
+                    let (dimensions, points) =
+                        (event_data.dimensions_after, event_data.after.clone());
+                    if let Some(dimensions) = dimensions {
+                        self.resize_dimensions(dimensions);
+                    }
+                    self.points = points;
+                }
+                Event::Augment(AugmentEventData { before, after, .. })
+                | Event::Script(ScriptEventData { before, after }) => {
+                    debug_assert_eq!(self.points, *before);
+                    self.points = after.clone();
+                }
+                Event::Merge(event_data) => {
+                    for (index, _) in event_data.removed.iter().rev() {
+                        self.points.remove(*index);
+                    }
+                    for (index, point) in &event_data.added {
+                        self.points.insert(*index, point.clone());
+                    }
+                }
+                Event::SetDimensions(event_data) => {
+                    debug_assert_eq!(self.points, event_data.before);
+                    let (dimensions, points) =
+                        (event_data.dimensions_after, event_data.after.clone());
+                    self.resize_dimensions(dimensions);
+                    self.points = points;
+                }
+                Event::SetTimeSeries(event_data) => {
+                    self.is_time_series = event_data.is_enabled;
+                    if event_data.is_enabled {
+                        let order = sort_by_time(&mut self.points);
+                        debug_assert!(
+                            event_data
+                                .previous_order
+                                .as_ref()
+                                .is_none_or(|previous| *previous == order),
+                            "sort should be repeatable"
+                        );
+                    }
+                }
+            }
+            // status_msg.add_msg(&format!("Redo: {event}")); // TODO: Decide if auto removal of status_msgs is worth implementing (leaving this off pending that)
+        }
+    }
+
+    /// Returns `true` if there is anything to undo or redo
+    pub fn has_history(&self) -> bool {
+        !self.undo_manager.is_empty()
+    }
+
+    /// Sets the number of changes that can be undone, `None` for no limit
+    pub fn set_history_size(&mut self, value: Option<u16>) {
+        self.undo_manager.set_max_history_size(value);
+    }
+
+    /// Number of changes that can be undone, `None` for no limit
+    pub fn max_history_size(&self) -> Option<u16> {
+        self.undo_manager.max_history_size()
+    }
+
+    /// Number of changes that can be undone unless set otherwise
+    pub fn get_default_max_history_size(&self) -> Option<u16> {
+        UndoManager::default_max_history()
+    }
+
+    /// The history of changes, see [`Self::undo`] and [`Self::redo`]
+    pub fn undo_manager(&self) -> &UndoManager {
+        &self.undo_manager
+    }
+}
+/// Sorts the points by time (x) keeping the order of equal times. Returns the previous index of each point
+fn sort_by_time(points: &mut Points) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a].x.total_cmp(&points[b].x));
+    *points = order.iter().map(|&i| points[i].clone()).collect();
+    order
+}
+
+/// The points removed from `before` and added to get `after`, with their index in `before` and `after` respectively
+///
+/// The points of `before` at `kept` (sorted) are matched in order to equal points of `after`, any point that cannot be
+/// matched counts as removed and added
+fn point_changes(
+    before: &[DataPoint],
+    kept: &[usize],
+    after: &[DataPoint],
+) -> (IndexedPoints, IndexedPoints) {
+    let mut is_matched = vec![false; before.len()];
+    let mut kept = kept.iter().peekable();
+    let mut added = vec![];
+    for (index, point) in after.iter().enumerate() {
+        match kept.peek() {
+            Some(&&i) if before[i] == *point => {
+                is_matched[i] = true;
+                kept.next();
+            }
+            _ => added.push((index, point.clone())),
+        }
+    }
+    let removed = before
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !is_matched[*i])
+        .map(|(i, point)| (i, point.clone()))
+        .collect();
+    (removed, added)
+}
+
+/// Reverses [`sort_by_time`] using the order it returned
+fn restore_order(points: &mut Points, previous_order: &[usize]) {
+    debug_assert_eq!(points.len(), previous_order.len());
+    let mut restored: Vec<Option<DataPoint>> = vec![None; points.len()];
+    for (point, &index) in std::mem::take(points).into_iter().zip(previous_order) {
+        restored[index] = Some(point);
+    }
+    *points = restored
+        .into_iter()
+        .map(|point| point.expect("previous order should be a permutation"))
+        .collect();
+}
+
+/// Class of a point
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum DataLabel {
+    /// An ordinary point
+    Normal,
+    /// A point that does not fit the pattern of the normal points
+    Anomaly,
+}
+
+impl DataLabel {
+    /// Name shown in the UI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataLabel::Normal => "Normal",
+            DataLabel::Anomaly => "Anomaly",
+        }
+    }
+
+    /// The label that follows this one when cycling through all labels
+    #[must_use]
+    pub fn next(&self) -> DataLabel {
+        match self {
+            DataLabel::Normal => DataLabel::Anomaly,
+            DataLabel::Anomaly => DataLabel::Normal,
+        }
+    }
+
+    /// Returns `true` if the data label is [`Normal`].
+    ///
+    /// [`Normal`]: DataLabel::Normal
+    #[must_use]
+    pub fn is_normal(&self) -> bool {
+        matches!(self, Self::Normal)
+    }
+
+    /// Returns `true` if the data label is [`Anomaly`].
+    ///
+    /// [`Anomaly`]: DataLabel::Anomaly
+    #[must_use]
+    pub fn is_anomaly(&self) -> bool {
+        matches!(self, Self::Anomaly)
+    }
+}
+
+/// A labelled point with any number of dimensions (at least 2)
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+pub struct DataPoint {
+    /// First dimension
+    pub x: f64,
+    /// Second dimension
+    pub y: f64,
+    /// Values of the dimensions after the first two, empty for 2D data
+    #[serde(default)]
+    pub extra_dims: Vec<f64>,
+    /// Class of the point
+    pub label: DataLabel,
+    /// Optional extra information, see [`PointMetadata`]
+    #[serde(default)]
+    pub meta: PointMetadata,
+}
+
+impl Display for DataPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:.2}, {:.2}, {}]",
+            self.x,
+            self.y,
+            match self.label {
+                DataLabel::Normal => "N",
+                DataLabel::Anomaly => "A",
+            }
+        )
+    }
+}
+
+impl DataPoint {
+    /// `coords` must have at least 2 values
+    pub fn new(coords: Vec<f64>, label: DataLabel) -> Self {
+        Self {
+            meta: PointMetadata::new_now(),
+            ..Self::without_timestamp(coords, label)
+        }
+    }
+
+    /// Same as [`Self::new`] but without a creation time, for points read from files that do not store metadata
+    pub fn without_timestamp(coords: Vec<f64>, label: DataLabel) -> Self {
+        debug_assert!(coords.len() >= 2, "points have at least 2 dimensions");
+        Self {
+            x: coords[0],
+            y: coords[1],
+            extra_dims: coords[2..].to_vec(),
+            label,
+            meta: PointMetadata::default(),
+        }
+    }
+
+    /// Returns the value of dimension `dim` (0 is x and 1 is y)
+    pub fn coord(&self, dim: usize) -> f64 {
+        match dim {
+            0 => self.x,
+            1 => self.y,
+            _ => self.extra_dims.get(dim - 2).copied().unwrap_or_default(),
+        }
+    }
+
+    /// Formats all coordinates as `(x, y, ...)` with 3 decimal places
+    pub fn format_coords(&self) -> String {
+        let values: Vec<String> = self.coords().iter().map(|v| format!("{v:.3}")).collect();
+        format!("({})", values.join(", "))
+    }
+
+    /// Sets the values of all dimensions, `coords` must have at least 2 values
+    pub fn set_coords(&mut self, coords: &[f64]) {
+        self.x = coords[0];
+        self.y = coords[1];
+        self.extra_dims = coords[2..].to_vec();
+    }
+
+    /// Number of coordinates (at least 2)
+    pub fn dimensions(&self) -> usize {
+        2 + self.extra_dims.len()
+    }
+
+    /// Returns the values of all dimensions
+    pub fn coords(&self) -> Vec<f64> {
+        let mut result = Vec::with_capacity(2 + self.extra_dims.len());
+        result.push(self.x);
+        result.push(self.y);
+        result.extend_from_slice(&self.extra_dims);
+        result
+    }
+}
+
+/// Points without creation times from `(coords, label)` pairs, shared by tests that compare points read back
+#[cfg(test)]
+pub(crate) fn test_points(rows: &[(&[f64], DataLabel)]) -> Vec<DataPoint> {
+    rows.iter()
+        .map(|(coords, label)| DataPoint::without_timestamp(coords.to_vec(), *label))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
+    fn margin_in_expected_range() {
+        assert!(Data::BOUNDARY_MARGIN >= 1.0 && Data::BOUNDARY_MARGIN <= 2.0);
+    }
+
+    #[test]
+    fn closest_point_respects_label() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([1.0, 0.0]), DataLabel::Anomaly, &mut status_msg);
+        data.add(Some([5.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        assert_eq!(data.get_closest_point([0.9, 0.0], None), Some(1));
+        assert_eq!(
+            data.get_closest_point([0.9, 0.0], Some(DataLabel::Normal)),
+            Some(0)
+        );
+        assert_eq!(
+            data.get_closest_point([4.0, 0.0], Some(DataLabel::Anomaly)),
+            Some(1)
+        );
+        assert_eq!(Data::default().get_closest_point([0.0, 0.0], None), None);
+    }
+
+    #[test]
+    fn locked_and_hidden_labels_ignore_clicks() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([1.0, 0.0]), DataLabel::Anomaly, &mut status_msg);
+
+        data.label_state_mut(DataLabel::Normal).is_locked = true;
+        data.add(Some([2.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.delete(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        assert_eq!(data.points().len(), 2);
+        assert!(status_msg
+            .get_msg()
+            .contains("Unable to delete Normal points because the label is locked"));
+
+        data.relabel_time_range(-1.0, 2.0, DataLabel::Normal, &mut status_msg);
+        data.relabel_time_range(-1.0, 2.0, DataLabel::Anomaly, &mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Normal);
+        assert_eq!(data.points()[1].label, DataLabel::Anomaly);
+
+        data.label_state_mut(DataLabel::Normal).is_locked = false;
+        data.label_state_mut(DataLabel::Anomaly).is_hidden = true;
+        assert_eq!(data.get_closest_point([0.9, 0.0], None), Some(0));
+        data.delete(Some([1.0, 0.0]), DataLabel::Anomaly, &mut status_msg);
+        assert_eq!(data.points().len(), 2);
+        assert!(status_msg
+            .get_msg()
+            .contains("Unable to delete Anomaly points because the label is hidden"));
+        data.select_in_bounds(
+            MinMaxPair {
+                min: [-1.0, -1.0],
+                max: [2.0, 1.0],
+            },
+            false,
+        );
+        assert_eq!(data.selection(), [0]);
+    }
+
+    #[test]
+    fn erase_stroke_undone_at_once() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in 0..6 {
+            data.add(Some([x as f64, 0.0]), DataLabel::Normal, &mut status_msg);
+        }
+        data.add(Some([2.0, 0.1]), DataLabel::Anomaly, &mut status_msg);
+        let before = data.points().to_vec();
+
+        data.erase_settings_mut().radius = 1.0;
+        data.erase(Some([1.0, 0.0]), DataLabel::Normal, true, &mut status_msg);
+        data.erase(Some([4.0, 0.0]), DataLabel::Normal, false, &mut status_msg);
+        data.erase(Some([2.0, 0.0]), DataLabel::Normal, false, &mut status_msg);
+        let xs: Vec<f64> = data.points().iter().map(|p| p.x).collect();
+        assert_eq!(xs, [2.0], "only the anomaly is left");
+        let descriptions = data.undo_manager().undo_descriptions();
+        assert_eq!(descriptions.len(), 8);
+        assert_eq!(descriptions.last().unwrap(), "Erase 6 point(s)");
+
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), before);
+        data.redo(&mut status_msg);
+        assert_eq!(data.points().len(), 1);
+
+        data.erase_settings_mut().should_ignore_label = true;
+        data.erase(Some([2.0, 0.0]), DataLabel::Normal, true, &mut status_msg);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn erase_stroke_starting_on_empty_space_recorded_separately() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([10.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.erase(Some([0.0, 0.0]), DataLabel::Normal, true, &mut status_msg);
+        data.undo(&mut status_msg);
+        data.redo(&mut status_msg);
+        let change_count = data.undo_manager().change_count();
+
+        data.erase(Some([5.0, 0.0]), DataLabel::Normal, true, &mut status_msg);
+        assert_eq!(
+            data.undo_manager().change_count(),
+            change_count,
+            "erasing nothing is not a change"
+        );
+        data.erase(Some([10.0, 0.0]), DataLabel::Normal, false, &mut status_msg);
+        let descriptions = data.undo_manager().undo_descriptions();
+        assert_eq!(
+            descriptions[descriptions.len() - 2..],
+            ["Erase 1 point(s)", "Erase 1 point(s)"]
+        );
+        data.undo(&mut status_msg);
+        assert_eq!(data.points().len(), 1);
+        assert_eq!(data.points()[0].x, 10.0);
+    }
+
+    #[test]
+    fn delete_limited_to_max_distance() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.erase_settings_mut().should_limit_delete_distance = true;
+        data.erase_settings_mut().max_delete_distance = 1.0;
+        assert_eq!(data.delete_target([2.0, 0.0], DataLabel::Normal), None);
+        data.delete(Some([2.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        assert_eq!(data.points().len(), 1);
+        data.delete(Some([0.5, 0.0]), DataLabel::Normal, &mut status_msg);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn cycling_label_keeps_index() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([1.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.cycle_label(Some([0.1, 0.0]), &mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Anomaly);
+        assert_eq!(
+            data.undo_manager().undo_descriptions().last().unwrap(),
+            "Relabel Point at index: 0 from Normal to Anomaly"
+        );
+        data.cycle_label(Some([0.1, 0.0]), &mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Normal);
+        data.undo(&mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Anomaly);
+
+        data.label_state_mut(DataLabel::Normal).is_locked = true;
+        data.cycle_label(Some([0.1, 0.0]), &mut status_msg);
+        data.cycle_label(Some([0.9, 0.0]), &mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Anomaly);
+        assert_eq!(data.points()[1].label, DataLabel::Normal);
+    }
+
+    #[test]
+    fn region_relabel_skips_hidden_and_locked() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([0.0, 0.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([2.0, 2.0]), DataLabel::Anomaly, &mut status_msg);
+        data.add(Some([5.0, 5.0]), DataLabel::Normal, &mut status_msg);
+        data.label_state_mut(DataLabel::Anomaly).is_hidden = true;
+        let bounds = MinMaxPair {
+            min: [-1.0, -1.0],
+            max: [3.0, 3.0],
+        };
+        data.relabel_in_bounds(bounds, DataLabel::Anomaly, &mut status_msg);
+        let labels: Vec<DataLabel> = data.points().iter().map(|p| p.label).collect();
+        assert_eq!(
+            labels,
+            [
+                DataLabel::Anomaly,
+                DataLabel::Anomaly,
+                DataLabel::Anomaly,
+                DataLabel::Normal
+            ]
+        );
+        assert_eq!(
+            data.undo_manager().undo_descriptions().last().unwrap(),
+            "Relabel 2 point(s)"
+        );
+
+        data.undo(&mut status_msg);
+        data.label_state_mut(DataLabel::Anomaly).is_hidden = false;
+        data.label_state_mut(DataLabel::Anomaly).is_locked = true;
+        data.relabel_in_bounds(bounds, DataLabel::Normal, &mut status_msg);
+        assert_eq!(data.points()[2].label, DataLabel::Anomaly);
+    }
+
+    #[test]
+    fn min_max_uses_plot_space() {
+        let mut data = Data::default();
+        data.axes_mut().x.is_log = true;
+        data.points = vec![
+            DataPoint::new(vec![1000.0, 5.0], DataLabel::Normal),
+            DataPoint::new(vec![-1.0, -5.0], DataLabel::Anomaly), // Not shown on log x axis
+        ];
+        let result = data.get_points_min_max_w_margin();
+        // Only one point is shown so it gets a range of 2
+        assert_eq!(result.min[1], Data::add_margin(4.0, 6.0).0);
+        assert_eq!(result.max[0], Data::add_margin(2.0, 4.0).1);
+    }
+
+    #[test]
+    fn min_max_fits_points_far_from_origin() {
+        let mut data = Data {
+            points: vec![
+                DataPoint::new(vec![100.0, 200.0], DataLabel::Normal),
+                DataPoint::new(vec![110.0, 220.0], DataLabel::Anomaly),
+            ],
+            ..Default::default()
+        };
+        let result = data.get_points_min_max_w_margin();
+        assert_eq!(result.min[0], Data::add_margin(100.0, 110.0).0);
+        assert_eq!(result.max[1], Data::add_margin(200.0, 220.0).1);
+
+        data.points.clear();
+        data.cached_points_min_max = None;
+        let result = data.get_points_min_max_w_margin();
+        assert_eq!(result.min, [Data::add_margin(-1.0, 1.0).0; 2]);
+    }
+
+    #[test]
+    fn selection_cleared_on_change() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in 0..5 {
+            data.add(Some([x as f64, 0.0]), DataLabel::Normal, &mut status_msg);
+        }
+        data.select_in_bounds(
+            MinMaxPair {
+                min: [0.5, -1.0],
+                max: [2.5, 1.0],
+            },
+            false,
+        );
+        assert_eq!(data.selection(), &[1, 2]);
+        data.undo(&mut status_msg);
+        assert!(data.selection().is_empty());
+    }
+
+    #[test]
+    fn metadata_edit_undo_redo() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 2.0]), DataLabel::Anomaly, &mut status_msg);
+        let original = data.points()[0].meta.clone();
+        let edited = PointMetadata {
+            note: "spike".to_string(),
+            weight: 2.5,
+            group: Some(3),
+            ..original.clone()
+        };
+        data.edit_metadata(vec![(0, edited.clone())], &mut status_msg);
+        assert_eq!(data.points()[0].meta, edited);
+        data.undo(&mut status_msg);
+        assert_eq!(data.points()[0].meta, original);
+        data.redo(&mut status_msg);
+        assert_eq!(data.points()[0].meta, edited);
+    }
+
+    #[test]
+    fn hidden_dimensions_from_defaults() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 2.0]), DataLabel::Normal, &mut status_msg);
+        data.set_dimensions(4, &mut status_msg);
+        assert_eq!(data.points()[0].coords(), vec![1.0, 2.0, 0.0, 0.0]);
+
+        data.set_projection(Projection::Axes([3, 1]), &mut status_msg);
+        data.new_point_defaults_mut()[0] = 7.0;
+        data.new_point_defaults_mut()[2] = 8.0;
+        data.add(Some([5.0, 6.0]), DataLabel::Anomaly, &mut status_msg);
+        assert_eq!(data.points()[1].coords(), vec![7.0, 6.0, 8.0, 5.0]);
+        assert_eq!(data.project(&data.points()[1]), [5.0, 6.0]);
+
+        data.set_dimensions(3, &mut status_msg);
+        assert_eq!(data.projection(), &Projection::default());
+        assert_eq!(data.points()[1].coords(), vec![7.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn removed_dimensions_restored_by_undo() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.set_dimensions(3, &mut status_msg);
+        data.new_point_defaults_mut()[2] = 9.0;
+        data.add(Some([1.0, 2.0]), DataLabel::Normal, &mut status_msg);
+
+        data.set_dimensions(2, &mut status_msg);
+        assert_eq!(data.points()[0].coords(), vec![1.0, 2.0]);
+        data.undo(&mut status_msg);
+        assert_eq!(data.dimensions(), 3);
+        assert_eq!(data.points()[0].coords(), vec![1.0, 2.0, 9.0]);
+        data.undo(&mut status_msg);
+        assert!(
+            data.points().is_empty(),
+            "adding the point is still undoable"
+        );
+        data.redo(&mut status_msg);
+        data.redo(&mut status_msg);
+        assert_eq!(data.dimensions(), 2);
+        assert_eq!(data.points()[0].coords(), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn split_assignment_undo() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in 0..10 {
+            data.add(Some([x as f64, 0.0]), DataLabel::Normal, &mut status_msg);
+        }
+        data.assign_splits(&SplitSettings::default(), &mut status_msg);
+        assert!(data.points().iter().all(|p| p.meta.split.is_some()));
+        data.undo(&mut status_msg);
+        assert!(data.points().iter().all(|p| p.meta.split.is_none()));
+
+        data.select_in_bounds(
+            MinMaxPair {
+                min: [-0.5, -1.0],
+                max: [1.5, 1.0],
+            },
+            false,
+        );
+        data.set_split_of_selection(Some(Split::Test), &mut status_msg);
+        let test_count = data
+            .points()
+            .iter()
+            .filter(|p| p.meta.split == Some(Split::Test))
+            .count();
+        assert_eq!(test_count, 2);
+    }
+
+    #[test]
+    fn import_undo_restores_points() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        let original = data.points().to_vec();
+        let imported = vec![
+            DataPoint::new(vec![5.0, 6.0], DataLabel::Anomaly),
+            DataPoint::new(vec![7.0, 8.0], DataLabel::Normal),
+        ];
+        data.import_points(imported.clone(), 2, "test", &mut status_msg);
+        assert_eq!(data.points(), imported);
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), original);
+        data.redo(&mut status_msg);
+        assert_eq!(data.points(), imported);
+
+        data.import_points(vec![], 1, "test", &mut status_msg);
+        assert_eq!(data.points(), imported, "invalid dimensions rejected");
+    }
+
+    #[test]
+    fn import_with_other_dimensions_undone() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([2.0, 2.0]), DataLabel::Anomaly, &mut status_msg);
+        let original = data.points().to_vec();
+        let imported = vec![DataPoint::new(vec![5.0, 6.0, 7.0], DataLabel::Normal)];
+
+        data.import_points(imported.clone(), 3, "test", &mut status_msg);
+        assert_eq!(data.undo_manager().undo_descriptions().len(), 3);
+        data.undo(&mut status_msg);
+        assert_eq!(data.dimensions(), 2);
+        assert_eq!(data.points(), original);
+        data.undo(&mut status_msg);
+        assert_eq!(data.points().len(), 1, "earlier history kept");
+        data.redo(&mut status_msg);
+        data.redo(&mut status_msg);
+        assert_eq!(data.dimensions(), 3);
+        assert_eq!(data.points(), imported);
+    }
+
+    #[test]
+    fn accepted_differences_undone_together() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([2.0, 2.0]), DataLabel::Normal, &mut status_msg);
+        let original = data.points().to_vec();
+        let other = vec![
+            DataPoint::new(vec![1.0, 1.0], DataLabel::Anomaly),
+            DataPoint::new(vec![5.0, 5.0], DataLabel::Normal),
+        ];
+        let differences = diff::diff(data.points(), &other, 0.1);
+        assert_eq!(differences.len(), 3, "relabelled, removed and added");
+
+        data.accept_differences(&differences[..1], "other.csv", &mut status_msg);
+        assert_eq!(data.points()[0].label, DataLabel::Anomaly);
+        assert_eq!(data.points().len(), 2);
+        let differences = diff::diff(data.points(), &other, 0.1);
+        data.accept_differences(&differences, "other.csv", &mut status_msg);
+        assert_eq!(data.points().len(), 2);
+        assert!(diff::diff(data.points(), &other, 0.0).is_empty());
+
+        data.undo(&mut status_msg);
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), original);
+    }
+
+    #[test]
+    fn accept_skips_differences_of_locked_points() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([2.0, 2.0]), DataLabel::Anomaly, &mut status_msg);
+        data.label_state_mut(DataLabel::Anomaly).is_locked = true;
+        let other = vec![
+            DataPoint::new(vec![1.0, 1.0], DataLabel::Anomaly),
+            DataPoint::new(vec![5.0, 5.0], DataLabel::Anomaly),
+            DataPoint::new(vec![6.0, 6.0], DataLabel::Normal),
+        ];
+        let differences = diff::diff(data.points(), &other, 0.1);
+        status_msg.clear();
+        data.accept_differences(&differences, "other.csv", &mut status_msg);
+        assert!(!status_msg.is_empty());
+        let points: Vec<_> = data
+            .points()
+            .iter()
+            .map(|p| (p.coords(), p.label))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (vec![1.0, 1.0], DataLabel::Normal),
+                (vec![2.0, 2.0], DataLabel::Anomaly),
+                (vec![6.0, 6.0], DataLabel::Normal),
+            ],
+            "only the addition of a normal point accepted"
+        );
+    }
+
+    #[test]
+    fn accepted_move_recorded_as_change_in_time_series() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in 0..5 {
+            data.add(Some([x as f64, 0.0]), DataLabel::Normal, &mut status_msg);
+        }
+        data.set_time_series(true, &mut status_msg);
+        let original = data.points().to_vec();
+        let mut other = original.clone();
+        other[1].x = 3.5; // Moves after the point at 3
+        other.push(DataPoint::new(vec![0.0, 0.0], DataLabel::Normal)); // Same as the first point
+        let differences = diff::diff(data.points(), &other, 0.0);
+
+        let before = data.points().to_vec();
+        data.accept_differences(&differences, "other.csv", &mut status_msg);
+        let xs: Vec<f64> = data.points().iter().map(|p| p.x).collect();
+        assert_eq!(xs, [0.0, 0.0, 2.0, 3.0, 3.5, 4.0]);
+        let (removed, added) = point_changes(&before, &[0, 2, 3, 4], data.points());
+        assert_eq!(removed.len(), 1, "only the moved point");
+        assert_eq!(added.len(), 2, "the moved and the added point");
+
+        let after = data.points().to_vec();
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), original);
+        data.redo(&mut status_msg);
+        assert_eq!(data.points(), after);
+    }
+
+    #[test]
+    fn append_is_one_undo_event() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        let original = data.points().to_vec();
+        let appended = vec![
+            DataPoint::new(vec![5.0, 6.0], DataLabel::Anomaly),
+            DataPoint::new(vec![7.0, 8.0], DataLabel::Normal),
+        ];
+        data.append_points(appended.clone(), "test", &mut status_msg);
+        assert_eq!(data.points(), [original.clone(), appended].concat());
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), original);
+
+        data.append_points(
+            vec![DataPoint::new(vec![1.0, 2.0, 3.0], DataLabel::Normal)],
+            "test",
+            &mut status_msg,
+        );
+        assert_eq!(data.points(), original, "different dimensions rejected");
+    }
+
+    #[test]
+    fn add_point_without_snapping() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.snap_settings_mut().is_enabled = true;
+        data.snap_settings_mut().mode = crate::dataset::SnapMode::Grid;
+        data.set_time_series(true, &mut status_msg);
+        for x in [3.0, 1.0, 2.0] {
+            data.add_point(
+                DataPoint::new(vec![x, 0.123], DataLabel::Normal),
+                &mut status_msg,
+            );
+        }
+        let xs: Vec<f64> = data.points().iter().map(|p| p.x).collect();
+        assert_eq!(xs, [1.0, 2.0, 3.0], "kept sorted by time");
+        assert!(data.points().iter().all(|p| p.y == 0.123), "not snapped");
+        assert_eq!(data.undo_manager().undo_descriptions().len(), 4);
+
+        data.add_point(
+            DataPoint::new(vec![1.0, 2.0, 3.0], DataLabel::Normal),
+            &mut status_msg,
+        );
+        assert_eq!(data.points().len(), 3, "different dimensions rejected");
+        data.undo(&mut status_msg);
+        assert_eq!(data.points().len(), 2);
+    }
+
+    #[test]
+    fn augment_is_one_undo_event() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in [1.0, 2.0] {
+            data.add(Some([x, x]), DataLabel::Normal, &mut status_msg);
+        }
+        let original = data.points().to_vec();
+        let augmentation = Augmentation {
+            copies: 3,
+            ..Default::default()
+        };
+        let preview = data.augmented_points(&augmentation);
+        data.augment(&augmentation, &mut status_msg);
+        assert_eq!(data.points(), preview);
+        assert_eq!(data.points().len(), 2 + 2 * 3);
+
+        data.undo(&mut status_msg);
+        assert_eq!(data.points(), original);
+        data.redo(&mut status_msg);
+        assert_eq!(data.points(), preview);
+
+        let selection_only = Augmentation {
+            is_selection_only: true,
+            ..Default::default()
+        };
+        data.augment(&selection_only, &mut status_msg);
+        assert_eq!(data.points(), preview, "nothing selected");
+        assert!(!status_msg.is_empty());
+    }
+
+    #[test]
+    fn augment_skips_locked_and_hidden_points() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        data.add(Some([1.0, 1.0]), DataLabel::Normal, &mut status_msg);
+        data.add(Some([2.0, 2.0]), DataLabel::Anomaly, &mut status_msg);
+        data.label_state_mut(DataLabel::Anomaly).is_locked = true;
+        let augmentation = Augmentation {
+            copies: 3,
+            ..Default::default()
+        };
+        data.augment(&augmentation, &mut status_msg);
+        assert_eq!(data.points().len(), 2 + 3);
+        let anomalies: Vec<_> = data
+            .points()
+            .iter()
+            .filter(|p| p.label == DataLabel::Anomaly)
+            .collect();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].coords(), vec![2.0, 2.0]);
+
+        data.label_state_mut(DataLabel::Anomaly).is_locked = false;
+        data.label_state_mut(DataLabel::Normal).is_hidden = true;
+        data.select_in_bounds(
+            MinMaxPair {
+                min: [0.5, 0.5],
+                max: [1.5, 1.5],
+            },
+            false,
+        );
+        let before = data.points().to_vec();
+        let selection_only = Augmentation {
+            is_selection_only: true,
+            ..Default::default()
+        };
+        status_msg.clear();
+        data.augment(&selection_only, &mut status_msg);
+        assert_eq!(data.points(), before, "only hidden points selected");
+        assert!(!status_msg.is_empty());
+    }
+
+    #[test]
+    fn time_series_kept_sorted() {
+        let mut data = Data::default();
+        let mut status_msg = StatusMsg::default();
+        for x in [3.0, 1.0, 2.0] {
+            data.add(Some([x, 0.0]), DataLabel::Normal, &mut status_msg);
+        }
+        data.set_time_series(true, &mut status_msg);
+        let times = |data: &Data| data.points().iter().map(|p| p.x).collect::<Vec<_>>();
+        assert_eq!(times(&data), vec![1.0, 2.0, 3.0]);
+
+        data.add(Some([1.5, 0.0]), DataLabel::Normal, &mut status_msg);
+        assert_eq!(times(&data), vec![1.0, 1.5, 2.0, 3.0]);
+
+        data.relabel_time_range(1.2, 2.5, DataLabel::Anomaly, &mut status_msg);
+        let anomalies = data
+            .points()
+            .iter()
+            .filter(|p| p.label.is_anomaly())
+            .count();
+        assert_eq!(anomalies, 2);
+
+        data.undo(&mut status_msg); // relabel
+        data.undo(&mut status_msg); // insert
+        assert_eq!(times(&data), vec![1.0, 2.0, 3.0]);
+        data.undo(&mut status_msg); // enable time series
+        assert!(!data.is_time_series());
+        assert_eq!(times(&data), vec![3.0, 1.0, 2.0]);
+        data.redo(&mut status_msg);
+        assert_eq!(times(&data), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn rounding_migrated_to_snapping() {
+        let mut data = Data {
+            rounding_decimal_places: Some(2),
+            ..Default::default()
+        };
+        data.migrate_rounding_to_snapping();
+        assert_eq!(data.rounding_decimal_places, None);
+        assert!(data.snap_settings.is_enabled);
+        assert_eq!(data.snap_settings.grid.decimal_places(), Some(2));
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt::Display;
+
+use super::{seeded_rng::SeededRng, DataPoint, Points, Projection};
+
+/// Settings for generating new points from existing ones (or moving existing ones)
+///
+/// Applying the same settings with the same seed to the same points always gives the same result
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct Augmentation {
+    /// Which transform is applied, only its settings below are used
+    pub transform: Transform,
+    /// Seed for the random values picked from the ranges below
+    pub seed: u64,
+    /// Only use the selected points as the source instead of all points
+    pub is_selection_only: bool,
+    /// Add the transformed points as copies instead of moving the original points
+    pub should_keep_originals: bool,
+    /// Number of transformed copies of the source points to add when keeping the originals
+    pub copies: usize,
+    /// Standard deviation of the noise added to each dimension
+    pub jitter_std_dev: f64,
+    /// Range that the angle (in degrees) is picked from
+    pub rotation_degrees: [f64; 2],
+    /// Range that the scale factor is picked from
+    pub scale_range: [f64; 2],
+    /// Range that the shear factor (change in x per unit of y) is picked from
+    pub shear_range: [f64; 2],
+    /// Axis to mirror across
+    pub mirror: Mirror,
+    /// Number of points drawn (with replacement) from the source points
+    pub bootstrap_count: usize,
+}
+
+/// How the source points are changed
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum Transform {
+    /// Adds normally distributed noise to every dimension
+    Jitter,
+    /// Rotates around the centre of the source points
+    Rotate,
+    /// Scales around the centre of the source points
+    Scale,
+    /// Shears around the centre of the source points
+    Shear,
+    /// Mirrors across an axis through the centre of the source points
+    Mirror,
+    /// Copies randomly picked source points
+    Bootstrap,
+}
+
+/// Axis that [`Transform::Mirror`] mirrors across
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum Mirror {
+    /// Negate the horizontal distance from the centre
+    FlipX,
+    /// Negate the vertical distance from the centre
+    FlipY,
+}
+
+impl Default for Augmentation {
+    fn default() -> Self {
+        Self {
+            transform: Transform::Jitter,
+            seed: 0,
+            is_selection_only: false,
+            should_keep_originals: true,
+            copies: 1,
+            jitter_std_dev: 0.1,
+            rotation_degrees: [-15.0, 15.0],
+            scale_range: [0.9, 1.1],
+            shear_range: [-0.2, 0.2],
+            mirror: Mirror::FlipX,
+            bootstrap_count: 10,
+        }
+    }
+}
+
+impl Transform {
+    /// All transforms in the order they are listed in the UI
+    pub const ALL: [Transform; 6] = [
+        Self::Jitter,
+        Self::Rotate,
+        Self::Scale,
+        Self::Shear,
+        Self::Mirror,
+        Self::Bootstrap,
+    ];
+
+    /// Name shown in the UI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transform::Jitter => "Jitter",
+            Transform::Rotate => "Rotate",
+            Transform::Scale => "Scale",
+            Transform::Shear => "Shear",
+            Transform::Mirror => "Mirror",
+            Transform::Bootstrap => "Bootstrap",
+        }
+    }
+}
+
+impl Display for Augmentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (seed {})", self.transform.as_str(), self.seed)
+    }
+}
+
+impl Augmentation {
+    /// Returns all points after augmenting the points at `indices`
+    ///
+    /// Rotate, scale, shear and mirror act around the centre of the source points in the plane shown
+    /// by `projection`. New points are copies of their source point including the metadata. When
+    /// keeping the originals the new points are added at the end.
+    pub fn apply(
+        &self,
+        points: &[DataPoint],
+        indices: &[usize],
+        projection: &Projection,
+    ) -> Points {
+        let mut rng = SeededRng::new(self.seed);
+        let mut result = points.to_vec();
+        if indices.is_empty() {
+            return result;
+        }
+
+        if self.transform == Transform::Bootstrap {
+            let sample: Points = (0..self.bootstrap_count)
+                .map(|_| points[indices[rng.below(indices.len())]].clone())
+                .collect();
+            if !self.should_keep_originals {
+                let mut is_source = vec![false; points.len()];
+                indices.iter().for_each(|&i| is_source[i] = true);
+                result = points
+                    .iter()
+                    .zip(is_source)
+                    .filter(|(_, is_source)| !is_source)
+                    .map(|(point, _)| point.clone())
+                    .collect();
+            }
+            result.extend(sample);
+            return result;
+        }
+
+        let centre = centroid(
+            indices
+                .iter()
+                .map(|&i| projection.project(&points[i].coords())),
+        );
+        if self.should_keep_originals {
+            for _ in 0..self.copies {
+                let matrix = self.random_matrix(&mut rng);
+                for &i in indices {
+                    result.push(
+                        self.transform_point(&points[i], matrix, centre, projection, &mut rng),
+                    );
+                }
+            }
+        } else {
+            let matrix = self.random_matrix(&mut rng);
+            for &i in indices {
+                result[i] = self.transform_point(&points[i], matrix, centre, projection, &mut rng);
+            }
+        }
+        result
+    }
+
+    /// Picks the linear map applied to the offset of each point from the centre (not used by jitter)
+    fn random_matrix(&self, rng: &mut SeededRng) -> [[f64; 2]; 2] {
+        match self.transform {
+            Transform::Rotate => {
+                let angle = rng.uniform(self.rotation_degrees).to_radians();
+                let (sin, cos) = angle.sin_cos();
+                [[cos, -sin], [sin, cos]]
+            }
+            Transform::Scale => {
+                let factor = rng.uniform(self.scale_range);
+                [[factor, 0.0], [0.0, factor]]
+            }
+            Transform::Shear => [[1.0, rng.uniform(self.shear_range)], [0.0, 1.0]],
+            Transform::Mirror => match self.mirror {
+                Mirror::FlipX => [[-1.0, 0.0], [0.0, 1.0]],
+                Mirror::FlipY => [[1.0, 0.0], [0.0, -1.0]],
+            },
+            Transform::Jitter | Transform::Bootstrap => [[1.0, 0.0], [0.0, 1.0]],
+        }
+    }
+
+    fn transform_point(
+        &self,
+        point: &DataPoint,
+        matrix: [[f64; 2]; 2],
+        centre: [f64; 2],
+        projection: &Projection,
+        rng: &mut SeededRng,
+    ) -> DataPoint {
+        let mut coords = point.coords();
+        if self.transform == Transform::Jitter {
+            for value in coords.iter_mut() {
+                *value += rng.normal() * self.jitter_std_dev;
+            }
+        } else {
+            let projected = projection.project(&coords);
+            let offset = [projected[0] - centre[0], projected[1] - centre[1]];
+            let moved = [
+                matrix[0][0] * offset[0] + matrix[0][1] * offset[1],
+                matrix[1][0] * offset[0] + matrix[1][1] * offset[1],
+            ];
+            projection.shift(&mut coords, [moved[0] - offset[0], moved[1] - offset[1]]);
+        }
+        let mut result = point.clone();
+        result.set_coords(&coords);
+        result
+    }
+}
+
+fn centroid(points: impl Iterator<Item = [f64; 2]>) -> [f64; 2] {
+    let mut count = 0;
+    let mut sum = [0.0; 2];
+    for point in points {
+        count += 1;
+        sum[0] += point[0];
+        sum[1] += point[1];
+    }
+    if count == 0 {
+        sum
+    } else {
+        sum.map(|total| total / count as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::super::DataLabel;
+    use super::*;
+
+    fn square() -> Points {
+        [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]]
+            .into_iter()
+            .map(|[x, y]| DataPoint::new(vec![x, y], DataLabel::Normal))
+            .collect()
+    }
+
+    fn assert_coords_eq(actual: &[DataPoint], expected: &[[f64; 2]]) {
+        assert_eq!(actual.len(), expected.len());
+        for (point, expected) in actual.iter().zip(expected) {
+            assert!(
+                (point.x - expected[0]).abs() < 1e-9 && (point.y - expected[1]).abs() < 1e-9,
+                "{point} vs {expected:?}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(Transform::Rotate, [[2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]])]
+    #[case(Transform::Scale, [[-1.0, -1.0], [3.0, -1.0], [3.0, 3.0], [-1.0, 3.0]])]
+    #[case(Transform::Shear, [[-0.5, 0.0], [1.5, 0.0], [2.5, 2.0], [0.5, 2.0]])]
+    #[case(Transform::Mirror, [[0.0, 2.0], [2.0, 2.0], [2.0, 0.0], [0.0, 0.0]])]
+    fn geometric_transforms_in_place(
+        #[case] transform: Transform,
+        #[case] expected: [[f64; 2]; 4],
+    ) {
+        let augmentation = Augmentation {
+            transform,
+            should_keep_originals: false,
+            rotation_degrees: [90.0, 90.0],
+            scale_range: [2.0, 2.0],
+            shear_range: [0.5, 0.5],
+            mirror: Mirror::FlipY,
+            ..Default::default()
+        };
+        let result = augmentation.apply(&square(), &[0, 1, 2, 3], &Projection::default());
+        assert_coords_eq(&result, &expected);
+    }
+
+    #[test]
+    fn copies_added_after_originals() {
+        let augmentation = Augmentation {
+            transform: Transform::Mirror,
+            copies: 2,
+            ..Default::default()
+        };
+        let points = square();
+        let result = augmentation.apply(&points, &[1, 2], &Projection::default());
+        assert_eq!(result.len(), 4 + 2 * 2);
+        assert_eq!(result[..4], points[..]);
+        assert_coords_eq(&result[4..6], &[[2.0, 0.0], [2.0, 2.0]]); // On the mirror line
+    }
+
+    #[test]
+    fn same_seed_same_result() {
+        let mut augmentation = Augmentation::default();
+        let points = square();
+        let first = augmentation.apply(&points, &[0, 1, 2, 3], &Projection::default());
+        let second = augmentation.apply(&points, &[0, 1, 2, 3], &Projection::default());
+        assert_eq!(first, second);
+        assert_ne!(first[4..], points[..]);
+        augmentation.seed += 1;
+        let third = augmentation.apply(&points, &[0, 1, 2, 3], &Projection::default());
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn bootstrap_replaces_only_sources() {
+        let augmentation = Augmentation {
+            transform: Transform::Bootstrap,
+            should_keep_originals: false,
+            bootstrap_count: 5,
+            ..Default::default()
+        };
+        let points = square();
+        let result = augmentation.apply(&points, &[2, 3], &Projection::default());
+        assert_eq!(result.len(), 2 + 5);
+        assert_eq!(result[..2], points[..2]);
+        assert!(result[2..]
+            .iter()
+            .all(|p| *p == points[2] || *p == points[3]));
+    }
+}
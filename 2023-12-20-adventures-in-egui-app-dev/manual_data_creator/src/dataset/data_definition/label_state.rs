@@ -0,0 +1,49 @@
+use super::DataLabel;
+
+/// How the points of one label can be seen and edited
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct LabelState {
+    /// Points are left off the plot and ignored when looking for the point closest to the pointer
+    pub is_hidden: bool,
+    /// Points cannot be added, deleted or have their label changed
+    pub is_locked: bool,
+}
+
+/// The [`LabelState`] of each label
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct LabelStates {
+    normal: LabelState,
+    anomaly: LabelState,
+}
+
+impl LabelStates {
+    /// The state of the points with `label`
+    pub fn get(&self, label: DataLabel) -> LabelState {
+        match label {
+            DataLabel::Normal => self.normal,
+            DataLabel::Anomaly => self.anomaly,
+        }
+    }
+
+    /// See [`Self::get`]
+    pub fn get_mut(&mut self, label: DataLabel) -> &mut LabelState {
+        match label {
+            DataLabel::Normal => &mut self.normal,
+            DataLabel::Anomaly => &mut self.anomaly,
+        }
+    }
+
+    /// Returns why points with `label` cannot be changed or `None` if they can
+    pub fn edit_blocker(&self, label: DataLabel) -> Option<&'static str> {
+        let state = self.get(label);
+        if state.is_locked {
+            Some("locked")
+        } else if state.is_hidden {
+            Some("hidden")
+        } else {
+            None
+        }
+    }
+}
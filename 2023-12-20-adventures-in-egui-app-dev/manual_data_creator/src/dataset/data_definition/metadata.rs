@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use super::Split;
+
+/// Optional extra information stored with each point
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct PointMetadata {
+    /// Free text entered by the user
+    pub note: String,
+    /// Importance of the point, for example as a sample weight when training
+    pub weight: f64,
+    /// Group or cluster that the point belongs to
+    pub group: Option<u32>,
+    /// Seconds since the unix epoch (UTC) when the point was created, `None` if not known
+    pub created: Option<f64>,
+    /// Subset of the data the point is used for, `None` if not assigned
+    pub split: Option<Split>,
+}
+
+impl Default for PointMetadata {
+    fn default() -> Self {
+        Self {
+            note: Default::default(),
+            weight: Self::DEFAULT_WEIGHT,
+            group: None,
+            created: None,
+            split: None,
+        }
+    }
+}
+
+impl PointMetadata {
+    /// Weight of points that were not given one
+    pub const DEFAULT_WEIGHT: f64 = 1.0;
+
+    /// Metadata for a point being created now
+    pub fn new_now() -> Self {
+        Self {
+            created: now_timestamp(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Display for PointMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Weight: {}", self.weight)?;
+        if let Some(group) = self.group {
+            writeln!(f, "Group: {group}")?;
+        }
+        if let Some(split) = self.split {
+            writeln!(f, "Split: {}", split.as_str())?;
+        }
+        if let Some(created) = self.created {
+            writeln!(f, "Created: {}", format_timestamp(created))?;
+        }
+        if !self.note.is_empty() {
+            writeln!(f, "Note: {}", self.note)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_timestamp() -> Option<f64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs_f64())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_timestamp() -> Option<f64> {
+    // SystemTime is not available on the web
+    Some(js_sys::Date::now() / 1000.0)
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_timestamp(timestamp: f64) -> String {
+    let total_seconds = timestamp.floor() as i64;
+    let days = total_seconds.div_euclid(86_400);
+    let seconds_of_day = total_seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Converts days since 1970-01-01 into (year, month, day)
+///
+/// Based on <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_portion = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_portion + 2) / 5 + 1) as u32;
+    let month = if month_portion < 10 {
+        month_portion + 3
+    } else {
+        month_portion - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0.0, "1970-01-01 00:00:00 UTC")]
+    #[case(951_782_400.0, "2000-02-29 00:00:00 UTC")]
+    #[case(1_703_116_800.5, "2023-12-21 00:00:00 UTC")]
+    #[case(1_703_102_399.0, "2023-12-20 19:59:59 UTC")]
+    fn timestamp_formatting(#[case] timestamp: f64, #[case] expected: &str) {
+        assert_eq!(format_timestamp(timestamp), expected);
+    }
+}
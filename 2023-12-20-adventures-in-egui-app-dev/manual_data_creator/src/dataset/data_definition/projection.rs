@@ -0,0 +1,242 @@
+/// Controls how points with more than two dimensions are shown on the 2D plot
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+pub enum Projection {
+    /// Shows the dimensions at these indices as x and y
+    Axes([usize; 2]),
+    /// Shows the first two principal components
+    Pca(PcaBasis),
+}
+
+/// The basis is stored instead of being recalculated on each change so the view does not move while editing
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+pub struct PcaBasis {
+    /// Mean of the points the basis was calculated from, subtracted before projecting
+    pub mean: Vec<f64>,
+    /// Unit length principal components, most variance first
+    pub components: [Vec<f64>; 2],
+    /// Fraction of the total variance explained by each component
+    pub explained_variance: [f64; 2],
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Self::Axes([0, 1])
+    }
+}
+
+impl Projection {
+    /// Converts the full coordinates of a point into the 2D coordinates shown
+    pub fn project(&self, coords: &[f64]) -> [f64; 2] {
+        match self {
+            Projection::Axes([x, y]) => [
+                coords.get(*x).copied().unwrap_or_default(),
+                coords.get(*y).copied().unwrap_or_default(),
+            ],
+            Projection::Pca(basis) => basis.project(coords),
+        }
+    }
+
+    /// Converts a 2D coordinate back into full coordinates, using `defaults` for any values not determined by the projection
+    pub fn unproject(&self, point: [f64; 2], defaults: &[f64]) -> Vec<f64> {
+        match self {
+            Projection::Axes([x, y]) => {
+                let mut result = defaults.to_vec();
+                result[*x] = point[0];
+                result[*y] = point[1];
+                result
+            }
+            Projection::Pca(basis) => basis.unproject(point),
+        }
+    }
+
+    /// Moves `coords` so that the projected point moves by `delta`, dimensions not shown are left unchanged
+    pub fn shift(&self, coords: &mut [f64], delta: [f64; 2]) {
+        match self {
+            Projection::Axes([x, y]) => {
+                coords[*x] += delta[0];
+                if x != y {
+                    coords[*y] += delta[1];
+                }
+            }
+            Projection::Pca(basis) => {
+                for (i, value) in coords.iter_mut().enumerate() {
+                    *value += delta[0] * basis.components[0][i] + delta[1] * basis.components[1][i];
+                }
+            }
+        }
+    }
+
+    /// Returns true if the projection can be used with points of `dimensions` dimensions
+    pub fn is_valid_for(&self, dimensions: usize) -> bool {
+        match self {
+            Projection::Axes([x, y]) => *x < dimensions && *y < dimensions,
+            Projection::Pca(basis) => basis.mean.len() == dimensions,
+        }
+    }
+}
+
+impl PcaBasis {
+    /// Iterations used to find each eigenvector
+    const POWER_ITERATIONS: usize = 500;
+
+    /// Calculates the basis from the points (each a list of coordinates of length `dimensions`)
+    ///
+    /// Returns `None` if there are not enough points to calculate the components
+    pub fn calculate(points: &[Vec<f64>], dimensions: usize) -> Option<Self> {
+        if points.len() < 2 || dimensions < 2 {
+            return None;
+        }
+        let count = points.len() as f64;
+        let mut mean = vec![0.0; dimensions];
+        for point in points {
+            for (sum, value) in mean.iter_mut().zip(point) {
+                *sum += value;
+            }
+        }
+        mean.iter_mut().for_each(|sum| *sum /= count);
+
+        let mut covariance = vec![vec![0.0; dimensions]; dimensions];
+        for point in points {
+            for i in 0..dimensions {
+                for j in 0..dimensions {
+                    covariance[i][j] += (point[i] - mean[i]) * (point[j] - mean[j]);
+                }
+            }
+        }
+        covariance
+            .iter_mut()
+            .flatten()
+            .for_each(|value| *value /= count - 1.0);
+        let total_variance: f64 = (0..dimensions).map(|i| covariance[i][i]).sum();
+        if total_variance <= 0.0 {
+            return None;
+        }
+
+        let (first, first_value) = dominant_eigenvector(&covariance, 0);
+        // Remove the first component so the second most dominant one can be found
+        for i in 0..dimensions {
+            for j in 0..dimensions {
+                covariance[i][j] -= first_value * first[i] * first[j];
+            }
+        }
+        let (second, second_value) = dominant_eigenvector(&covariance, 1);
+
+        Some(Self {
+            mean,
+            components: [first, second],
+            explained_variance: [
+                first_value / total_variance,
+                second_value.max(0.0) / total_variance,
+            ],
+        })
+    }
+
+    fn project(&self, coords: &[f64]) -> [f64; 2] {
+        self.components.each_ref().map(|component| {
+            component
+                .iter()
+                .zip(coords.iter().zip(&self.mean))
+                .map(|(c, (value, mean))| c * (value - mean))
+                .sum()
+        })
+    }
+
+    fn unproject(&self, point: [f64; 2]) -> Vec<f64> {
+        self.mean
+            .iter()
+            .enumerate()
+            .map(|(i, mean)| {
+                mean + point[0] * self.components[0][i] + point[1] * self.components[1][i]
+            })
+            .collect()
+    }
+}
+
+/// Finds the eigenvector with the largest eigenvalue by power iteration
+///
+/// `seed_axis` selects the starting vector to avoid starting orthogonal to the answer in simple cases
+fn dominant_eigenvector(matrix: &[Vec<f64>], seed_axis: usize) -> (Vec<f64>, f64) {
+    let dimensions = matrix.len();
+    let mut vector: Vec<f64> = (0..dimensions)
+        .map(|i| if i == seed_axis { 1.0 } else { 0.5 })
+        .collect();
+    normalize(&mut vector);
+    let mut eigenvalue = 0.0;
+    for _ in 0..PcaBasis::POWER_ITERATIONS {
+        let mut next: Vec<f64> = matrix
+            .iter()
+            .map(|row| row.iter().zip(&vector).map(|(a, b)| a * b).sum())
+            .collect();
+        eigenvalue = next.iter().zip(&vector).map(|(a, b)| a * b).sum();
+        if !normalize(&mut next) {
+            break; // Matrix maps the vector to zero, no more variance to find
+        }
+        vector = next;
+    }
+    (vector, eigenvalue)
+}
+
+/// Scales `vector` to unit length, returns false if it has no length
+fn normalize(vector: &mut [f64]) -> bool {
+    let length = vector.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if length <= f64::EPSILON {
+        return false;
+    }
+    vector.iter_mut().for_each(|v| *v /= length);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_round_trip() {
+        let projection = Projection::Axes([2, 0]);
+        let coords = vec![1.0, 2.0, 3.0];
+        assert_eq!(projection.project(&coords), [3.0, 1.0]);
+        assert_eq!(
+            projection.unproject([5.0, 6.0], &[0.0, 7.0, 0.0]),
+            vec![6.0, 7.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn pca_finds_main_direction() {
+        // Points spread along (1, 1, 0) with a little noise in z (not correlated with the spread)
+        let noise = [0.1, -0.1, -0.1, 0.1];
+        let points: Vec<Vec<f64>> = (0..8)
+            .map(|i| {
+                let t = i as f64;
+                vec![t, t, noise[i % noise.len()]]
+            })
+            .collect();
+        let basis = PcaBasis::calculate(&points, 3).expect("enough points");
+        let first = &basis.components[0];
+        let expected = 1.0 / 2f64.sqrt();
+        assert!((first[0].abs() - expected).abs() < 1e-6, "{first:?}");
+        assert!((first[1].abs() - expected).abs() < 1e-6, "{first:?}");
+        assert!(first[2].abs() < 1e-6, "{first:?}");
+        assert!(basis.explained_variance[0] > 0.99);
+
+        // Points on the plane of the two components survive a round trip
+        let projection = Projection::Pca(basis);
+        let projected = projection.project(&points[3]);
+        let restored = projection.unproject(projected, &[]);
+        for (a, b) in restored.iter().zip(&points[3]) {
+            assert!((a - b).abs() < 1e-6, "{restored:?} vs {:?}", points[3]);
+        }
+
+        // Shifting moves the projection by the same amount
+        let mut shifted = points[3].clone();
+        projection.shift(&mut shifted, [1.0, -0.5]);
+        let moved = projection.project(&shifted);
+        assert!((moved[0] - projected[0] - 1.0).abs() < 1e-6, "{moved:?}");
+        assert!((moved[1] - projected[1] + 0.5).abs() < 1e-6, "{moved:?}");
+    }
+
+    #[test]
+    fn pca_needs_points() {
+        assert_eq!(PcaBasis::calculate(&[vec![1.0, 2.0]], 2), None);
+    }
+}
@@ -0,0 +1,78 @@
+use std::f64::consts::TAU;
+
+/// Small seeded random number generator so results only depend on the seed (and not on a
+/// dependency's version or the platform)
+///
+/// Based on <https://prng.di.unimi.it/splitmix64.c>
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Generator that always produces the same numbers for the same `seed`
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform between the two values of `range` (in either order)
+    pub fn uniform(&mut self, range: [f64; 2]) -> f64 {
+        range[0] + (range[1] - range[0]) * self.next_f64()
+    }
+
+    /// Uniform in `0..n`, `n` must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize
+    }
+
+    /// Puts `values` in a random order (Fisher-Yates)
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// Standard normal distribution using the Box-Muller transform
+    pub fn normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64(); // Avoid 0 as ln(0) is infinite
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_distribution_moments() {
+        let mut rng = SeededRng::new(7);
+        let samples: Vec<f64> = (0..10_000).map(|_| rng.normal()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05, "mean: {mean}");
+        assert!((variance - 1.0).abs() < 0.05, "variance: {variance}");
+    }
+
+    #[test]
+    fn shuffle_is_permutation() {
+        let mut values: Vec<usize> = (0..20).collect();
+        SeededRng::new(3).shuffle(&mut values);
+        assert_ne!(values, (0..20).collect::<Vec<_>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,172 @@
+use super::{seeded_rng::SeededRng, DataLabel};
+
+/// The subset of the data a point is used for when training a model
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Split {
+    /// Used to fit the model
+    Train,
+    /// Used to tune the model while fitting
+    Validation,
+    /// Held back to evaluate the final model
+    Test,
+}
+
+/// Settings for randomly assigning points to splits
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct SplitSettings {
+    /// Relative size of each split in the order of [`Split::ALL`], does not need to add up to 1
+    pub fractions: [f64; 3],
+    /// Seed for the random assignment, the same seed and points always give the same splits
+    pub seed: u64,
+    /// Split each label separately so each split gets the same ratio of labels as the whole dataset
+    pub is_stratified: bool,
+}
+
+impl Default for SplitSettings {
+    fn default() -> Self {
+        Self {
+            fractions: [0.7, 0.15, 0.15],
+            seed: 0,
+            is_stratified: true,
+        }
+    }
+}
+
+impl Split {
+    /// All splits in the order they are listed in the UI
+    pub const ALL: [Split; 3] = [Self::Train, Self::Validation, Self::Test];
+
+    /// Name shown in the UI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Split::Train => "Train",
+            Split::Validation => "Validation",
+            Split::Test => "Test",
+        }
+    }
+
+    /// Lower case name used in file names
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            Split::Train => "train",
+            Split::Validation => "validation",
+            Split::Test => "test",
+        }
+    }
+}
+
+impl SplitSettings {
+    /// Returns the split for each point given the point's label
+    ///
+    /// Returns `None` if the fractions do not add up to a positive number
+    pub fn assign(&self, labels: &[DataLabel]) -> Option<Vec<Split>> {
+        let total: f64 = self.fractions.iter().map(|f| f.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut rng = SeededRng::new(self.seed);
+        let groups: Vec<Vec<usize>> = if self.is_stratified {
+            [DataLabel::Normal, DataLabel::Anomaly]
+                .iter()
+                .map(|label| (0..labels.len()).filter(|&i| labels[i] == *label).collect())
+                .collect()
+        } else {
+            vec![(0..labels.len()).collect()]
+        };
+
+        let mut result = vec![Split::Train; labels.len()];
+        for mut group in groups {
+            rng.shuffle(&mut group);
+            let counts = self.split_sizes(group.len(), total);
+            let mut remaining = group.into_iter();
+            for (split, count) in Split::ALL.into_iter().zip(counts) {
+                for index in remaining.by_ref().take(count) {
+                    result[index] = split;
+                }
+            }
+        }
+        Some(result)
+    }
+
+    /// Divides `count` items between the splits, rounding so the sizes always add up to `count`
+    fn split_sizes(&self, count: usize, total: f64) -> [usize; 3] {
+        let mut result = [0; 3];
+        let mut cumulative = 0.0;
+        let mut assigned = 0;
+        for (size, fraction) in result.iter_mut().zip(self.fractions) {
+            cumulative += fraction.max(0.0) / total;
+            let end = (cumulative * count as f64).round() as usize;
+            *size = end.min(count) - assigned;
+            assigned += *size;
+        }
+        result[2] += count - assigned; // Any left over due to rounding
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case([0.7, 0.15, 0.15], 20, [14, 3, 3])]
+    #[case([1.0, 1.0, 1.0], 10, [3, 4, 3])]
+    #[case([1.0, 0.0, 0.0], 7, [7, 0, 0])]
+    #[case([0.5, 0.5, 0.0], 0, [0, 0, 0])]
+    fn sizes_add_up(
+        #[case] fractions: [f64; 3],
+        #[case] count: usize,
+        #[case] expected: [usize; 3],
+    ) {
+        let settings = SplitSettings {
+            fractions,
+            ..Default::default()
+        };
+        let total = fractions.iter().sum();
+        assert_eq!(settings.split_sizes(count, total), expected);
+    }
+
+    #[test]
+    fn stratified_keeps_label_ratio() {
+        let labels: Vec<DataLabel> = (0..40)
+            .map(|i| {
+                if i % 4 == 0 {
+                    DataLabel::Anomaly
+                } else {
+                    DataLabel::Normal
+                }
+            })
+            .collect();
+        let settings = SplitSettings {
+            fractions: [0.6, 0.2, 0.2],
+            ..Default::default()
+        };
+        let splits = settings.assign(&labels).expect("valid fractions");
+        let count = |split: Split, label: DataLabel| {
+            (0..labels.len())
+                .filter(|&i| splits[i] == split && labels[i] == label)
+                .count()
+        };
+        assert_eq!(count(Split::Train, DataLabel::Anomaly), 6);
+        assert_eq!(count(Split::Test, DataLabel::Anomaly), 2);
+        assert_eq!(count(Split::Train, DataLabel::Normal), 18);
+        assert_eq!(count(Split::Validation, DataLabel::Normal), 6);
+        assert_eq!(
+            settings.assign(&labels),
+            Some(splits),
+            "same seed same result"
+        );
+    }
+
+    #[test]
+    fn invalid_fractions() {
+        let settings = SplitSettings {
+            fractions: [0.0, 0.0, -1.0],
+            ..Default::default()
+        };
+        assert_eq!(settings.assign(&[DataLabel::Normal]), None);
+    }
+}
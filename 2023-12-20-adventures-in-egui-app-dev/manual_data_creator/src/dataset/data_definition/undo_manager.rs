@@ -0,0 +1,389 @@
+use std::fmt::Display;
+
+use self::{dequeue::Deque, stack::Stack};
+
+use super::{DataLabel, DataPoint, PointMetadata, Points};
+
+mod dequeue;
+mod stack;
+
+/// Changes made to [`super::Data`] that can be undone and redone, oldest changes are dropped once the maximum history size is reached
+#[derive(serde::Deserialize, serde::Serialize, PartialEq)]
+pub struct UndoManager {
+    max_history_size: Option<u16>,
+    undo_events: Deque<Event>,
+    redo_events: Stack<Event>,
+    /// Counts every change made, undone or redone since starting, not saved
+    #[serde(skip)]
+    change_count: u64,
+}
+
+impl Default for UndoManager {
+    fn default() -> Self {
+        Self {
+            max_history_size: Self::default_max_history(),
+            undo_events: Default::default(),
+            redo_events: Default::default(),
+            change_count: 0,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub enum Event {
+    Add(AddEventData),
+    Delete(DeleteEventData),
+    Erase(EraseEventData),
+    Clear(ClearEventData),
+    EditMetadata(EditMetadataEventData),
+    Insert(InsertEventData),
+    Relabel(RelabelEventData),
+    SetTimeSeries(SetTimeSeriesEventData),
+    Augment(AugmentEventData),
+    Import(ImportEventData),
+    Script(ScriptEventData),
+    Merge(MergeEventData),
+    SetDimensions(SetDimensionsEventData),
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct AddEventData {
+    pub point: DataPoint,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct DeleteEventData {
+    pub index: usize,
+    pub point: DataPoint,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct EraseEventData {
+    /// Removed points with the index they had before the first of them was removed, sorted by index
+    pub removed: Vec<(usize, DataPoint)>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct ClearEventData {
+    pub points: Points,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct EditMetadataEventData {
+    pub changes: Vec<MetadataEdit>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct MetadataEdit {
+    pub index: usize,
+    pub before: PointMetadata,
+    pub after: PointMetadata,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct InsertEventData {
+    pub index: usize,
+    pub point: DataPoint,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct RelabelEventData {
+    pub changes: Vec<LabelChange>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct LabelChange {
+    pub index: usize,
+    pub before: DataLabel,
+    pub after: DataLabel,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct SetTimeSeriesEventData {
+    pub is_enabled: bool,
+    /// When enabling, the points get sorted. For each position after sorting this holds the index the point had before sorting
+    pub previous_order: Option<Vec<usize>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct AugmentEventData {
+    /// Describes the augmentation applied
+    pub description: String,
+    pub before: Points,
+    pub after: Points,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct ImportEventData {
+    /// Where the points came from, usually a file name
+    pub source: String,
+    pub before: Points,
+    pub after: Points,
+    /// Number of dimensions before the import, `None` in history saved before imports could change it
+    #[serde(default)]
+    pub dimensions_before: Option<usize>,
+    #[serde(default)]
+    pub dimensions_after: Option<usize>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct ScriptEventData {
+    pub before: Points,
+    pub after: Points,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Add(data) => data.fmt(f),
+            Event::Delete(data) => data.fmt(f),
+            Event::Erase(data) => data.fmt(f),
+            Event::Clear(data) => data.fmt(f),
+            Event::EditMetadata(data) => data.fmt(f),
+            Event::Insert(data) => data.fmt(f),
+            Event::Relabel(data) => data.fmt(f),
+            Event::SetTimeSeries(data) => data.fmt(f),
+            Event::Augment(data) => data.fmt(f),
+            Event::Import(data) => data.fmt(f),
+            Event::Script(data) => data.fmt(f),
+            Event::Merge(data) => data.fmt(f),
+            Event::SetDimensions(data) => data.fmt(f),
+        }
+    }
+}
+
+impl Display for AddEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Add Point: {}", self.point)
+    }
+}
+
+impl Display for DeleteEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Delete Point: {} at index: {}", self.point, self.index)
+    }
+}
+
+impl Display for EraseEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Erase {} point(s)", self.removed.len())
+    }
+}
+
+impl Display for ClearEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Clear of Points")
+    }
+}
+
+impl Display for EditMetadataEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Edit Metadata of {} point(s)", self.changes.len())
+    }
+}
+
+impl Display for InsertEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Insert Point: {} at index: {}", self.point, self.index)
+    }
+}
+
+impl Display for RelabelEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.changes.as_slice() {
+            [change] => write!(
+                f,
+                "Relabel Point at index: {} from {} to {}",
+                change.index,
+                change.before.as_str(),
+                change.after.as_str()
+            ),
+            changes => write!(f, "Relabel {} point(s)", changes.len()),
+        }
+    }
+}
+
+impl Display for SetTimeSeriesEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_enabled {
+            write!(f, "Enable Time Series Mode")
+        } else {
+            write!(f, "Disable Time Series Mode")
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct MergeEventData {
+    /// Describes the differences accepted
+    pub description: String,
+    /// Points removed or changed with their index before the merge, sorted by index
+    pub removed: Vec<(usize, DataPoint)>,
+    /// Points added or changed with their index after the merge, sorted by index
+    pub added: Vec<(usize, DataPoint)>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct SetDimensionsEventData {
+    pub dimensions_before: usize,
+    pub dimensions_after: usize,
+    /// Kept whole because lowering the number of dimensions drops the values of the removed dimensions
+    pub before: Points,
+    pub after: Points,
+}
+
+impl Display for AugmentEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Augment: {} ({} to {} points)",
+            self.description,
+            self.before.len(),
+            self.after.len()
+        )
+    }
+}
+
+impl UndoManager {
+    const DEFAULT_MAX_HISTORY: u16 = 200;
+
+    /// Most changes kept for undoing, `None` if unlimited
+    pub fn max_history_size(&self) -> Option<u16> {
+        self.max_history_size
+    }
+
+    /// Changes the limit on the number of changes kept, dropping the oldest ones if there are too many
+    pub fn set_max_history_size(&mut self, value: Option<u16>) {
+        self.max_history_size = value;
+        if let Some(max_size) = self.max_history_size {
+            while self.undo_events.len() > max_size as usize {
+                self.undo_events.remove_oldest();
+            }
+        }
+    }
+
+    /// Forgets all changes that could be undone or redone
+    pub fn clear_all(&mut self) {
+        self.undo_events.clear();
+        self.redo_events.clear();
+    }
+
+    /// Returns `true` if there is nothing to undo
+    pub fn is_undo_empty(&self) -> bool {
+        self.undo_events.is_empty()
+    }
+
+    /// Returns `true` if there is nothing to redo
+    pub fn is_redo_empty(&self) -> bool {
+        self.redo_events.is_empty()
+    }
+
+    /// Returns `true` if there is nothing to undo or redo
+    pub fn is_empty(&self) -> bool {
+        self.is_undo_empty() && self.is_redo_empty()
+    }
+
+    /// Descriptions of the changes that can be undone, most recent last
+    pub fn undo_descriptions(&self) -> Vec<String> {
+        self.undo_events.iter().map(ToString::to_string).collect()
+    }
+
+    /// Increases every time a change is recorded, undone or redone, so it can be used to notice that the points changed
+    pub fn change_count(&self) -> u64 {
+        self.change_count
+    }
+
+    pub(crate) fn add_undo(&mut self, event: Event) {
+        self.change_count += 1;
+        self.redo_events.clear();
+        self.undo_events.push(event);
+        if let Some(max_size) = self.max_history_size {
+            if self.undo_events.len() > max_size as usize {
+                self.undo_events.remove_oldest();
+            }
+            debug_assert!(
+                self.undo_events.len() <= max_size as usize,
+                "at this point it should be withing the limit"
+            );
+        }
+    }
+
+    /// The most recent change that can be undone, used to extend it instead of recording another one
+    ///
+    /// Counts as a change since the caller is expected to extend the event
+    pub(crate) fn last_undo_mut(&mut self) -> Option<&mut Event> {
+        self.change_count += 1;
+        self.redo_events.clear();
+        self.undo_events.peek()
+    }
+
+    /// Moves the most recent item into redo and returns a reference to it
+    ///
+    /// PANICS: Panics if there is nothing to undo
+    pub(crate) fn undo(&mut self) -> &mut Event {
+        self.change_count += 1;
+        let event = self
+            .undo_events
+            .pop()
+            .expect("should not be empty if called");
+        self.redo_events.push(event);
+        self.redo_events
+            .peek()
+            .unwrap_or_else(|| panic!("should not be empty we just put an item into it"))
+    }
+
+    /// Moves the most recent item into undo and returns a reference to it
+    ///
+    /// PANICS: Panics if there is nothing to redo
+    pub(crate) fn redo(&mut self) -> &mut Event {
+        self.change_count += 1;
+        let event = self
+            .redo_events
+            .pop()
+            .expect("should not be empty if called");
+        self.undo_events.push(event);
+        self.undo_events
+            .peek()
+            .unwrap_or_else(|| panic!("should not be empty we just put an item into it"))
+    }
+
+    /// It is assumed in the UI that this is Some and not None
+    pub fn default_max_history() -> Option<u16> {
+        let result = Some(Self::DEFAULT_MAX_HISTORY);
+        debug_assert!(result.is_some());
+        result
+    }
+}
+
+impl Display for ImportEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Import {} points from {}", self.after.len(), self.source)
+    }
+}
+
+impl Display for ScriptEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Script ({} to {} points)",
+            self.before.len(),
+            self.after.len()
+        )
+    }
+}
+
+impl Display for MergeEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Merge: {}", self.description)
+    }
+}
+
+impl Display for SetDimensionsEventData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Change Number of Dimensions from {} to {}",
+            self.dimensions_before, self.dimensions_after
+        )
+    }
+}
@@ -41,4 +41,9 @@ impl<T> Deque<T> {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+
+    /// Iterates from the oldest to the most recent value
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
 }
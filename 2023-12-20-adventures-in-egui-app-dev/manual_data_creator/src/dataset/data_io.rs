@@ -0,0 +1,236 @@
+//! Reading and writing data from / to files
+
+use std::fmt::Display;
+
+pub use self::csv::{NumericTable, ParseError};
+pub use self::npy::{
+    read_features_and_labels as read_npy, write_features_and_labels as write_npy,
+    FEATURES_FILE_NAME as NPY_FEATURES_FILE_NAME, LABELS_FILE_NAME as NPY_LABELS_FILE_NAME,
+};
+use super::data_definition::{DataLabel, DataPoint, Split};
+
+mod arff;
+mod csv;
+mod json;
+mod libsvm;
+mod mat;
+mod npy;
+pub mod share_link;
+
+/// Largest size compressed contents (`.npz` entries and MAT-file variables) may have once decompressed, so a small file
+/// cannot use up all memory
+const MAX_DECOMPRESSED_SIZE: usize = 1 << 30;
+
+/// The file formats that points can be exported to
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum FileFormat {
+    /// Comma separated values with a header row
+    Csv,
+    /// The points as serialized by serde
+    Json,
+    /// NumPy archive holding `X.npy` and `y.npy`
+    Npz,
+    /// Weka's attribute-relation file format
+    Arff,
+    /// Sparse `label index:value` lines as used by LIBSVM
+    Libsvm,
+}
+
+impl FileFormat {
+    /// All formats in the order they are listed in the UI
+    pub const ALL: [FileFormat; 5] = [Self::Csv, Self::Json, Self::Npz, Self::Arff, Self::Libsvm];
+
+    /// The format a file can be written back in, found like in [`read_file`]. `None` for formats that are only read
+    pub fn from_file_name(file_name: &str, bytes: &[u8]) -> Option<Self> {
+        match detect_extension(file_name, bytes)? {
+            "svm" => Some(Self::Libsvm),
+            extension => Self::ALL
+                .into_iter()
+                .find(|format| format.extension() == extension),
+        }
+    }
+
+    /// File extension (without the dot) used when saving in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Json => "json",
+            FileFormat::Npz => "npz",
+            FileFormat::Arff => "arff",
+            FileFormat::Libsvm => "libsvm",
+        }
+    }
+
+    /// Converts the points into the contents of a file of this format
+    pub fn write(&self, points: &[DataPoint], dimensions: usize) -> Result<Vec<u8>, String> {
+        Ok(match self {
+            FileFormat::Csv => csv::write_points(points, dimensions).into_bytes(),
+            FileFormat::Json => json::write_points(points, dimensions)?.into_bytes(),
+            FileFormat::Npz => npy::write_npz(points, dimensions)?,
+            FileFormat::Arff => arff::write_points(points, dimensions).into_bytes(),
+            FileFormat::Libsvm => libsvm::write_points(points, dimensions).into_bytes(),
+        })
+    }
+
+    /// Returns the contents of one file per split (that has points) with the file name suffix to use for it
+    ///
+    /// Points without a split are not included
+    pub fn write_splits(
+        &self,
+        points: &[DataPoint],
+        dimensions: usize,
+    ) -> Result<Vec<(&'static str, Vec<u8>)>, String> {
+        let mut result = vec![];
+        for split in Split::ALL {
+            let split_points: Vec<DataPoint> = points
+                .iter()
+                .filter(|p| p.meta.split == Some(split))
+                .cloned()
+                .collect();
+            if !split_points.is_empty() {
+                result.push((split.file_suffix(), self.write(&split_points, dimensions)?));
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Label names accepted when reading (compared ignoring case)
+fn parse_label(value: &str) -> Option<DataLabel> {
+    match value.to_ascii_lowercase().as_str() {
+        "normal" | "0" => Some(DataLabel::Normal),
+        "anomaly" | "1" => Some(DataLabel::Anomaly),
+        _ => None,
+    }
+}
+
+/// Extensions of the files that [`read_file`] can read
+pub const READABLE_EXTENSIONS: [&str; 7] = ["csv", "json", "npz", "mat", "arff", "libsvm", "svm"];
+
+/// Returns the extension of the format of a file, from its name if it has a known extension otherwise from its contents
+///
+/// `npy` is returned for NumPy arrays, they need to be read in pairs with [`read_npy`]
+pub fn detect_extension(file_name: &str, bytes: &[u8]) -> Option<&'static str> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    if let Some(known) = READABLE_EXTENSIONS
+        .into_iter()
+        .chain(["npy"])
+        .find(|known| *known == extension)
+    {
+        return Some(known);
+    }
+    if bytes.starts_with(npy::MAGIC) {
+        return Some("npy");
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Some("npz");
+    }
+    if bytes.starts_with(mat::MAGIC) {
+        return Some("mat");
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with(['%', '#']))?;
+    if first_line.starts_with('{') {
+        Some("json")
+    } else if first_line.to_ascii_lowercase().starts_with("@relation") {
+        Some("arff")
+    } else if first_line.contains(',') {
+        Some("csv")
+    } else if first_line.contains(':') {
+        Some("libsvm")
+    } else {
+        None
+    }
+}
+
+/// Reads points from the contents of a file, the format is picked by [`detect_extension`]
+///
+/// Returns the points and their number of dimensions or a list of the problems found
+pub fn read_file(file_name: &str, bytes: &[u8]) -> Result<(Vec<DataPoint>, usize), Vec<String>> {
+    let text = || {
+        std::str::from_utf8(bytes).map_err(|e| vec![format!("File is not valid UTF-8 text: {e}")])
+    };
+    let line_errors = |errors: Vec<ParseError>| errors.iter().map(ToString::to_string).collect();
+    match detect_extension(file_name, bytes) {
+        Some("csv") => csv::read_points(text()?).map_err(line_errors),
+        Some("json") => json::read_points(bytes).map_err(|e| vec![e]),
+        Some("npz") => npy::read_npz(bytes).map_err(|e| vec![e]),
+        Some("mat") => mat::read_points(bytes).map_err(|e| vec![e]),
+        Some("arff") => arff::read_points(text()?).map_err(line_errors),
+        Some("libsvm" | "svm") => libsvm::read_points(text()?).map_err(line_errors),
+        Some("npy") => Err(vec![format!(
+            "{file_name:?} is a NumPy array. Load the features (X.npy) and labels (y.npy) together"
+        )]),
+        _ => Err(vec![format!(
+            "Unable to read {file_name:?}. Supported extensions are {}, npy",
+            READABLE_EXTENSIONS.join(", ")
+        )]),
+    }
+}
+
+impl Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileFormat::Csv => write!(f, "CSV"),
+            FileFormat::Json => write!(f, "JSON"),
+            FileFormat::Npz => write!(f, "NumPy NPZ"),
+            FileFormat::Arff => write!(f, "ARFF"),
+            FileFormat::Libsvm => write!(f, "LIBSVM"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(FileFormat::Csv)]
+    #[case(FileFormat::Json)]
+    #[case(FileFormat::Npz)]
+    #[case(FileFormat::Arff)]
+    #[case(FileFormat::Libsvm)]
+    fn points_survive_round_trip(#[case] format: FileFormat) {
+        let points: Vec<DataPoint> = (0..5)
+            .map(|i| {
+                let label = if i % 2 == 0 {
+                    DataLabel::Normal
+                } else {
+                    DataLabel::Anomaly
+                };
+                DataPoint::without_timestamp(vec![i as f64 * 0.1, -(i as f64), 1.0 / 3.0], label)
+            })
+            .collect();
+        let bytes = format.write(&points, 3).expect("writes");
+        let file_name = format!("data.{}", format.extension());
+        assert_eq!(read_file(&file_name, &bytes), Ok((points.clone(), 3)));
+        assert_eq!(FileFormat::from_file_name(&file_name, &bytes), Some(format));
+        // Without an extension the format is detected from the contents
+        assert_eq!(read_file("data", &bytes), Ok((points, 3)));
+    }
+
+    #[rstest]
+    #[case("data.CSV", b"", Some("csv"))]
+    #[case("data.txt", b"x,y,label\n1,2,Normal", Some("csv"))]
+    #[case("data.txt", b"  {\"dimensions\": 2}", Some("json"))]
+    #[case("data", b"% comment\n@RELATION test", Some("arff"))]
+    #[case("data", b"1 1:0.5 2:3", Some("libsvm"))]
+    #[case("X", b"\x93NUMPY\x01\x00", Some("npy"))]
+    #[case("data", b"MATLAB 5.0 MAT-file", Some("mat"))]
+    #[case("data", b"hello", None)]
+    fn format_detection(
+        #[case] file_name: &str,
+        #[case] bytes: &[u8],
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(detect_extension(file_name, bytes), expected);
+    }
+}
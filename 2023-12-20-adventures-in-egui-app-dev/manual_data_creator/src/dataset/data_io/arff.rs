@@ -0,0 +1,238 @@
+//! Attribute-Relation File Format used by Weka, see <https://waikato.github.io/weka-wiki/formats_and_processing/arff_stable/>
+
+use super::{parse_label, ParseError};
+use crate::dataset::data_definition::{DataLabel, DataPoint};
+
+const RELATION_NAME: &str = "manual_data";
+const CLASS_ATTRIBUTE: &str = "class";
+
+/// Writes the points with one numeric attribute per dimension followed by a nominal class attribute
+pub fn write_points(points: &[DataPoint], dimensions: usize) -> String {
+    let mut result = format!("@RELATION {RELATION_NAME}\n\n");
+    for name in ["x", "y"]
+        .into_iter()
+        .map(String::from)
+        .chain((3..=dimensions).map(|dim| format!("dim{dim}")))
+    {
+        result.push_str(&format!("@ATTRIBUTE {name} NUMERIC\n"));
+    }
+    result.push_str(&format!(
+        "@ATTRIBUTE {CLASS_ATTRIBUTE} {{{},{}}}\n\n@DATA\n",
+        label_name(DataLabel::Normal),
+        label_name(DataLabel::Anomaly)
+    ));
+    for point in points {
+        for dim in 0..dimensions {
+            result.push_str(&format!("{},", point.coord(dim)));
+        }
+        result.push_str(label_name(point.label));
+        result.push('\n');
+    }
+    result
+}
+
+fn label_name(label: DataLabel) -> &'static str {
+    match label {
+        DataLabel::Normal => "Normal",
+        DataLabel::Anomaly => "Anomaly",
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum Attribute {
+    Numeric,
+    Class,
+}
+
+/// Reads points from ARFF text, returns the points and their number of dimensions
+///
+/// Every numeric attribute becomes a dimension and exactly one nominal attribute with the values
+/// Normal and Anomaly (or 0 and 1) is used as the label. Sparse data is not supported.
+pub fn read_points(text: &str) -> Result<(Vec<DataPoint>, usize), Vec<ParseError>> {
+    let error = |line: usize, message: String| ParseError { line, message };
+    let mut attributes = vec![];
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('%'));
+
+    // Header
+    let mut has_data_section = false;
+    for (line_number, line) in lines.by_ref() {
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match keyword.to_ascii_lowercase().as_str() {
+            "@relation" => (),
+            "@attribute" => {
+                let attribute_type = skip_name(rest.trim());
+                let attribute = if attribute_type.starts_with('{') {
+                    let values: Vec<&str> = attribute_type
+                        .trim_matches(|c| c == '{' || c == '}')
+                        .split(',')
+                        .map(unquote)
+                        .collect();
+                    if !values.iter().all(|value| parse_label(value).is_some()) {
+                        return Err(vec![error(
+                            line_number,
+                            format!("Nominal values {values:?} can not be used as labels (expected Normal and Anomaly)"),
+                        )]);
+                    }
+                    Attribute::Class
+                } else if ["numeric", "real", "integer"]
+                    .contains(&attribute_type.to_ascii_lowercase().as_str())
+                {
+                    Attribute::Numeric
+                } else {
+                    return Err(vec![error(
+                        line_number,
+                        format!("Unsupported attribute type {attribute_type:?}"),
+                    )]);
+                };
+                attributes.push(attribute);
+            }
+            "@data" => {
+                has_data_section = true;
+                break;
+            }
+            _ => {
+                return Err(vec![error(
+                    line_number,
+                    format!("Unexpected line in header {line:?}"),
+                )])
+            }
+        }
+    }
+    if !has_data_section {
+        return Err(vec![error(
+            text.lines().count(),
+            "No @DATA section found".to_string(),
+        )]);
+    }
+    let dimensions = attributes
+        .iter()
+        .filter(|a| **a == Attribute::Numeric)
+        .count();
+    let class_count = attributes.len() - dimensions;
+    if class_count != 1 || dimensions < 2 {
+        return Err(vec![error(
+            1,
+            format!("Expected at least 2 numeric attributes and 1 class attribute but found {dimensions} numeric and {class_count} class"),
+        )]);
+    }
+
+    // Data
+    let mut points = vec![];
+    let mut errors = vec![];
+    for (line_number, line) in lines {
+        if line.starts_with('{') {
+            errors.push(error(
+                line_number,
+                "Sparse data is not supported".to_string(),
+            ));
+            break;
+        }
+        let values: Vec<&str> = line.split(',').map(unquote).collect();
+        if values.len() != attributes.len() {
+            errors.push(error(
+                line_number,
+                format!(
+                    "Expected {} values but found {}",
+                    attributes.len(),
+                    values.len()
+                ),
+            ));
+            continue;
+        }
+        let mut coords = Vec::with_capacity(dimensions);
+        let mut label = DataLabel::Normal;
+        for (attribute, value) in attributes.iter().zip(values) {
+            match attribute {
+                Attribute::Numeric => match value.parse() {
+                    Ok(value) => coords.push(value),
+                    Err(_) => errors.push(error(
+                        line_number,
+                        format!("Unable to parse {value:?} as a number"),
+                    )),
+                },
+                Attribute::Class => match parse_label(value) {
+                    Some(value) => label = value,
+                    None => errors.push(error(line_number, format!("Unknown class {value:?}"))),
+                },
+            }
+        }
+        if coords.len() == dimensions {
+            points.push(DataPoint::without_timestamp(coords, label));
+        }
+    }
+    if errors.is_empty() {
+        Ok((points, dimensions))
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns what is left after the (possibly quoted) attribute name at the start of `text`
+fn skip_name(text: &str) -> &str {
+    let rest = match text.chars().next() {
+        Some(quote @ ('\'' | '"')) => text[1..].split_once(quote).map_or("", |(_, rest)| rest),
+        _ => text
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest),
+    };
+    rest.trim()
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '\'' || c == '"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::test_points;
+
+    fn points() -> Vec<DataPoint> {
+        test_points(&[
+            (&[1.5, -2.0, 0.1], DataLabel::Normal),
+            (&[1e-7, 3.0, 12345.678], DataLabel::Anomaly),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = write_points(&points(), 3);
+        assert_eq!(read_points(&text).expect("valid file"), (points(), 3));
+    }
+
+    #[test]
+    fn reads_weka_style_file() {
+        let text = "% Comment\n\
+                    @relation 'test data'\n\
+                    @attribute 'first value' real\n\
+                    @attribute class {normal,anomaly}\n\
+                    @attribute second INTEGER\n\
+                    \n\
+                    @data\n\
+                    1.0,'anomaly',2\n\
+                    3.0,normal,4\n";
+        let (points, dimensions) = read_points(text).expect("valid file");
+        assert_eq!(dimensions, 2);
+        assert_eq!(points[0].coords(), vec![1.0, 2.0]);
+        assert_eq!(points[0].label, DataLabel::Anomaly);
+        assert_eq!(points[1].label, DataLabel::Normal);
+    }
+
+    #[test]
+    fn errors_reported_by_line() {
+        let text = "@relation r\n@attribute x numeric\n@attribute y numeric\n@attribute c {Normal,Anomaly}\n@data\n1,2,Normal\n1,x,Normal\n1,2,Other\n";
+        let errors = read_points(text).expect_err("invalid data");
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+
+        let errors =
+            read_points("@relation r\n@attribute c {cat,dog}\n@data\n").expect_err("bad class");
+        assert_eq!(errors[0].line, 2);
+    }
+}
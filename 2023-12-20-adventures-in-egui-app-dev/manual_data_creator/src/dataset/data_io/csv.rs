@@ -0,0 +1,391 @@
+use std::fmt::Display;
+
+use super::parse_label;
+use crate::dataset::data_definition::{DataPoint, Split};
+
+/// A table of numbers read from a CSV (or similar delimited text) file, stored by column
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct NumericTable {
+    /// Name of each column, taken from the header row or numbered if there is none
+    pub headers: Vec<String>,
+    /// Values of each column, all columns have the same length
+    pub columns: Vec<Vec<f64>>,
+}
+
+/// An error found at a specific line of the input
+#[derive(PartialEq, Clone, Debug)]
+pub struct ParseError {
+    /// Line number starting at 1
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+impl NumericTable {
+    /// Stop collecting errors after this many as the rest are usually caused by the same problem
+    const MAX_ERRORS: usize = 20;
+    const DELIMITERS: [char; 3] = [',', ';', '\t'];
+
+    /// Parses delimited text where every value is a number. The first row is used as the headers if it is not numeric
+    ///
+    /// Empty lines and lines starting with `#` are skipped
+    pub fn parse(text: &str) -> Result<Self, Vec<ParseError>> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+        let Some(&(first_line_number, first_line)) = lines.peek() else {
+            return Err(vec![ParseError {
+                line: 1,
+                message: "No data found".to_string(),
+            }]);
+        };
+        let delimiter = Self::DELIMITERS
+            .into_iter()
+            .max_by_key(|d| first_line.matches(*d).count())
+            .expect("there is at least one delimiter");
+        let first_fields: Vec<&str> = split_fields(first_line, delimiter).collect();
+        let column_count = first_fields.len();
+        let headers = if first_fields
+            .iter()
+            .any(|field| field.parse::<f64>().is_err())
+        {
+            lines.next(); // Consume header row
+            first_fields.iter().map(|field| field.to_string()).collect()
+        } else {
+            (1..=column_count).map(|i| format!("Column {i}")).collect()
+        };
+
+        let mut columns = vec![vec![]; column_count];
+        let mut errors = vec![];
+        for (line_number, line) in lines {
+            let fields: Vec<&str> = split_fields(line, delimiter).collect();
+            if fields.len() != column_count {
+                errors.push(ParseError {
+                    line: line_number,
+                    message: format!("Expected {column_count} values but found {}", fields.len()),
+                });
+            } else {
+                for (i, (column, field)) in columns.iter_mut().zip(fields).enumerate() {
+                    match field.parse::<f64>() {
+                        Ok(value) => column.push(value),
+                        Err(_) => errors.push(ParseError {
+                            line: line_number,
+                            message: format!(
+                                "Unable to parse {field:?} in column {} as a number",
+                                i + 1
+                            ),
+                        }),
+                    }
+                }
+            }
+            if errors.len() >= Self::MAX_ERRORS {
+                break;
+            }
+        }
+
+        if !errors.is_empty() {
+            Err(errors)
+        } else if columns[0].is_empty() {
+            Err(vec![ParseError {
+                line: first_line_number,
+                message: "Only a header row was found".to_string(),
+            }])
+        } else {
+            Ok(Self { headers, columns })
+        }
+    }
+}
+
+/// Writes the points with one row per point, all dimensions first followed by the label and metadata
+pub fn write_points(points: &[DataPoint], dimensions: usize) -> String {
+    let mut headers = vec!["x".to_string(), "y".to_string()];
+    headers.extend((3..=dimensions).map(|dim| format!("dim{dim}")));
+    headers.extend(
+        ["label", "split", "weight", "group", "created", "note"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    let mut result = headers.join(",");
+    result.push('\n');
+    for point in points {
+        let mut fields: Vec<String> = (0..dimensions)
+            .map(|dim| point.coord(dim).to_string())
+            .collect();
+        fields.push(format!("{:?}", point.label));
+        fields.push(
+            point
+                .meta
+                .split
+                .map_or_else(String::new, |s| s.as_str().to_string()),
+        );
+        fields.push(point.meta.weight.to_string());
+        fields.push(point.meta.group.map_or_else(String::new, |g| g.to_string()));
+        fields.push(
+            point
+                .meta
+                .created
+                .map_or_else(String::new, |c| c.to_string()),
+        );
+        fields.push(quote_if_needed(&point.meta.note));
+        result.push_str(&fields.join(","));
+        result.push('\n');
+    }
+    result
+}
+
+/// Reads points written by [`write_points`], returns the points and their number of dimensions
+///
+/// The columns before `label` are the dimensions. The metadata columns are optional and can be in any order
+pub fn read_points(text: &str) -> Result<(Vec<DataPoint>, usize), Vec<ParseError>> {
+    let error = |line: usize, message: String| ParseError { line, message };
+    let mut records = records(text)?.into_iter();
+    let Some((header_line, headers)) = records.next() else {
+        return Err(vec![error(1, "No data found".to_string())]);
+    };
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let Some(dimensions) = column("label") else {
+        return Err(vec![error(
+            header_line,
+            "No \"label\" column found".to_string(),
+        )]);
+    };
+    if dimensions < 2 {
+        return Err(vec![error(
+            header_line,
+            format!(
+                "Expected at least 2 dimension columns before \"label\" but found {dimensions}"
+            ),
+        )]);
+    }
+    let [split, weight, group, created, note] =
+        ["split", "weight", "group", "created", "note"].map(column);
+
+    let mut points = vec![];
+    let mut errors = vec![];
+    for (line, fields) in records {
+        if fields.len() != headers.len() {
+            errors.push(error(
+                line,
+                format!(
+                    "Expected {} values but found {}",
+                    headers.len(),
+                    fields.len()
+                ),
+            ));
+            continue;
+        }
+        match read_point(&fields, dimensions, [split, weight, group, created, note]) {
+            Ok(point) => points.push(point),
+            Err(message) => errors.push(error(line, message)),
+        }
+        if errors.len() >= NumericTable::MAX_ERRORS {
+            break;
+        }
+    }
+    if errors.is_empty() {
+        Ok((points, dimensions))
+    } else {
+        Err(errors)
+    }
+}
+
+/// `metadata_columns` are the indices of the split, weight, group, created and note columns
+fn read_point(
+    fields: &[String],
+    dimensions: usize,
+    metadata_columns: [Option<usize>; 5],
+) -> Result<DataPoint, String> {
+    let number = |value: &str| {
+        value
+            .parse::<f64>()
+            .map_err(|_| format!("Unable to parse {value:?} as a number"))
+    };
+    let coords = fields[..dimensions]
+        .iter()
+        .map(|field| number(field))
+        .collect::<Result<Vec<_>, _>>()?;
+    let label = &fields[dimensions];
+    let label = parse_label(label).ok_or_else(|| format!("Unknown label {label:?}"))?;
+    let mut point = DataPoint::without_timestamp(coords, label);
+
+    // Empty values leave the default
+    let [split, weight, group, created, note] =
+        metadata_columns.map(|column| column.map(|i| fields[i].as_str()).filter(|v| !v.is_empty()));
+    if let Some(split) = split {
+        point.meta.split = Some(
+            Split::ALL
+                .into_iter()
+                .find(|s| s.as_str().eq_ignore_ascii_case(split))
+                .ok_or_else(|| format!("Unknown split {split:?}"))?,
+        );
+    }
+    if let Some(weight) = weight {
+        point.meta.weight = number(weight)?;
+    }
+    if let Some(group) = group {
+        point.meta.group = Some(
+            group
+                .parse()
+                .map_err(|_| format!("Group {group:?} is not a whole number"))?,
+        );
+    }
+    if let Some(created) = created {
+        point.meta.created = Some(number(created)?);
+    }
+    if let Some(note) = note {
+        point.meta.note = note.to_string();
+    }
+    Ok(point)
+}
+
+/// Splits the text into records of comma separated fields with the line each record starts on
+///
+/// Fields can be quoted to include commas, quotes (written twice) and new lines. Empty lines are skipped
+fn records(text: &str) -> Result<Vec<(usize, Vec<String>)>, Vec<ParseError>> {
+    let mut result = vec![];
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut is_quoted = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, is_quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => is_quoted = false,
+            ('"', false) if field.trim().is_empty() => {
+                field.clear();
+                is_quoted = true;
+            }
+            (',', false) => fields.push(std::mem::take(&mut field).trim().to_string()),
+            ('\r', false) => (),
+            ('\n', false) => {
+                fields.push(std::mem::take(&mut field).trim().to_string());
+                if fields.len() > 1 || !fields[0].is_empty() {
+                    result.push((record_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                record_line = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if is_quoted {
+        return Err(vec![ParseError {
+            line: record_line,
+            message: "Quoted value is not closed".to_string(),
+        }]);
+    }
+    fields.push(field.trim().to_string());
+    if fields.len() > 1 || !fields[0].is_empty() {
+        result.push((record_line, fields));
+    }
+    Ok(result)
+}
+
+/// Surrounds `field` in quotes (doubling any quotes in it) if it would otherwise not be read back as one field
+fn quote_if_needed(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits on `delimiter` trimming whitespace and surrounding quotes from each field
+fn split_fields(line: &str, delimiter: char) -> impl Iterator<Item = &str> {
+    line.split(delimiter)
+        .map(|field| field.trim().trim_matches('"').trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dataset::data_definition::{DataLabel, PointMetadata};
+
+    #[rstest]
+    #[case("time,value\n0,1.5\n1,2.5\n", vec!["time", "value"])]
+    #[case("# comment\n\n0;1.5\n1;2.5", vec!["Column 1", "Column 2"])]
+    #[case("\"time\"\t\"value\"\n0\t1.5\n1\t2.5", vec!["time", "value"])]
+    fn parse_valid(#[case] text: &str, #[case] headers: Vec<&str>) {
+        let table = NumericTable::parse(text).expect("valid input");
+        assert_eq!(table.headers, headers);
+        assert_eq!(table.columns, vec![vec![0.0, 1.0], vec![1.5, 2.5]]);
+    }
+
+    #[test]
+    fn errors_reported_by_line() {
+        let errors = NumericTable::parse("a,b\n1,2\n3\n4,x\n").expect_err("invalid input");
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Line 4: Unable to parse \"x\" in column 2 as a number"
+        );
+    }
+
+    #[test]
+    fn write_points_with_metadata() {
+        let mut point = DataPoint::new(vec![1.0, 2.5, -3.0], DataLabel::Anomaly);
+        point.meta.created = None;
+        point.meta.note = "a, \"b\"".to_string();
+        point.meta.split = Some(Split::Test);
+        let expected = "x,y,dim3,label,split,weight,group,created,note\n\
+                        1,2.5,-3,Anomaly,Test,1,,,\"a, \"\"b\"\"\"\n";
+        assert_eq!(write_points(&[point], 3), expected);
+    }
+
+    #[test]
+    fn read_points_with_metadata() {
+        let mut point = DataPoint::new(vec![1.0, 2.5, -3.0], DataLabel::Anomaly);
+        point.meta = PointMetadata {
+            note: "line one\nsaid \"hi\", then left".to_string(),
+            weight: 0.5,
+            group: Some(3),
+            created: Some(1_703_116_800.25),
+            split: Some(Split::Validation),
+        };
+        let text = write_points(&[point.clone()], 3);
+        assert_eq!(read_points(&text).expect("valid file"), (vec![point], 3));
+    }
+
+    #[test]
+    fn read_points_errors_by_line() {
+        let text = "x,y,label\n1,2,Normal\n\n1,2\n1,2,Other\n";
+        let errors = read_points(text).expect_err("invalid data");
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert!(read_points("x,y\n1,2\n").is_err(), "no label column");
+        assert!(read_points("x,y,label,note\n1,2,Normal,\"open\n").is_err());
+    }
+
+    #[test]
+    fn empty_input_is_error() {
+        assert!(NumericTable::parse("\n# only comments\n").is_err());
+        assert!(NumericTable::parse("a,b\n").is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::dataset::data_definition::DataPoint;
+
+/// The layout of the JSON files written
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct JsonDataset {
+    pub dimensions: usize,
+    pub points: Vec<DataPoint>,
+}
+
+pub fn write_points(points: &[DataPoint], dimensions: usize) -> Result<String, String> {
+    serde_json::to_string_pretty(&JsonDataset {
+        dimensions,
+        points: points.to_vec(),
+    })
+    .map_err(|e| format!("Failed to convert points to JSON: {e}"))
+}
+
+/// Reads points written by [`write_points`], returns the points and their number of dimensions
+pub fn read_points(bytes: &[u8]) -> Result<(Vec<DataPoint>, usize), String> {
+    let dataset: JsonDataset =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid JSON dataset: {e}"))?;
+    if let Some((index, point)) = dataset
+        .points
+        .iter()
+        .enumerate()
+        .find(|(_, point)| point.extra_dims.len() + 2 != dataset.dimensions)
+    {
+        return Err(format!(
+            "Point at index {index} has {} dimensions but the dataset has {}",
+            point.extra_dims.len() + 2,
+            dataset.dimensions
+        ));
+    }
+    Ok((dataset.points, dataset.dimensions))
+}
@@ -0,0 +1,149 @@
+//! Sparse text format used by LIBSVM, see <https://www.csie.ntu.edu.tw/~cjlin/libsvm/faq.html#/Q03:_Data_preparation>
+
+use super::ParseError;
+use crate::dataset::data_definition::{Data, DataLabel, DataPoint};
+
+/// Writes one line per point with the target (0 for normal and 1 for anomaly) followed by
+/// `index:value` pairs. Indices start at 1 and values of 0 are left out, except in the last dimension
+/// which is always written so reading the file gives back the number of dimensions.
+pub fn write_points(points: &[DataPoint], dimensions: usize) -> String {
+    let mut result = String::new();
+    for point in points {
+        result.push_str(if point.label.is_anomaly() { "1" } else { "0" });
+        for dim in 0..dimensions {
+            let value = point.coord(dim);
+            if value != 0.0 || dim + 1 == dimensions {
+                result.push_str(&format!(" {}:{value}", dim + 1));
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Reads points from LIBSVM text, returns the points and their number of dimensions (the largest
+/// index used but at least 2)
+///
+/// Targets of 0 or -1 are normal and 1 is an anomaly
+pub fn read_points(text: &str) -> Result<(Vec<DataPoint>, usize), Vec<ParseError>> {
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for (line_number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line)) {
+        let line = line.split('#').next().unwrap_or_default().trim(); // Remove comments
+        if line.is_empty() {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(row) => rows.push(row),
+            Err(message) => errors.push(ParseError {
+                line: line_number,
+                message,
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let dimensions = rows
+        .iter()
+        .flat_map(|(_, values)| values.iter().map(|(index, _)| *index))
+        .max()
+        .unwrap_or_default()
+        .max(2);
+    let points = rows
+        .into_iter()
+        .map(|(label, values)| {
+            let mut coords = vec![0.0; dimensions];
+            for (index, value) in values {
+                coords[index - 1] = value;
+            }
+            DataPoint::without_timestamp(coords, label)
+        })
+        .collect();
+    Ok((points, dimensions))
+}
+
+/// Returns the label and the `(index, value)` pairs (index starting at 1)
+fn parse_line(line: &str) -> Result<(DataLabel, Vec<(usize, f64)>), String> {
+    let mut fields = line.split_whitespace();
+    let target = fields.next().unwrap_or_default();
+    let label = match target.parse::<f64>() {
+        Ok(value) if value == 0.0 || value == -1.0 => DataLabel::Normal,
+        Ok(1.0) => DataLabel::Anomaly,
+        _ => {
+            return Err(format!(
+                "Target {target:?} is not 0, -1 (normal) or 1 (anomaly)"
+            ))
+        }
+    };
+    let values = fields
+        .map(|field| {
+            let (index, value) = field
+                .split_once(':')
+                .ok_or_else(|| format!("Expected index:value but found {field:?}"))?;
+            let index: usize = index
+                .parse()
+                .ok()
+                .filter(|index| *index > 0)
+                .ok_or_else(|| format!("Index {index:?} is not a whole number above 0"))?;
+            if index > Data::MAX_DIMENSIONS {
+                return Err(format!(
+                    "Index {index} is above the maximum number of dimensions ({})",
+                    Data::MAX_DIMENSIONS
+                ));
+            }
+            let value: f64 = value
+                .parse()
+                .map_err(|_| format!("Unable to parse {value:?} as a number"))?;
+            Ok((index, value))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok((label, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::test_points;
+
+    fn points() -> Vec<DataPoint> {
+        test_points(&[
+            (&[1.5, 0.0, -0.25], DataLabel::Normal),
+            (&[0.0, 3.0, 1e10], DataLabel::Anomaly),
+            (&[2.0, 0.0, 0.0], DataLabel::Normal),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = write_points(&points(), 3);
+        assert_eq!(text, "0 1:1.5 3:-0.25\n1 2:3 3:10000000000\n0 1:2 3:0\n");
+        assert_eq!(read_points(&text).expect("valid file"), (points(), 3));
+
+        let last_zero = &points()[2..];
+        let text = write_points(last_zero, 3);
+        assert_eq!(
+            read_points(&text).expect("valid file"),
+            (last_zero.to_vec(), 3)
+        );
+    }
+
+    #[test]
+    fn sparse_and_signed_targets() {
+        let (points, dimensions) = read_points("-1 1:2\n+1 2:5 # comment\n").expect("valid");
+        assert_eq!(dimensions, 2);
+        assert_eq!(points[0].coords(), vec![2.0, 0.0]);
+        assert_eq!(points[1].label, DataLabel::Anomaly);
+    }
+
+    #[test]
+    fn errors_reported_by_line() {
+        let errors =
+            read_points("0 1:1\n2 1:1\n0 0:1\n0 1:x\n0 99999999999:1\n").expect_err("invalid");
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![2, 3, 4, 5]
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! MATLAB `.mat` files (level 5, as saved by version 5 to 7.2), see <https://www.mathworks.com/help/pdf_doc/matlab/matfile_format.pdf>
+//!
+//! Points are read from the numeric variables `X` (N×D) and `y` (N labels) like in NumPy files
+
+use std::io::Read as _;
+
+use super::{npy::points_from_rows, MAX_DECOMPRESSED_SIZE};
+use crate::dataset::data_definition::DataPoint;
+
+/// Text at the start of the header of every MAT-file (followed by the version)
+pub const MAGIC: &[u8] = b"MATLAB ";
+const HEADER_LEN: usize = 128;
+pub const FEATURES_VARIABLE: &str = "X";
+pub const LABELS_VARIABLE: &str = "y";
+
+// Data types
+const MI_INT8: u32 = 1;
+const MI_UINT8: u32 = 2;
+const MI_INT16: u32 = 3;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_SINGLE: u32 = 7;
+const MI_DOUBLE: u32 = 9;
+const MI_INT64: u32 = 12;
+const MI_UINT64: u32 = 13;
+const MI_MATRIX: u32 = 14;
+const MI_COMPRESSED: u32 = 15;
+
+/// Array classes from `mxDOUBLE_CLASS` to `mxUINT64_CLASS` hold plain numbers
+const NUMERIC_CLASSES: std::ops::RangeInclusive<u32> = 6..=15;
+const COMPLEX_FLAG: u32 = 0x0800;
+
+/// The values of a numeric variable converted to `f64` in column major order (as stored by MATLAB)
+#[derive(PartialEq, Debug)]
+struct Variable {
+    dims: Vec<usize>,
+    values: Vec<f64>,
+}
+
+/// Reads data elements one after the other
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    is_big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], is_big_endian: bool) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            is_big_endian,
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.is_big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    /// Returns the type and data of the next element or `None` at the end
+    fn element(&mut self) -> Result<Option<(u32, &'a [u8])>, String> {
+        let remaining = &self.bytes[self.position..];
+        if remaining.is_empty() {
+            return Ok(None);
+        }
+        if remaining.len() < 8 {
+            return Err("File ends in the middle of a data element".to_string());
+        }
+        let first = self.u32(&remaining[..4]);
+        // Small data elements pack the size into the upper bytes and the data into the tag
+        let (data_type, size, data_start, padded_size) = if first >> 16 != 0 {
+            (first & 0xFFFF, (first >> 16) as usize, 4, 4)
+        } else {
+            let size = self.u32(&remaining[4..8]) as usize;
+            // Compressed elements are not padded
+            let padded_size = if first == MI_COMPRESSED {
+                size
+            } else {
+                size.next_multiple_of(8)
+            };
+            (first, size, 8, padded_size)
+        };
+        let data = remaining
+            .get(data_start..data_start + size)
+            .ok_or("File ends in the middle of a data element")?;
+        self.position = (self.position + data_start + padded_size).min(self.bytes.len());
+        Ok(Some((data_type, data)))
+    }
+
+    /// Converts the data of a numeric element to `f64`
+    fn numbers(&self, data_type: u32, data: &[u8]) -> Result<Vec<f64>, String> {
+        macro_rules! convert {
+            ($t:ty) => {
+                data.chunks_exact(std::mem::size_of::<$t>())
+                    .map(|chunk| {
+                        let bytes = chunk.try_into().expect("chunk has the size of the type");
+                        (if self.is_big_endian {
+                            <$t>::from_be_bytes(bytes)
+                        } else {
+                            <$t>::from_le_bytes(bytes)
+                        }) as f64
+                    })
+                    .collect()
+            };
+        }
+        Ok(match data_type {
+            MI_INT8 => convert!(i8),
+            MI_UINT8 => convert!(u8),
+            MI_INT16 => convert!(i16),
+            MI_UINT16 => convert!(u16),
+            MI_INT32 => convert!(i32),
+            MI_UINT32 => convert!(u32),
+            MI_SINGLE => convert!(f32),
+            MI_DOUBLE => convert!(f64),
+            MI_INT64 => convert!(i64),
+            MI_UINT64 => convert!(u64),
+            other => return Err(format!("Unsupported numeric data type {other}")),
+        })
+    }
+
+    /// Reads the next variable, skipping those that are not numeric matrices (cells, structs, text, ...)
+    ///
+    /// Returns the name of every variable seen (to be able to say what the file contains) and the numeric ones
+    fn variable(&mut self) -> Result<Option<(String, Option<Variable>)>, String> {
+        while let Some((data_type, data)) = self.element()? {
+            match data_type {
+                MI_MATRIX => return self.matrix(data).map(Some),
+                MI_COMPRESSED => {
+                    let mut decompressed = vec![];
+                    flate2::read::ZlibDecoder::new(data)
+                        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+                        .read_to_end(&mut decompressed)
+                        .map_err(|e| format!("Failed to decompress variable: {e}"))?;
+                    if decompressed.len() > MAX_DECOMPRESSED_SIZE {
+                        return Err(format!(
+                            "Compressed variable is larger than {MAX_DECOMPRESSED_SIZE} bytes"
+                        ));
+                    }
+                    if let Some(variable) =
+                        Reader::new(&decompressed, self.is_big_endian).variable()?
+                    {
+                        return Ok(Some(variable));
+                    }
+                }
+                _ => (), // Not a variable
+            }
+        }
+        Ok(None)
+    }
+
+    fn matrix(&self, data: &[u8]) -> Result<(String, Option<Variable>), String> {
+        let mut parts = Reader::new(data, self.is_big_endian);
+        let mut next = || {
+            parts
+                .element()?
+                .ok_or_else(|| "Variable ends before all of its parts".to_string())
+        };
+        let (_, flags) = next()?;
+        if flags.len() < 4 {
+            return Err("Invalid array flags".to_string());
+        }
+        let flags = self.u32(flags);
+        let (_, dims) = next()?;
+        let dims: Vec<usize> = self
+            .numbers(MI_INT32, dims)?
+            .into_iter()
+            .map(|d| d as usize)
+            .collect();
+        let (_, name) = next()?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        if !NUMERIC_CLASSES.contains(&(flags & 0xFF)) {
+            return Ok((name, None));
+        }
+        if flags & COMPLEX_FLAG != 0 {
+            return Err(format!("Variable {name:?} holds complex numbers"));
+        }
+        let (data_type, real) = next()?;
+        let values = self.numbers(data_type, real)?;
+        let count = dims
+            .iter()
+            .try_fold(1usize, |count, &size| count.checked_mul(size))
+            .ok_or_else(|| format!("Variable {name:?} has a size {dims:?} that is too large"))?;
+        if values.len() != count {
+            return Err(format!(
+                "Variable {name:?} has {} values but its size is {dims:?}",
+                values.len()
+            ));
+        }
+        Ok((name, Some(Variable { dims, values })))
+    }
+}
+
+/// Reads points from the variables `X` and `y`, returns the points and their number of dimensions
+pub fn read_points(bytes: &[u8]) -> Result<(Vec<DataPoint>, usize), String> {
+    if bytes.len() < HEADER_LEN {
+        return Err("File is too short to be a MAT-file".to_string());
+    }
+    let is_big_endian = match &bytes[126..128] {
+        b"IM" => false,
+        b"MI" => true,
+        _ => return Err("Not a MATLAB MAT-file (missing endian indicator)".to_string()),
+    };
+    if bytes.starts_with(b"MATLAB 7.3") {
+        return Err(
+            "MATLAB 7.3 MAT-files (HDF5) are not supported. Save with -v7 instead".to_string(),
+        );
+    }
+
+    let mut reader = Reader::new(&bytes[HEADER_LEN..], is_big_endian);
+    let mut names = vec![];
+    let mut features = None;
+    let mut labels = None;
+    while let Some((name, variable)) = reader.variable()? {
+        match (name.as_str(), variable) {
+            (FEATURES_VARIABLE, Some(variable)) => features = Some(variable),
+            (LABELS_VARIABLE, Some(variable)) => labels = Some(variable),
+            _ => (),
+        }
+        names.push(name);
+    }
+    let missing = |variable: &str| {
+        format!(
+            "Expected numeric variables {FEATURES_VARIABLE} and {LABELS_VARIABLE} but {variable} was not found. Variables in the file: {}",
+            names.join(", ")
+        )
+    };
+    let features = features.ok_or_else(|| missing(FEATURES_VARIABLE))?;
+    let labels = labels.ok_or_else(|| missing(LABELS_VARIABLE))?;
+
+    let [count, dimensions] = features.dims[..] else {
+        return Err(format!(
+            "{FEATURES_VARIABLE}: Expected 2 dimensional size N×D but found {:?}",
+            features.dims
+        ));
+    };
+    if dimensions < 2 {
+        return Err(format!(
+            "{FEATURES_VARIABLE}: Expected at least 2 columns but found {dimensions}"
+        ));
+    }
+    if !(labels.dims == [count, 1] || labels.dims == [1, count]) {
+        return Err(format!(
+            "{LABELS_VARIABLE}: Expected {count} labels to match {FEATURES_VARIABLE} but found size {:?}",
+            labels.dims
+        ));
+    }
+    let rows: Vec<f64> = (0..count)
+        .flat_map(|row| (0..dimensions).map(move |column| column * count + row))
+        .map(|i| features.values[i])
+        .collect();
+    points_from_rows(&rows, dimensions, &labels.values, LABELS_VARIABLE)
+        .map(|points| (points, dimensions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dataset::{data_definition::DataLabel, test_points};
+
+    /// A tag and data padded to 8 bytes, using the small format when the data fits in 4 bytes
+    fn element(data_type: u32, data: &[u8]) -> Vec<u8> {
+        let mut result = vec![];
+        if data.len() <= 4 {
+            result.extend_from_slice(&(data_type | (data.len() as u32) << 16).to_le_bytes());
+            result.extend_from_slice(data);
+            result.resize(8, 0);
+        } else {
+            result.extend_from_slice(&data_type.to_le_bytes());
+            result.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            result.extend_from_slice(data);
+            result.resize(result.len().next_multiple_of(8), 0);
+        }
+        result
+    }
+
+    /// A `double` (class 6) or `uint8` (class 9) matrix with `values` in column major order
+    fn matrix(name: &str, class: u32, dims: [i32; 2], values: &[f64]) -> Vec<u8> {
+        let mut flags = class.to_le_bytes().to_vec();
+        flags.extend_from_slice(&[0; 4]);
+        let dims: Vec<u8> = dims.iter().flat_map(|d| d.to_le_bytes()).collect();
+        let (data_type, data): (u32, Vec<u8>) = if class == 6 {
+            (
+                MI_DOUBLE,
+                values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            )
+        } else {
+            (MI_UINT8, values.iter().map(|&v| v as u8).collect())
+        };
+        let mut contents = element(MI_UINT32, &flags);
+        contents.extend(element(MI_INT32, &dims));
+        contents.extend(element(MI_INT8, name.as_bytes()));
+        contents.extend(element(data_type, &data));
+        element(MI_MATRIX, &contents)
+    }
+
+    fn file(variables: &[Vec<u8>], is_compressed: bool) -> Vec<u8> {
+        let mut result = b"MATLAB 5.0 MAT-file".to_vec();
+        result.resize(124, b' ');
+        result.extend_from_slice(&0x0100u16.to_le_bytes());
+        result.extend_from_slice(b"IM");
+        for variable in variables {
+            if is_compressed {
+                let mut encoder =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(variable).expect("in memory");
+                let compressed = encoder.finish().expect("in memory");
+                result.extend_from_slice(&MI_COMPRESSED.to_le_bytes());
+                result.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                result.extend(compressed);
+            } else {
+                result.extend_from_slice(variable);
+            }
+        }
+        result
+    }
+
+    fn expected_points() -> Vec<DataPoint> {
+        test_points(&[
+            (&[1.0, 2.0], DataLabel::Normal),
+            (&[3.0, 4.5], DataLabel::Anomaly),
+        ])
+    }
+
+    #[rstest]
+    fn reads_features_and_labels(#[values(false, true)] is_compressed: bool) {
+        let bytes = file(
+            &[
+                matrix("other", 6, [1, 1], &[7.0]),
+                // Column major: first column is 1, 3 and the second 2, 4.5
+                matrix("X", 6, [2, 2], &[1.0, 3.0, 2.0, 4.5]),
+                matrix("y", 9, [1, 2], &[0.0, 1.0]),
+            ],
+            is_compressed,
+        );
+        assert_eq!(read_points(&bytes), Ok((expected_points(), 2)));
+    }
+
+    #[test]
+    fn many_other_elements_skipped() {
+        let mut variables = vec![element(MI_INT8, b"not a variable"); 100_000];
+        variables.push(matrix("X", 6, [2, 2], &[1.0, 3.0, 2.0, 4.5]));
+        variables.push(matrix("y", 9, [1, 2], &[0.0, 1.0]));
+        assert_eq!(
+            read_points(&file(&variables, false)),
+            Ok((expected_points(), 2))
+        );
+    }
+
+    #[rstest]
+    #[case(file(&[matrix("X", 6, [2, 2], &[1.0, 3.0, 2.0, 4.5])], false), "y was not found. Variables in the file: X")]
+    #[case(file(&[matrix("X", 6, [2, 2], &[1.0; 4]), matrix("y", 6, [3, 1], &[0.0; 3])], false), "Expected 2 labels")]
+    #[case(file(&[matrix("X", 6, [1, 2], &[1.0; 2]), matrix("y", 6, [1, 1], &[2.0])], false), "Label 2 at index 0")]
+    #[case(b"MATLAB 7.3 MAT-file".to_vec(), "too short")]
+    #[case({
+        let mut bytes = b"MATLAB 7.3 MAT-file".to_vec();
+        bytes.resize(126, b' ');
+        bytes.extend_from_slice(b"IM");
+        bytes
+    }, "not supported")]
+    fn errors(#[case] bytes: Vec<u8>, #[case] expected: &str) {
+        let error = read_points(&bytes).expect_err("invalid file");
+        assert!(error.contains(expected), "{error}");
+    }
+}
@@ -0,0 +1,483 @@
+//! NumPy `.npy` and `.npz` files, see <https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html>
+
+use std::io::{Cursor, Read as _, Write as _};
+
+use super::MAX_DECOMPRESSED_SIZE;
+use crate::dataset::data_definition::{DataLabel, DataPoint};
+
+/// Every `.npy` file starts with these bytes
+pub const MAGIC: &[u8] = b"\x93NUMPY";
+/// The total length of the header (including the magic string) is padded to a multiple of this
+const HEADER_ALIGNMENT: usize = 64;
+/// Name of the coordinates array in a `.npz` archive
+pub const FEATURES_FILE_NAME: &str = "X.npy";
+/// Name of the labels array in a `.npz` archive
+pub const LABELS_FILE_NAME: &str = "y.npy";
+
+/// An array read from a `.npy` file with its values converted to `f64` in row major order
+#[derive(PartialEq, Debug)]
+pub struct NpyArray {
+    pub shape: Vec<usize>,
+    pub dtype: DType,
+    pub values: Vec<f64>,
+}
+
+/// The supported element types (the byte order is handled while reading)
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct DType {
+    pub kind: DTypeKind,
+    /// Size in bytes
+    pub size: usize,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DTypeKind {
+    Float,
+    SignedInt,
+    UnsignedInt,
+    Bool,
+}
+
+impl DType {
+    /// Parses a descr such as `<f8` or `|u1`, returns the type and if it is big endian
+    fn parse(descr: &str) -> Result<(Self, bool), String> {
+        let unsupported = || format!("Unsupported dtype {descr:?}. Expected a numeric type");
+        let mut chars = descr.chars();
+        let is_big_endian = match chars.next() {
+            Some('>') => true,
+            Some('<' | '|' | '=') => false,
+            _ => return Err(unsupported()),
+        };
+        let kind = match chars.next() {
+            Some('f') => DTypeKind::Float,
+            Some('i') => DTypeKind::SignedInt,
+            Some('u') => DTypeKind::UnsignedInt,
+            Some('b') => DTypeKind::Bool,
+            _ => return Err(unsupported()),
+        };
+        let size: usize = chars.as_str().parse().map_err(|_| unsupported())?;
+        let is_size_supported = match kind {
+            DTypeKind::Float => matches!(size, 4 | 8),
+            DTypeKind::SignedInt | DTypeKind::UnsignedInt => matches!(size, 1 | 2 | 4 | 8),
+            DTypeKind::Bool => size == 1,
+        };
+        if is_size_supported {
+            Ok((Self { kind, size }, is_big_endian))
+        } else {
+            Err(unsupported())
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        !matches!(self.kind, DTypeKind::Float)
+    }
+
+    /// Converts one little endian element into `f64`
+    fn to_f64(self, bytes: &[u8]) -> f64 {
+        let mut buffer = [0u8; 8];
+        buffer[..self.size].copy_from_slice(bytes);
+        match (self.kind, self.size) {
+            (DTypeKind::Float, 4) => f32::from_le_bytes(buffer[..4].try_into().unwrap()) as f64,
+            (DTypeKind::Float, _) => f64::from_le_bytes(buffer),
+            (DTypeKind::SignedInt, size) => {
+                // Shift up then back down to extend the sign bit
+                let shift = 64 - 8 * size as u32;
+                (i64::from_le_bytes(buffer) << shift >> shift) as f64
+            }
+            (DTypeKind::UnsignedInt | DTypeKind::Bool, _) => u64::from_le_bytes(buffer) as f64,
+        }
+    }
+}
+
+/// Writes an array of `f64` (`<f8`) with the given shape
+pub fn write_f64(values: &[f64], shape: &[usize]) -> Vec<u8> {
+    let mut result = header("<f8", shape);
+    values
+        .iter()
+        .for_each(|value| result.extend_from_slice(&value.to_le_bytes()));
+    result
+}
+
+/// Writes an array of `i64` (`<i8`) with the given shape
+pub fn write_i64(values: &[i64], shape: &[usize]) -> Vec<u8> {
+    let mut result = header("<i8", shape);
+    values
+        .iter()
+        .for_each(|value| result.extend_from_slice(&value.to_le_bytes()));
+    result
+}
+
+/// Builds a version 1.0 header
+fn header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [length] => format!("({length},)"), // One element tuples need a trailing comma in Python
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut dict = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    // Magic (6) + version (2) + header length (2) + dict + newline
+    let unpadded = MAGIC.len() + 4 + dict.len() + 1;
+    let padding = (HEADER_ALIGNMENT - unpadded % HEADER_ALIGNMENT) % HEADER_ALIGNMENT;
+    dict.push_str(&" ".repeat(padding));
+    dict.push('\n');
+
+    let mut result = MAGIC.to_vec();
+    result.extend_from_slice(&[1, 0]);
+    result.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    result.extend_from_slice(dict.as_bytes());
+    result
+}
+
+/// Reads a `.npy` file
+pub fn read(bytes: &[u8]) -> Result<NpyArray, String> {
+    if !bytes.starts_with(MAGIC) {
+        return Err("Not a .npy file (magic string not found)".to_string());
+    }
+    let major_version = *bytes.get(MAGIC.len()).ok_or("File ends in the header")?;
+    let (header_length, header_start) = match major_version {
+        1 => {
+            let length = bytes.get(8..10).ok_or("File ends in the header")?;
+            (u16::from_le_bytes([length[0], length[1]]) as usize, 10)
+        }
+        2 | 3 => {
+            let length = bytes.get(8..12).ok_or("File ends in the header")?;
+            (u32::from_le_bytes(length.try_into().unwrap()) as usize, 12)
+        }
+        _ => return Err(format!("Unsupported .npy version {major_version}")),
+    };
+    let header = bytes
+        .get(header_start..header_start + header_length)
+        .ok_or("File ends in the header")?;
+    let header = std::str::from_utf8(header).map_err(|_| "Header is not valid text")?;
+
+    let (dtype, is_big_endian) = DType::parse(&dict_value(header, "descr")?)?;
+    let is_fortran_order = match dict_value(header, "fortran_order")?.as_str() {
+        "True" => true,
+        "False" => false,
+        other => return Err(format!("Invalid fortran_order value {other:?}")),
+    };
+    let shape = parse_shape(&dict_value(header, "shape")?)?;
+
+    let expected_length = shape
+        .iter()
+        .try_fold(dtype.size, |length, &size| length.checked_mul(size))
+        .ok_or_else(|| format!("Shape {shape:?} is too large"))?;
+    let data = &bytes[header_start + header_length..];
+    if data.len() != expected_length {
+        return Err(format!(
+            "Expected {expected_length} bytes of data for shape {shape:?} but found {}",
+            data.len()
+        ));
+    }
+    let mut values: Vec<f64> = data
+        .chunks_exact(dtype.size)
+        .map(|chunk| {
+            if is_big_endian {
+                let mut reversed = chunk.to_vec();
+                reversed.reverse();
+                dtype.to_f64(&reversed)
+            } else {
+                dtype.to_f64(chunk)
+            }
+        })
+        .collect();
+    if is_fortran_order && shape.len() == 2 {
+        let [rows, columns] = [shape[0], shape[1]];
+        values = (0..rows * columns)
+            .map(|i| values[(i % columns) * rows + i / columns])
+            .collect();
+    } else if is_fortran_order && shape.len() > 2 {
+        return Err("Fortran order is only supported for arrays with up to 2 dimensions".into());
+    }
+    Ok(NpyArray {
+        shape,
+        dtype,
+        values,
+    })
+}
+
+/// Returns the text of the value of `key` in a header dictionary such as
+/// `{'descr': '<f8', 'fortran_order': False, 'shape': (3, 2), }` with any quotes removed
+fn dict_value(header: &str, key: &str) -> Result<String, String> {
+    let missing = || format!("Header does not contain {key:?}");
+    let start = header.find(&format!("'{key}'")).ok_or_else(missing)? + key.len() + 2;
+    let rest = header[start..]
+        .trim_start()
+        .strip_prefix(':')
+        .ok_or_else(missing)?;
+    let rest = rest.trim_start();
+    let value = if let Some(quoted) = rest.strip_prefix('\'') {
+        quoted.split('\'').next().unwrap_or_default()
+    } else if rest.starts_with('(') {
+        let end = rest.find(')').ok_or("Header has an unclosed shape tuple")?;
+        &rest[..=end]
+    } else {
+        rest.split([',', '}']).next().unwrap_or_default().trim()
+    };
+    Ok(value.to_string())
+}
+
+/// Parses a Python tuple of integers such as `()`, `(5,)` or `(5, 2)`
+fn parse_shape(text: &str) -> Result<Vec<usize>, String> {
+    text.trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.trim_end_matches('L') // Written by Python 2
+                .parse()
+                .map_err(|_| format!("Invalid shape {text:?}"))
+        })
+        .collect()
+}
+
+/// Converts the points into `X` (float64, one row per point and one column per dimension) and
+/// `y` (int64, 0 for normal and 1 for anomaly)
+pub fn write_features_and_labels(points: &[DataPoint], dimensions: usize) -> (Vec<u8>, Vec<u8>) {
+    let features: Vec<f64> = points
+        .iter()
+        .flat_map(|point| (0..dimensions).map(|dim| point.coord(dim)))
+        .collect();
+    let labels: Vec<i64> = points
+        .iter()
+        .map(|point| i64::from(point.label.is_anomaly()))
+        .collect();
+    (
+        write_f64(&features, &[points.len(), dimensions]),
+        write_i64(&labels, &[points.len()]),
+    )
+}
+
+/// Reads points from the contents of `X.npy` and `y.npy`, returns the points and their number of dimensions
+pub fn read_features_and_labels(
+    features: &[u8],
+    labels: &[u8],
+) -> Result<(Vec<DataPoint>, usize), String> {
+    let features = read(features).map_err(|e| format!("{FEATURES_FILE_NAME}: {e}"))?;
+    let labels = read(labels).map_err(|e| format!("{LABELS_FILE_NAME}: {e}"))?;
+    let [count, dimensions] = features.shape[..] else {
+        return Err(format!(
+            "{FEATURES_FILE_NAME}: Expected 2 dimensional shape (N, D) but found {:?}",
+            features.shape
+        ));
+    };
+    if dimensions < 2 {
+        return Err(format!(
+            "{FEATURES_FILE_NAME}: Expected at least 2 columns but found {dimensions}"
+        ));
+    }
+    if !(labels.shape == [count] || labels.shape == [count, 1]) {
+        return Err(format!(
+            "{LABELS_FILE_NAME}: Expected shape ({count},) to match {FEATURES_FILE_NAME} but found {:?}",
+            labels.shape
+        ));
+    }
+    if !labels.dtype.is_integer() {
+        return Err(format!(
+            "{LABELS_FILE_NAME}: Expected integer labels but found {:?}",
+            labels.dtype.kind
+        ));
+    }
+    points_from_rows(
+        &features.values,
+        dimensions,
+        &labels.values,
+        LABELS_FILE_NAME,
+    )
+    .map(|points| (points, dimensions))
+}
+
+/// Pairs each row of `features` (row major with `dimensions` columns) with the label at the same index (0 = normal, 1 = anomaly)
+///
+/// `labels_name` is used in errors to say where the labels came from
+pub fn points_from_rows(
+    features: &[f64],
+    dimensions: usize,
+    labels: &[f64],
+    labels_name: &str,
+) -> Result<Vec<DataPoint>, String> {
+    features
+        .chunks_exact(dimensions)
+        .zip(labels)
+        .enumerate()
+        .map(|(i, (coords, &label))| {
+            let label = if label == 0.0 {
+                DataLabel::Normal
+            } else if label == 1.0 {
+                DataLabel::Anomaly
+            } else {
+                return Err(format!(
+                    "{labels_name}: Label {label} at index {i} is not 0 (normal) or 1 (anomaly)"
+                ));
+            };
+            Ok(DataPoint::without_timestamp(coords.to_vec(), label))
+        })
+        .collect()
+}
+
+/// Writes `X.npy` and `y.npy` into an uncompressed `.npz` archive (the same as `numpy.savez`)
+pub fn write_npz(points: &[DataPoint], dimensions: usize) -> Result<Vec<u8>, String> {
+    let (features, labels) = write_features_and_labels(points, dimensions);
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (name, contents) in [(FEATURES_FILE_NAME, features), (LABELS_FILE_NAME, labels)] {
+        writer
+            .start_file(name, options)
+            .and_then(|()| writer.write_all(&contents).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {name} into .npz: {e}"))?;
+    }
+    writer
+        .finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to finish .npz: {e}"))
+}
+
+/// Reads points from a `.npz` archive containing `X.npy` and `y.npy` (compressed or not)
+pub fn read_npz(bytes: &[u8]) -> Result<(Vec<DataPoint>, usize), String> {
+    read_npz_with_limit(bytes, MAX_DECOMPRESSED_SIZE)
+}
+
+/// Same as [`read_npz`] but fails if an entry is larger than `max_entry_size` bytes once decompressed
+fn read_npz_with_limit(
+    bytes: &[u8],
+    max_entry_size: usize,
+) -> Result<(Vec<DataPoint>, usize), String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not a valid .npz: {e}"))?;
+    let mut read_entry = |name: &str| -> Result<Vec<u8>, String> {
+        let file = archive
+            .by_name(name)
+            .map_err(|_| format!(".npz does not contain {name}"))?;
+        let mut contents = vec![];
+        file.take(max_entry_size as u64 + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| format!("Failed to read {name} from .npz: {e}"))?;
+        if contents.len() > max_entry_size {
+            return Err(format!(
+                "{name} in .npz is larger than {max_entry_size} bytes"
+            ));
+        }
+        Ok(contents)
+    };
+    let features = read_entry(FEATURES_FILE_NAME)?;
+    let labels = read_entry(LABELS_FILE_NAME)?;
+    read_features_and_labels(&features, &labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::dataset::test_points;
+
+    fn points() -> Vec<DataPoint> {
+        test_points(&[
+            (&[1.0, 2.0, 3.0], DataLabel::Normal),
+            (&[-4.5, 0.25, 1e9], DataLabel::Anomaly),
+        ])
+    }
+
+    #[test]
+    fn header_is_aligned_and_readable() {
+        let bytes = write_f64(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[3, 2]);
+        let header_length = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_length) % HEADER_ALIGNMENT, 0);
+        assert_eq!(bytes[10 + header_length - 1], b'\n');
+        let array = read(&bytes).expect("valid file");
+        assert_eq!(array.shape, vec![3, 2]);
+        assert_eq!(array.values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[rstest]
+    #[case("<i4", &[0xFF, 0xFF, 0xFF, 0xFF], -1.0)]
+    #[case(">i2", &[0x01, 0x00], 256.0)]
+    #[case("|u1", &[0xFF], 255.0)]
+    #[case("<f4", &1.5f32.to_le_bytes(), 1.5)]
+    #[case("|b1", &[1], 1.0)]
+    fn dtypes(#[case] descr: &str, #[case] data: &[u8], #[case] expected: f64) {
+        let mut bytes = header(descr, &[1]);
+        bytes.extend_from_slice(data);
+        assert_eq!(read(&bytes).expect("valid file").values, vec![expected]);
+    }
+
+    #[test]
+    fn fortran_order_converted() {
+        let mut bytes = header("<f8", &[2, 3]);
+        let position = bytes
+            .windows(5)
+            .position(|window| window == b"False")
+            .expect("header contains fortran_order");
+        bytes[position..position + 5].copy_from_slice(b"True ");
+        // Stored column by column
+        for value in [1.0f64, 4.0, 2.0, 5.0, 3.0, 6.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let array = read(&bytes).expect("valid file");
+        assert_eq!(array.values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[rstest]
+    #[case(b"not numpy".to_vec(), "magic")]
+    #[case(header("<c16", &[1]), "Unsupported dtype")]
+    #[case(write_f64(&[1.0], &[2]), "Expected 16 bytes")]
+    #[case(header("<f8", &[usize::MAX, 2]), "too large")]
+    fn read_errors(#[case] bytes: Vec<u8>, #[case] expected: &str) {
+        let error = read(&bytes).expect_err("invalid file");
+        assert!(error.contains(expected), "{error}");
+    }
+
+    #[test]
+    fn large_compressed_entry_rejected() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        writer
+            .start_file(FEATURES_FILE_NAME, options)
+            .expect("in memory");
+        writer
+            .write_all(&write_f64(&[0.0; 1000], &[500, 2]))
+            .expect("in memory");
+        let bytes = writer.finish().expect("in memory").into_inner();
+        assert!(bytes.len() < 1000, "compressed well");
+        let error = read_npz_with_limit(&bytes, 4000).expect_err("too large");
+        assert!(error.contains("larger than 4000 bytes"), "{error}");
+    }
+
+    #[test]
+    fn features_and_labels_round_trip() {
+        let (features, labels) = write_features_and_labels(&points(), 3);
+        let (result, dimensions) =
+            read_features_and_labels(&features, &labels).expect("valid files");
+        assert_eq!(dimensions, 3);
+        assert_eq!(result, points());
+    }
+
+    #[test]
+    fn npz_round_trip() {
+        let bytes = write_npz(&points(), 3).expect("writes");
+        assert_eq!(read_npz(&bytes).expect("valid archive"), (points(), 3));
+    }
+
+    #[rstest]
+    #[case(write_f64(&[1.0, 2.0], &[2]), write_i64(&[0, 1], &[2]), "Expected 2 dimensional")]
+    #[case(write_f64(&[1.0, 2.0], &[1, 2]), write_i64(&[0, 1], &[2]), "Expected shape (1,)")]
+    #[case(write_f64(&[1.0, 2.0], &[1, 2]), write_f64(&[0.0], &[1]), "integer labels")]
+    #[case(write_f64(&[1.0, 2.0], &[1, 2]), write_i64(&[2], &[1]), "Label 2 at index 0")]
+    fn validation_errors(
+        #[case] features: Vec<u8>,
+        #[case] labels: Vec<u8>,
+        #[case] expected: &str,
+    ) {
+        let error = read_features_and_labels(&features, &labels).expect_err("invalid");
+        assert!(error.contains(expected), "{error}");
+    }
+}
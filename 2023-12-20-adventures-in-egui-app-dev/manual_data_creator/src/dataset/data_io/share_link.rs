@@ -0,0 +1,175 @@
+//! Compact encoding of small datasets that fits in the fragment of a URL
+
+use std::io::{Read as _, Write as _};
+
+use base64::Engine as _;
+
+use crate::dataset::{
+    data_definition::{DataLabel, DataPoint},
+    snapping::SnapSettings,
+};
+
+/// Longest encoded dataset allowed, longer links are truncated by some chat and mail programs
+pub const MAX_ENCODED_LEN: usize = 8_000;
+
+/// Starts the URL fragment holding an encoded dataset
+pub const FRAGMENT_PREFIX: &str = "#data=";
+
+/// What is kept of a dataset when sharing it, metadata is left out to keep links short
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+pub struct SharedData {
+    /// Number of dimensions of the points
+    pub dimensions: usize,
+    /// See [`Data::is_time_series`](crate::dataset::Data::is_time_series)
+    pub is_time_series: bool,
+    /// Shared so points added by the recipient line up with the shared ones
+    pub snap_settings: SnapSettings,
+    /// Label (0 = Normal, 1 = Anomaly) and coordinates of each point
+    points: Vec<(u8, Vec<f64>)>,
+}
+
+impl SharedData {
+    /// Keeps what is shared of the given dataset
+    pub fn new(
+        points: &[DataPoint],
+        dimensions: usize,
+        is_time_series: bool,
+        snap_settings: SnapSettings,
+    ) -> Self {
+        let points = points
+            .iter()
+            .map(|point| {
+                let label = match point.label {
+                    DataLabel::Normal => 0,
+                    DataLabel::Anomaly => 1,
+                };
+                (label, point.coords())
+            })
+            .collect();
+        Self {
+            dimensions,
+            is_time_series,
+            snap_settings,
+            points,
+        }
+    }
+
+    /// The shared points, without metadata
+    pub fn points(&self) -> Vec<DataPoint> {
+        self.points
+            .iter()
+            .map(|(label, coords)| {
+                let label = if *label == 0 {
+                    DataLabel::Normal
+                } else {
+                    DataLabel::Anomaly
+                };
+                DataPoint::without_timestamp(coords.clone(), label)
+            })
+            .collect()
+    }
+
+    /// Compresses and converts to URL safe base64, fails if the result is longer than [`MAX_ENCODED_LEN`]
+    pub fn encode(&self) -> Result<String, String> {
+        let json = serde_json::to_vec(self).map_err(|e| format!("Failed to serialize: {e}"))?;
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder
+            .write_all(&json)
+            .map_err(|e| format!("Failed to compress: {e}"))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| format!("Failed to compress: {e}"))?;
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed);
+        if encoded.len() > MAX_ENCODED_LEN {
+            return Err(format!(
+                "Dataset is too big to share as a link ({} characters, the limit is {MAX_ENCODED_LEN}). Download it as a file instead",
+                encoded.len()
+            ));
+        }
+        Ok(encoded)
+    }
+
+    /// Reverses [`Self::encode`] and checks that the points match the number of dimensions
+    pub fn decode(encoded: &str) -> Result<Self, String> {
+        if encoded.len() > MAX_ENCODED_LEN {
+            return Err(format!(
+                "Shared data is {} characters but at most {MAX_ENCODED_LEN} are allowed",
+                encoded.len()
+            ));
+        }
+        let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| format!("Shared data is not valid base64: {e}"))?;
+        let mut json = vec![];
+        flate2::read::DeflateDecoder::new(compressed.as_slice())
+            .read_to_end(&mut json)
+            .map_err(|e| format!("Failed to decompress shared data: {e}"))?;
+        let result: Self = serde_json::from_slice(&json)
+            .map_err(|e| format!("Shared data is not a valid dataset: {e}"))?;
+        if result.dimensions < 2 {
+            return Err(format!(
+                "Shared data has {} dimensions but at least 2 are needed",
+                result.dimensions
+            ));
+        }
+        if let Some(index) = result.points.iter().position(|(label, coords)| {
+            *label > 1 || coords.len() != result.dimensions || coords.iter().any(|v| !v.is_finite())
+        }) {
+            return Err(format!("Shared point at index {index} is not valid"));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(count: usize) -> Vec<DataPoint> {
+        (0..count)
+            .map(|i| {
+                let label = if i % 3 == 0 {
+                    DataLabel::Anomaly
+                } else {
+                    DataLabel::Normal
+                };
+                DataPoint::without_timestamp(vec![i as f64 * 0.37, -(i as f64), 2.5], label)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let points = points(50);
+        let shared = SharedData::new(&points, 3, true, SnapSettings::default());
+        let encoded = shared.encode().expect("small dataset");
+        assert!(encoded
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        let decoded = SharedData::decode(&encoded).expect("valid encoding");
+        assert_eq!(decoded, shared);
+        assert_eq!(decoded.points(), points);
+    }
+
+    #[test]
+    fn too_big_is_error() {
+        // Pseudo random values so compression can't shrink them below the limit
+        let points: Vec<DataPoint> = (0..5_000u64)
+            .map(|i| {
+                let value = (i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 11) as f64 / 7.0;
+                DataPoint::new(vec![value, -value], DataLabel::Normal)
+            })
+            .collect();
+        let error = SharedData::new(&points, 2, false, SnapSettings::default())
+            .encode()
+            .expect_err("too big to share");
+        assert!(error.contains("too big"), "{error}");
+    }
+
+    #[test]
+    fn invalid_input_is_error() {
+        assert!(SharedData::decode("not base64!").is_err());
+        assert!(SharedData::decode("AAAA").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Comparing the points with another version of them (for example a copy edited by someone else)
+
+use super::{calculate_distance, DataPoint};
+
+/// How a point differs between the current points and the other version
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Only in the other version
+    Added(DataPoint),
+    /// Index of a point that is only in the current points
+    Removed(usize),
+    /// Index of a current point matched to a point of the other version that is moved, relabelled or both
+    Changed(usize, DataPoint),
+}
+
+impl Difference {
+    /// Describes the difference for the list of differences and the undo history
+    pub fn describe(&self, current: &[DataPoint]) -> String {
+        match self {
+            Difference::Added(point) => format!("Added {point}"),
+            Difference::Removed(index) => format!("Removed {}", current[*index]),
+            Difference::Changed(index, other) => {
+                let point = &current[*index];
+                let kind = match (point.coords() != other.coords(), point.label != other.label) {
+                    (true, true) => "Moved and relabelled",
+                    (true, false) => "Moved",
+                    (false, _) => "Relabelled",
+                };
+                format!("{kind} {point} to {other}")
+            }
+        }
+    }
+}
+
+/// Finds how `other` differs from `current`, both need to have the same number of dimensions
+///
+/// Each point is matched to the closest point of the other version that is at most `tolerance` away (over all
+/// dimensions), preferring points with the same label. Matched points that are neither moved nor relabelled are left out.
+/// Removed and changed points come first in the order of `current`, followed by the added points in the order of `other`.
+pub fn diff(current: &[DataPoint], other: &[DataPoint], tolerance: f64) -> Vec<Difference> {
+    let current_coords: Vec<Vec<f64>> = current.iter().map(DataPoint::coords).collect();
+    let other_coords: Vec<Vec<f64>> = other.iter().map(DataPoint::coords).collect();
+    // Points further apart than the tolerance on the first dimension cannot match, so only the points of the other
+    // version in a window of the first dimension are compared (NaN never matches)
+    let mut by_first: Vec<usize> = (0..other.len())
+        .filter(|&j| !other_coords[j][0].is_nan())
+        .collect();
+    by_first.sort_by(|&a, &b| other_coords[a][0].total_cmp(&other_coords[b][0]));
+    let mut candidates = vec![];
+    for (i, coords) in current_coords.iter().enumerate() {
+        let start = by_first.partition_point(|&j| other_coords[j][0] < coords[0] - tolerance);
+        let end = by_first.partition_point(|&j| other_coords[j][0] <= coords[0] + tolerance);
+        for &j in by_first.get(start..end).unwrap_or_default() {
+            let distance = calculate_distance(coords, &other_coords[j]);
+            if distance <= tolerance {
+                candidates.push((distance, current[i].label != other[j].label, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| {
+        (a.0.total_cmp(&b.0))
+            .then(a.1.cmp(&b.1))
+            .then((a.2, a.3).cmp(&(b.2, b.3)))
+    });
+
+    let mut matches = vec![None; current.len()];
+    let mut is_other_matched = vec![false; other.len()];
+    for (_, _, i, j) in candidates {
+        if matches[i].is_none() && !is_other_matched[j] {
+            matches[i] = Some(j);
+            is_other_matched[j] = true;
+        }
+    }
+
+    let mut result = vec![];
+    for (i, matched) in matches.into_iter().enumerate() {
+        match matched {
+            None => result.push(Difference::Removed(i)),
+            Some(j)
+                if current_coords[i] != other_coords[j] || current[i].label != other[j].label =>
+            {
+                result.push(Difference::Changed(i, other[j].clone()))
+            }
+            Some(_) => {}
+        }
+    }
+    result.extend(
+        other
+            .iter()
+            .zip(is_other_matched)
+            .filter(|(_, is_matched)| !is_matched)
+            .map(|(point, _)| Difference::Added(point.clone())),
+    );
+    result
+}
+
+/// Returns `current` with the `accepted` differences (found by [`diff`] for these points) applied
+///
+/// Changed points take the coordinates and label of the other version but keep their metadata
+pub fn apply(current: &[DataPoint], accepted: &[Difference]) -> Vec<DataPoint> {
+    let mut result: Vec<Option<DataPoint>> = current.iter().cloned().map(Some).collect();
+    let mut added = vec![];
+    for difference in accepted {
+        match difference {
+            Difference::Added(point) => added.push(point.clone()),
+            Difference::Removed(index) => result[*index] = None,
+            Difference::Changed(index, other) => {
+                if let Some(point) = &mut result[*index] {
+                    point.set_coords(&other.coords());
+                    point.label = other.label;
+                }
+            }
+        }
+    }
+    result.into_iter().flatten().chain(added).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::DataLabel;
+
+    fn point(x: f64, y: f64, label: DataLabel) -> DataPoint {
+        DataPoint::without_timestamp(vec![x, y], label)
+    }
+
+    #[test]
+    fn differences_found_within_tolerance() {
+        let current = [
+            point(0.0, 0.0, DataLabel::Normal),
+            point(1.0, 1.0, DataLabel::Normal),
+            point(2.0, 2.0, DataLabel::Normal),
+            point(3.0, 3.0, DataLabel::Normal),
+        ];
+        let other = [
+            point(9.0, 9.0, DataLabel::Anomaly),
+            point(1.0, 1.0, DataLabel::Anomaly),
+            point(0.0, 0.0, DataLabel::Normal),
+            point(2.05, 2.0, DataLabel::Normal),
+        ];
+        let differences = diff(&current, &other, 0.1);
+        assert_eq!(
+            differences,
+            [
+                Difference::Changed(1, other[1].clone()),
+                Difference::Changed(2, other[3].clone()),
+                Difference::Removed(3),
+                Difference::Added(other[0].clone()),
+            ]
+        );
+        let descriptions: Vec<String> = differences
+            .iter()
+            .map(|difference| difference.describe(&current))
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                "Relabelled [1.00, 1.00, N] to [1.00, 1.00, A]",
+                "Moved [2.00, 2.00, N] to [2.05, 2.00, N]",
+                "Removed [3.00, 3.00, N]",
+                "Added [9.00, 9.00, A]",
+            ]
+        );
+
+        let mut merged = apply(&current, &differences);
+        merged.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let mut expected = other.to_vec();
+        expected.sort_by(|a, b| a.x.total_cmp(&b.x));
+        assert_eq!(merged, expected);
+        assert!(diff(&merged, &other, 0.1).is_empty());
+    }
+
+    #[test]
+    fn same_label_preferred_at_equal_distance() {
+        let current = [
+            point(0.0, 0.0, DataLabel::Anomaly),
+            point(0.0, 0.0, DataLabel::Normal),
+        ];
+        let other = [point(0.0, 0.0, DataLabel::Normal)];
+        assert_eq!(diff(&current, &other, 0.0), [Difference::Removed(0)]);
+    }
+
+    #[test]
+    fn metadata_kept_when_accepting_changes() {
+        let mut current = vec![point(0.0, 0.0, DataLabel::Normal)];
+        current[0].meta.note = "keep".to_string();
+        let other = [point(0.5, 0.0, DataLabel::Anomaly)];
+        let differences = diff(&current, &other, 1.0);
+        let merged = apply(&current, &differences);
+        assert_eq!(merged[0].meta.note, "keep");
+        assert_eq!((merged[0].x, merged[0].label), (0.5, DataLabel::Anomaly));
+    }
+
+    #[test]
+    fn matches_at_edge_of_first_dimension_window() {
+        let current = [
+            point(1.0, 0.0, DataLabel::Normal),
+            point(f64::NAN, 0.0, DataLabel::Normal),
+        ];
+        let other = [
+            point(1.5, 5.0, DataLabel::Normal), // Close on the first dimension only
+            point(0.5, 0.0, DataLabel::Anomaly),
+            point(1.5, 0.0, DataLabel::Normal),
+        ];
+        assert_eq!(
+            diff(&current, &other, 0.5),
+            [
+                Difference::Changed(0, other[2].clone()),
+                Difference::Removed(1),
+                Difference::Added(other[0].clone()),
+                Difference::Added(other[1].clone()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+/// Settings that control which points are removed by deleting and erasing
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct EraseSettings {
+    /// Only delete the closest point if it is within `max_delete_distance` of the click
+    pub should_limit_delete_distance: bool,
+    /// Maximum distance (data space) between a click and the point it deletes
+    pub max_delete_distance: f64,
+    /// Points within this distance (data space) of the pointer are erased
+    pub radius: f64,
+    /// Erase points of every label instead of only the label of the button used
+    pub should_ignore_label: bool,
+}
+
+impl Default for EraseSettings {
+    fn default() -> Self {
+        Self {
+            should_limit_delete_distance: false,
+            max_delete_distance: 1.0,
+            radius: 0.5,
+            should_ignore_label: false,
+        }
+    }
+}
+
+/// Converts `index` into the points left after removing the points at `removed` (sorted) into an index of the points
+/// before they were removed
+pub(super) fn index_before_removal(
+    index: usize,
+    removed: impl IntoIterator<Item = usize>,
+) -> usize {
+    let mut result = index;
+    for removed_index in removed {
+        if removed_index <= result {
+            result += 1;
+        } else {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, &[], 0)]
+    #[case(0, &[0], 1)]
+    #[case(0, &[0, 1, 2], 3)]
+    #[case(1, &[0, 2], 3)]
+    #[case(1, &[3, 4], 1)]
+    #[case(2, &[1, 5], 3)]
+    fn index_mapped_past_removed(
+        #[case] index: usize,
+        #[case] removed: &[usize],
+        #[case] expected: usize,
+    ) {
+        assert_eq!(
+            index_before_removal(index, removed.iter().copied()),
+            expected
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use super::view::MinMaxPair;
+
+/// Settings that control how the data is mapped onto the axes of the plot
+///
+/// Values are stored in "data space" (the values of the points) and shown in "plot space" (the
+/// values the plot works with). These only differ on log scaled axes where plot space is `log10`
+/// of data space.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct AxesSettings {
+    /// How zooming changes the ratio between the axes
+    pub aspect: AspectMode,
+    /// Horizontal axis
+    pub x: AxisSettings,
+    /// Vertical axis
+    pub y: AxisSettings,
+}
+
+/// Whether the ratio between the scales of the axes is kept while zooming
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum AspectMode {
+    /// Each axis can be zoomed independently
+    Free,
+    /// The number of plot units in x per plot unit in y is kept fixed at this value
+    Locked(f64),
+}
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug, Default)]
+#[serde(default)]
+/// How a single axis is labelled and scaled
+pub struct AxisSettings {
+    /// Shown as the axis label
+    pub name: String,
+    /// Shown after the name in parentheses, left out if empty
+    pub unit: String,
+    /// If set the axis always shows exactly this range (in data space)
+    pub fixed_range: Option<[f64; 2]>,
+    /// Show the axis with a `log10` scale, values that are not positive cannot be shown
+    pub is_log: bool,
+}
+
+impl Default for AxesSettings {
+    fn default() -> Self {
+        Self {
+            aspect: AspectMode::Locked(Self::DEFAULT_ASPECT),
+            x: AxisSettings::new("x"),
+            y: AxisSettings::new("y"),
+        }
+    }
+}
+
+impl AspectMode {
+    /// Returns `true` if the aspect mode is [`Locked`].
+    ///
+    /// [`Locked`]: AspectMode::Locked
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        matches!(self, Self::Locked(..))
+    }
+}
+
+impl AxesSettings {
+    /// Aspect of a locked aspect mode by default, equal scales on both axes
+    pub const DEFAULT_ASPECT: f64 = 1.0;
+
+    /// Returns the aspect to pass to the plot, if any
+    ///
+    /// A fixed range on either axis takes precedence over a locked aspect as both cannot be honoured at once
+    pub fn effective_aspect(&self) -> Option<f64> {
+        match self.aspect {
+            AspectMode::Locked(aspect) if !self.has_fixed_range() => Some(aspect),
+            AspectMode::Locked(_) | AspectMode::Free => None,
+        }
+    }
+
+    /// Returns `true` if either axis has a fixed range
+    pub fn has_fixed_range(&self) -> bool {
+        self.x.fixed_range.is_some() || self.y.fixed_range.is_some()
+    }
+
+    /// Converts a point in data space into plot space, returns `None` if it cannot be shown (non-positive value on a log axis)
+    pub fn to_plot(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        Some([self.x.to_plot(point[0])?, self.y.to_plot(point[1])?])
+    }
+
+    /// Converts a point in plot space (for example the pointer coordinate) into data space
+    pub fn to_data(&self, point: [f64; 2]) -> [f64; 2] {
+        [self.x.to_data(point[0]), self.y.to_data(point[1])]
+    }
+
+    /// Converts bounds in plot space into data space
+    pub fn bounds_to_data(&self, bounds: MinMaxPair) -> MinMaxPair {
+        MinMaxPair {
+            min: [self.x.to_data(bounds.min[0]), self.y.to_data(bounds.min[1])],
+            max: [self.x.to_data(bounds.max[0]), self.y.to_data(bounds.max[1])],
+        }
+    }
+
+    /// Replaces the range of any axis that has a fixed range with that range (in plot space)
+    pub fn apply_fixed_ranges(&self, mut bounds: MinMaxPair) -> MinMaxPair {
+        for (i, axis) in [&self.x, &self.y].into_iter().enumerate() {
+            if let Some([min, max]) = axis.fixed_range_in_plot() {
+                bounds.min[i] = min;
+                bounds.max[i] = max;
+            }
+        }
+        bounds
+    }
+}
+
+impl AxisSettings {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The text shown next to the axis
+    pub fn label(&self) -> String {
+        let mut result = self.name.clone();
+        if !self.unit.is_empty() {
+            result.push_str(&format!(" ({})", self.unit));
+        }
+        if self.is_log {
+            result.push_str(" [log]");
+        }
+        result
+    }
+
+    /// Converts a value from data space to plot space, `None` if it cannot be shown on a log axis
+    pub fn to_plot(&self, value: f64) -> Option<f64> {
+        if !self.is_log {
+            Some(value)
+        } else if value > 0.0 {
+            Some(value.log10())
+        } else {
+            None
+        }
+    }
+
+    /// Converts a value from plot space to data space
+    pub fn to_data(&self, value: f64) -> f64 {
+        if self.is_log {
+            10f64.powf(value)
+        } else {
+            value
+        }
+    }
+
+    /// Returns the fixed range converted into plot space, `None` if not set or not representable
+    pub fn fixed_range_in_plot(&self) -> Option<[f64; 2]> {
+        let [min, max] = self.fixed_range?;
+        let result = [self.to_plot(min)?, self.to_plot(max)?];
+        if result[0] < result[1] {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(false, 5.0, Some(5.0))]
+    #[case(false, -5.0, Some(-5.0))]
+    #[case(true, 100.0, Some(2.0))]
+    #[case(true, 0.0, None)]
+    #[case(true, -1.0, None)]
+    fn axis_to_plot(#[case] is_log: bool, #[case] value: f64, #[case] expected: Option<f64>) {
+        let axis = AxisSettings {
+            is_log,
+            ..Default::default()
+        };
+        assert_eq!(axis.to_plot(value), expected);
+        if let Some(plot_value) = expected {
+            assert!((axis.to_data(plot_value) - value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fixed_range_overrides_aspect() {
+        let mut axes = AxesSettings::default();
+        assert_eq!(axes.effective_aspect(), Some(AxesSettings::DEFAULT_ASPECT));
+        axes.y.fixed_range = Some([0.0, 10.0]);
+        assert_eq!(axes.effective_aspect(), None);
+        let bounds = axes.apply_fixed_ranges(MinMaxPair {
+            min: [-1.0, -1.0],
+            max: [1.0, 1.0],
+        });
+        assert_eq!(bounds.min, [-1.0, 0.0]);
+        assert_eq!(bounds.max, [1.0, 10.0]);
+    }
+}
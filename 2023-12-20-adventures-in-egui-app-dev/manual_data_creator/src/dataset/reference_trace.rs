@@ -0,0 +1,158 @@
+use super::data_io::NumericTable;
+
+/// Non-editable data loaded from a file and shown behind the points to guide labelling
+#[derive(PartialEq, Clone, Debug)]
+pub struct ReferenceTrace {
+    /// Shown in the legend, usually the file name
+    pub name: String,
+    table: NumericTable,
+    columns: TraceColumns,
+    /// The samples for the selected columns in data space
+    samples: Vec<[f64; 2]>,
+}
+
+/// Selects which columns of the table are plotted
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TraceColumns {
+    /// Plot `value` against `time`, the row number is used as the time if `time` is `None`
+    AgainstTime {
+        /// Column plotted on the x axis
+        time: Option<usize>,
+        /// Column plotted on the y axis
+        value: usize,
+    },
+    /// Plot one column against another
+    XY {
+        /// Column plotted on the x axis
+        x: usize,
+        /// Column plotted on the y axis
+        y: usize,
+    },
+}
+
+impl ReferenceTrace {
+    /// Creates a trace of the last column against the first (or against the row number if there is only one column)
+    pub fn new(name: String, table: NumericTable) -> Self {
+        let last = table.columns.len().saturating_sub(1);
+        let columns = TraceColumns::AgainstTime {
+            time: (last > 0).then_some(0),
+            value: last,
+        };
+        let mut result = Self {
+            name,
+            table,
+            columns,
+            samples: vec![],
+        };
+        result.set_columns(columns);
+        result
+    }
+
+    /// Names of the columns of the table
+    pub fn headers(&self) -> &[String] {
+        &self.table.headers
+    }
+
+    /// The columns currently plotted
+    pub fn columns(&self) -> TraceColumns {
+        self.columns
+    }
+
+    /// Changes the columns plotted, indices out of range are replaced with the last column
+    pub fn set_columns(&mut self, columns: TraceColumns) {
+        let last = self.table.columns.len().saturating_sub(1);
+        self.columns = match columns {
+            TraceColumns::AgainstTime { time, value } => TraceColumns::AgainstTime {
+                time: time.map(|t| t.min(last)),
+                value: value.min(last),
+            },
+            TraceColumns::XY { x, y } => TraceColumns::XY {
+                x: x.min(last),
+                y: y.min(last),
+            },
+        };
+        self.samples = match self.columns {
+            TraceColumns::AgainstTime { time, value } => self.table.columns[value]
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let time = time.map_or(i as f64, |t| self.table.columns[t][i]);
+                    [time, *value]
+                })
+                .collect(),
+            TraceColumns::XY { x, y } => self.table.columns[x]
+                .iter()
+                .zip(&self.table.columns[y])
+                .map(|(x, y)| [*x, *y])
+                .collect(),
+        };
+    }
+
+    /// The plotted points in data space, in the order of the rows
+    pub fn samples(&self) -> &[[f64; 2]] {
+        &self.samples
+    }
+
+    /// Returns the sample closest to `point`
+    ///
+    /// When plotted against time only the time is compared, so clicking anywhere above or below
+    /// the trace picks the sample at that time
+    pub fn nearest_sample(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        let distance = |sample: &[f64; 2]| match self.columns {
+            TraceColumns::AgainstTime { .. } => (sample[0] - point[0]).abs(),
+            TraceColumns::XY { .. } => (sample[0] - point[0]).hypot(sample[1] - point[1]),
+        };
+        self.samples
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn trace(columns: TraceColumns) -> ReferenceTrace {
+        let table = NumericTable {
+            headers: vec!["t".to_string(), "a".to_string(), "b".to_string()],
+            columns: vec![
+                vec![0.0, 10.0, 20.0],
+                vec![1.0, 2.0, 3.0],
+                vec![5.0, -5.0, 0.0],
+            ],
+        };
+        let mut result = ReferenceTrace::new("test".to_string(), table);
+        result.set_columns(columns);
+        result
+    }
+
+    #[rstest]
+    #[case(TraceColumns::AgainstTime { time: Some(0), value: 2 }, [12.0, 100.0], [10.0, -5.0])]
+    #[case(TraceColumns::AgainstTime { time: None, value: 1 }, [1.6, -9.0], [2.0, 3.0])]
+    #[case(TraceColumns::XY { x: 1, y: 2 }, [2.0, 4.0], [1.0, 5.0])]
+    fn nearest_sample(
+        #[case] columns: TraceColumns,
+        #[case] point: [f64; 2],
+        #[case] expected: [f64; 2],
+    ) {
+        assert_eq!(trace(columns).nearest_sample(point), Some(expected));
+    }
+
+    #[test]
+    fn default_columns_and_clamping() {
+        let mut trace = trace(TraceColumns::XY { x: 7, y: 1 });
+        assert_eq!(trace.columns(), TraceColumns::XY { x: 2, y: 1 });
+        trace.set_columns(ReferenceTrace::new("t".to_string(), trace.table.clone()).columns());
+        assert_eq!(
+            trace.columns(),
+            TraceColumns::AgainstTime {
+                time: Some(0),
+                value: 2
+            }
+        );
+        assert_eq!(trace.samples().len(), 3);
+    }
+}
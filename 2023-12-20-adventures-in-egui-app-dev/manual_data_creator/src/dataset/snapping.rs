@@ -0,0 +1,415 @@
+use std::f64::consts::TAU;
+
+use super::{reference_trace::ReferenceTrace, view::MinMaxPair};
+
+/// Settings that control how the coordinates of new points are adjusted before they are added
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Debug)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct SnapSettings {
+    /// If disabled points are added where they are clicked
+    pub is_enabled: bool,
+    /// Which of the settings below is used
+    pub mode: SnapMode,
+    /// Used for [`SnapMode::Grid`]
+    pub grid: GridSnap,
+    /// Used for [`SnapMode::Polar`]
+    pub polar: PolarSnap,
+    /// Used for [`SnapMode::ExistingPoints`]
+    pub existing_points: ExistingPointsSnap,
+    /// Draw the lines of the grid being snapped to on the plot
+    pub should_show_grid: bool,
+}
+
+/// What new points are snapped to
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum SnapMode {
+    /// Snaps to a rectangular grid, see [`GridSnap`]
+    Grid,
+    /// Snaps to circles and rays around a point, see [`PolarSnap`]
+    Polar,
+    /// Snaps to the axis values of existing points, see [`ExistingPointsSnap`]
+    ExistingPoints,
+    /// Snaps to the nearest sample of the reference trace (if one is loaded)
+    ReferenceTrace,
+}
+
+/// Snaps to a rectangular grid, each axis has its own step. A step of 0 disables snapping on that axis
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub struct GridSnap {
+    /// Distance between grid lines on the x and y axes
+    pub step: [f64; 2],
+    /// A point on the grid, all other grid points are a whole number of steps away from it
+    pub origin: [f64; 2],
+}
+
+/// Snaps to circles around `origin` and to evenly spaced rays leaving from it
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub struct PolarSnap {
+    /// Center of the circles
+    pub origin: [f64; 2],
+    /// Distance between circles. A value of 0 disables snapping of the radius
+    pub radial_step: f64,
+    /// Number of rays in a full turn. A value of 0 disables snapping of the angle
+    pub angle_divisions: u16,
+}
+
+/// Snaps each axis to the same axis value of an existing point if one is close enough
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Debug)]
+pub struct ExistingPointsSnap {
+    /// Snap the x value to the x value of an existing point
+    pub should_snap_x: bool,
+    /// Snap the y value to the y value of an existing point
+    pub should_snap_y: bool,
+    /// Maximum distance (per axis) that a value can be moved to match an existing point
+    pub max_distance: f64,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            mode: SnapMode::Grid,
+            grid: Default::default(),
+            polar: Default::default(),
+            existing_points: Default::default(),
+            should_show_grid: true,
+        }
+    }
+}
+
+impl Default for GridSnap {
+    fn default() -> Self {
+        Self::from_decimal_places(SnapSettings::DEFAULT_DECIMAL_PLACES)
+    }
+}
+
+impl Default for PolarSnap {
+    fn default() -> Self {
+        Self {
+            origin: [0.0, 0.0],
+            radial_step: 1.0,
+            angle_divisions: 16,
+        }
+    }
+}
+
+impl Default for ExistingPointsSnap {
+    fn default() -> Self {
+        Self {
+            should_snap_x: true,
+            should_snap_y: true,
+            max_distance: 0.5,
+        }
+    }
+}
+
+impl SnapMode {
+    /// All modes in the order they are listed in the UI
+    pub const ALL: [SnapMode; 4] = [
+        Self::Grid,
+        Self::Polar,
+        Self::ExistingPoints,
+        Self::ReferenceTrace,
+    ];
+
+    /// Name shown in the UI
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapMode::Grid => "Grid",
+            SnapMode::Polar => "Polar Grid",
+            SnapMode::ExistingPoints => "Existing Points",
+            SnapMode::ReferenceTrace => "Reference Trace",
+        }
+    }
+}
+
+impl SnapSettings {
+    const DEFAULT_DECIMAL_PLACES: u8 = 0;
+    /// Most decimal places that can be chosen for the grid step
+    pub const MAX_DECIMAL_PLACES: u8 = 10;
+
+    /// Settings equivalent to the rounding to decimal places that was used before snapping was added
+    pub fn from_decimal_places(decimal_places: u8) -> Self {
+        Self {
+            is_enabled: true,
+            mode: SnapMode::Grid,
+            grid: GridSnap::from_decimal_places(decimal_places),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `point` moved to the closest snap target or unchanged if snapping is disabled
+    /// `existing_points` are the 2D coordinates the points are shown at
+    pub fn snap(
+        &self,
+        point: [f64; 2],
+        existing_points: &[[f64; 2]],
+        reference_trace: Option<&ReferenceTrace>,
+    ) -> [f64; 2] {
+        if !self.is_enabled {
+            return point;
+        }
+        match self.mode {
+            SnapMode::Grid => self.grid.snap(point),
+            SnapMode::Polar => self.polar.snap(point),
+            SnapMode::ExistingPoints => self.existing_points.snap(point, existing_points),
+            SnapMode::ReferenceTrace => reference_trace
+                .and_then(|trace| trace.nearest_sample(point))
+                .unwrap_or(point),
+        }
+    }
+
+    /// Returns the lines (as lists of points) to draw to show the snap grid within `bounds`
+    ///
+    /// Returns no lines if the grid is hidden, not applicable for the mode or too dense to be useful
+    pub fn grid_lines(&self, bounds: MinMaxPair) -> Vec<Vec<[f64; 2]>> {
+        if !self.is_enabled || !self.should_show_grid {
+            return vec![];
+        }
+        match self.mode {
+            SnapMode::Grid => self.grid.lines(bounds),
+            SnapMode::Polar => self.polar.lines(bounds),
+            SnapMode::ExistingPoints | SnapMode::ReferenceTrace => vec![],
+        }
+    }
+}
+
+impl GridSnap {
+    /// Above this number of lines on screen the grid is not drawn as it would just fill the plot
+    const MAX_LINES_PER_AXIS: f64 = 200.0;
+
+    /// Grid with a step of one unit in the last of `decimal_places` on both axes
+    pub fn from_decimal_places(decimal_places: u8) -> Self {
+        let step = 10f64.powi(-(decimal_places as i32));
+        Self {
+            step: [step, step],
+            origin: [0.0, 0.0],
+        }
+    }
+
+    /// Returns the number of decimal places that `step` is equivalent to (if it is a power of ten)
+    pub fn decimal_places(&self) -> Option<u8> {
+        let [step_x, step_y] = self.step;
+        if step_x != step_y || step_x <= 0.0 {
+            return None;
+        }
+        let places = -step_x.log10().round();
+        if (0.0..=SnapSettings::MAX_DECIMAL_PLACES as f64).contains(&places)
+            && Self::from_decimal_places(places as u8).step[0] == step_x
+        {
+            Some(places as u8)
+        } else {
+            None
+        }
+    }
+
+    fn snap(&self, point: [f64; 2]) -> [f64; 2] {
+        [
+            snap_to_step(point[0], self.step[0], self.origin[0]),
+            snap_to_step(point[1], self.step[1], self.origin[1]),
+        ]
+    }
+
+    fn lines(&self, bounds: MinMaxPair) -> Vec<Vec<[f64; 2]>> {
+        let mut result = vec![];
+        for axis in 0..2 {
+            let other = 1 - axis;
+            let step = self.step[axis];
+            if step <= 0.0
+                || (bounds.max[axis] - bounds.min[axis]) / step > Self::MAX_LINES_PER_AXIS
+            {
+                continue;
+            }
+            let first = ((bounds.min[axis] - self.origin[axis]) / step).ceil() as i64;
+            let last = ((bounds.max[axis] - self.origin[axis]) / step).floor() as i64;
+            for i in first..=last {
+                let value = self.origin[axis] + i as f64 * step;
+                let mut start = [0.0; 2];
+                let mut end = [0.0; 2];
+                start[axis] = value;
+                end[axis] = value;
+                start[other] = bounds.min[other];
+                end[other] = bounds.max[other];
+                result.push(vec![start, end]);
+            }
+        }
+        result
+    }
+}
+
+impl PolarSnap {
+    const MAX_CIRCLES: f64 = 200.0;
+    /// Number of segments used to draw a full circle
+    const CIRCLE_SEGMENTS: usize = 128;
+
+    fn snap(&self, point: [f64; 2]) -> [f64; 2] {
+        let dx = point[0] - self.origin[0];
+        let dy = point[1] - self.origin[1];
+        let mut radius = dx.hypot(dy);
+        let mut angle = dy.atan2(dx);
+        radius = snap_to_step(radius, self.radial_step, 0.0);
+        if self.angle_divisions > 0 {
+            angle = snap_to_step(angle, TAU / self.angle_divisions as f64, 0.0);
+        }
+        [
+            self.origin[0] + radius * angle.cos(),
+            self.origin[1] + radius * angle.sin(),
+        ]
+    }
+
+    fn lines(&self, bounds: MinMaxPair) -> Vec<Vec<[f64; 2]>> {
+        let mut result = vec![];
+
+        // Furthest distance from the origin that is still visible
+        let max_radius = [
+            bounds.min,
+            [bounds.min[0], bounds.max[1]],
+            bounds.max,
+            [bounds.max[0], bounds.min[1]],
+        ]
+        .iter()
+        .map(|corner| (corner[0] - self.origin[0]).hypot(corner[1] - self.origin[1]))
+        .fold(0.0, f64::max);
+
+        if self.radial_step > 0.0 && max_radius / self.radial_step <= Self::MAX_CIRCLES {
+            let circle_count = (max_radius / self.radial_step).floor() as usize;
+            for i in 1..=circle_count {
+                let radius = i as f64 * self.radial_step;
+                result.push(
+                    (0..=Self::CIRCLE_SEGMENTS)
+                        .map(|segment| {
+                            let angle = TAU * segment as f64 / Self::CIRCLE_SEGMENTS as f64;
+                            [
+                                self.origin[0] + radius * angle.cos(),
+                                self.origin[1] + radius * angle.sin(),
+                            ]
+                        })
+                        .collect(),
+                );
+            }
+        }
+
+        for i in 0..self.angle_divisions {
+            let angle = TAU * i as f64 / self.angle_divisions as f64;
+            result.push(vec![
+                self.origin,
+                [
+                    self.origin[0] + max_radius * angle.cos(),
+                    self.origin[1] + max_radius * angle.sin(),
+                ],
+            ]);
+        }
+        result
+    }
+}
+
+impl ExistingPointsSnap {
+    fn snap(&self, point: [f64; 2], existing_points: &[[f64; 2]]) -> [f64; 2] {
+        let [mut x, mut y] = point;
+        if self.should_snap_x {
+            x = closest_within(x, existing_points.iter().map(|p| p[0]), self.max_distance);
+        }
+        if self.should_snap_y {
+            y = closest_within(y, existing_points.iter().map(|p| p[1]), self.max_distance);
+        }
+        [x, y]
+    }
+}
+
+/// Rounds `value` to the nearest `origin + k * step`. Leaves `value` unchanged if `step` is not positive
+fn snap_to_step(value: f64, step: f64, origin: f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+    let steps = ((value - origin) / step).round();
+    let reciprocal = step.recip();
+    if reciprocal.fract() == 0.0 {
+        // Dividing by a whole number keeps decimal steps exact (3.0 / 10.0 is 0.3 but 3.0 * 0.1 is not)
+        origin + steps / reciprocal
+    } else {
+        origin + steps * step
+    }
+}
+
+/// Returns the candidate closest to `value` if it is within `max_distance` otherwise `value`
+fn closest_within(value: f64, candidates: impl Iterator<Item = f64>, max_distance: f64) -> f64 {
+    candidates
+        .map(|candidate| (candidate, (candidate - value).abs()))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(value, |(candidate, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, [1.4, -1.6], [1.0, -2.0])]
+    #[case(1, [0.26, 0.34], [0.3, 0.3])]
+    #[case(2, [1.005_1, 2.999], [1.01, 3.0])]
+    fn decimal_places_match_rounding(
+        #[case] decimal_places: u8,
+        #[case] point: [f64; 2],
+        #[case] expected: [f64; 2],
+    ) {
+        let settings = SnapSettings::from_decimal_places(decimal_places);
+        assert_eq!(settings.snap(point, &[], None), expected);
+        assert_eq!(settings.grid.decimal_places(), Some(decimal_places));
+    }
+
+    #[rstest]
+    #[case([0.5, 2.0], [0.25, 1.0], [1.1, 3.4], [1.25, 3.0])]
+    #[case([0.5, 0.0], [0.0, 0.0], [1.1, 3.4], [1.0, 3.4])]
+    #[case([2.0, 2.0], [1.0, 1.0], [-0.2, 0.2], [-1.0, 1.0])]
+    fn grid_snap(
+        #[case] step: [f64; 2],
+        #[case] origin: [f64; 2],
+        #[case] point: [f64; 2],
+        #[case] expected: [f64; 2],
+    ) {
+        let grid = GridSnap { step, origin };
+        assert_eq!(grid.snap(point), expected);
+    }
+
+    #[test]
+    fn polar_snap() {
+        let polar = PolarSnap {
+            origin: [1.0, 1.0],
+            radial_step: 1.0,
+            angle_divisions: 4,
+        };
+        let [x, y] = polar.snap([1.1, 3.2]);
+        assert!((x - 1.0).abs() < 1e-9, "x: {x}");
+        assert!((y - 3.0).abs() < 1e-9, "y: {y}");
+    }
+
+    #[test]
+    fn existing_points_snap_only_within_distance() {
+        let existing = [[1.0, 5.0], [3.0, 10.0]];
+        let snap = ExistingPointsSnap {
+            should_snap_x: true,
+            should_snap_y: true,
+            max_distance: 0.5,
+        };
+        assert_eq!(snap.snap([2.8, 7.0], &existing), [3.0, 7.0]);
+    }
+
+    #[test]
+    fn disabled_does_not_change_point() {
+        let settings = SnapSettings::default();
+        assert_eq!(settings.snap([0.123, 4.567], &[], None), [0.123, 4.567]);
+    }
+
+    #[test]
+    fn grid_lines_within_bounds() {
+        let settings = SnapSettings::from_decimal_places(0);
+        let lines = settings.grid_lines(MinMaxPair {
+            min: [-1.5, -0.5],
+            max: [1.5, 0.5],
+        });
+        assert_eq!(lines.len(), 3 + 1); // x = -1, 0, 1 and y = 0
+    }
+}
@@ -8,6 +8,7 @@ pub struct StatusMsg {
     msg: String,
 }
 impl StatusMsg {
+    /// Adds `msg` on a new line after any messages already shown
     pub fn add_msg(&mut self, msg: &str) {
         if !self.msg.is_empty() {
             self.msg.push('\n');
@@ -15,19 +16,23 @@ impl StatusMsg {
         self.msg.push_str(msg);
     }
 
+    /// Adds `msg` like [`Self::add_msg`] and also logs it as an error
     pub fn add_err(&mut self, msg: &str) {
         error!("{msg}");
         self.add_msg(msg);
     }
 
+    /// All messages separated by new lines
     pub fn get_msg(&self) -> &str {
         &self.msg
     }
 
+    /// Removes all messages
     pub fn clear(&mut self) {
         self.msg.clear()
     }
 
+    /// Returns `true` if there are no messages
     pub fn is_empty(&self) -> bool {
         self.msg.is_empty()
     }
@@ -0,0 +1,66 @@
+//! Areas of the plane, used to describe what part of the data the plot shows
+
+use std::fmt::Debug;
+
+use super::data_definition::DataLabel;
+
+/// What the plot should be zoomed to fit
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ZoomTarget {
+    /// Every point
+    AllPoints,
+    /// The selected points
+    Selection,
+    /// The points with this label
+    Label(DataLabel),
+    /// Index into the bookmarks stored with the data
+    Bookmark(usize),
+}
+
+/// Axis aligned rectangle given by its lowest and highest corners
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Copy, Clone)]
+pub struct MinMaxPair {
+    /// Lowest x and y
+    pub min: [f64; 2],
+    /// Highest x and y
+    pub max: [f64; 2],
+}
+
+impl MinMaxPair {
+    /// Returns true if `self` can fit in `outer_bounds`
+    pub fn is_contained(&self, outer_bounds: &MinMaxPair) -> bool {
+        fn is_less_equal(a: f64, b: f64) -> bool {
+            // Return true if a <= b
+            a - b <= f64::EPSILON
+        }
+        is_less_equal(outer_bounds.min[0], self.min[0])
+            && is_less_equal(outer_bounds.min[1], self.min[1])
+            && is_less_equal(self.max[0], outer_bounds.max[0])
+            && is_less_equal(self.max[1], outer_bounds.max[1])
+    }
+}
+
+impl MinMaxPair {
+    /// Returns the bounds `t` of the way from `self` to `other` (`t` of 0 is `self` and 1 is `other`)
+    pub fn interpolate(&self, other: &MinMaxPair, t: f64) -> MinMaxPair {
+        fn lerp(a: f64, b: f64, t: f64) -> f64 {
+            a + (b - a) * t
+        }
+        MinMaxPair {
+            min: [
+                lerp(self.min[0], other.min[0], t),
+                lerp(self.min[1], other.min[1], t),
+            ],
+            max: [
+                lerp(self.max[0], other.max[0], t),
+                lerp(self.max[1], other.max[1], t),
+            ],
+        }
+    }
+}
+
+impl Debug for MinMaxPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "min: {:.02?}, max: {:.02?}", self.min, self.max)
+    }
+}
@@ -1,4 +1,9 @@
+//! Creating labelled datasets by clicking on a plot, see [`dataset`] for using them without the GUI
+
 #![warn(clippy::all, rust_2018_idioms)]
 
+#[cfg(feature = "gui")]
 mod app;
+pub mod dataset;
+#[cfg(feature = "gui")]
 pub use app::ManualDataCreatorApp;